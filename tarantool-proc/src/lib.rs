@@ -6,6 +6,7 @@ use syn::{
     ItemFn, Signature, Token,
 };
 
+mod space_format;
 mod test;
 
 /// Mark a function as a test.
@@ -217,6 +218,15 @@ pub fn derive_encode(input: TokenStream) -> TokenStream {
     expanded.into()
 }
 
+/// Macro to automatically derive `tarantool::space::SpaceFormat`.
+/// Deriving this trait generates the space format from the struct definition.
+///
+/// See `tarantool::space::SpaceFormat` doc-comments in tarantool crate for details.
+#[proc_macro_derive(SpaceFormat, attributes(space_format))]
+pub fn derive_space_format(input: TokenStream) -> TokenStream {
+    space_format::impl_derive(input)
+}
+
 #[proc_macro]
 pub fn impl_tuple_encode(_input: TokenStream) -> TokenStream {
     let mut impls = vec![];
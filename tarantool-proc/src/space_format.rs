@@ -0,0 +1,394 @@
+use darling::{ast, util::Flag, FromDeriveInput, FromField};
+use proc_macro::TokenStream as TS1;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{parse_macro_input, spanned::Spanned, DeriveInput};
+
+use crate::imp;
+
+/// Field types supported by tarantool's space format, see
+/// `tarantool::space::FieldType`.
+const FIELD_TYPES: &[(&str, &str)] = &[
+    ("any", "Any"),
+    ("unsigned", "Unsigned"),
+    ("string", "String"),
+    ("number", "Number"),
+    ("double", "Double"),
+    ("integer", "Integer"),
+    ("boolean", "Boolean"),
+    ("varbinary", "Varbinary"),
+    ("scalar", "Scalar"),
+    ("decimal", "Decimal"),
+    ("uuid", "Uuid"),
+    ("datetime", "Datetime"),
+    ("interval", "Interval"),
+    ("array", "Array"),
+    ("map", "Map"),
+];
+
+#[derive(FromDeriveInput)]
+#[darling(attributes(space_format), forward_attrs(serde), supports(struct_named))]
+struct Args {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    attrs: Vec<syn::Attribute>,
+    data: ast::Data<(), FieldArgs>,
+    /// Path to tarantool crate
+    tarantool: Option<String>,
+}
+
+#[derive(FromField)]
+#[darling(attributes(space_format), forward_attrs(serde))]
+struct FieldArgs {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    attrs: Vec<syn::Attribute>,
+    /// Explicit field type, overrides the one deduced from the rust type.
+    field_type: Option<syn::LitStr>,
+    /// Explicit nullability, overrides the one deduced from the rust type.
+    is_nullable: Option<bool>,
+    /// Field is a part of the primary key. Parts are added in the order of
+    /// fields in the struct.
+    primary_key: Flag,
+}
+
+pub fn impl_derive(input: TS1) -> TS1 {
+    let input = parse_macro_input!(input as DeriveInput);
+    let args = match Args::from_derive_input(&input) {
+        Ok(args) => args,
+        Err(e) => return e.write_errors().into(),
+    };
+    match expand(args) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(args: Args) -> syn::Result<TokenStream2> {
+    let tarantool = match &args.tarantool {
+        Some(path) => syn::parse_str(path)?,
+        None => imp::path_from_ts2(quote! { ::tarantool }),
+    };
+    for name in UNSUPPORTED_CONTAINER_ATTRS {
+        if let Some(meta) = serde_meta(&args.attrs, name)? {
+            return Err(unsupported(&meta, name));
+        }
+    }
+    let rename_all = match serde_attr(&args.attrs, "rename_all")? {
+        Some(lit) => Some(RenameRule::from_lit(&lit)?),
+        None => None,
+    };
+    let fields = args
+        .data
+        .take_struct()
+        .ok_or_else(|| {
+            syn::Error::new(
+                args.ident.span(),
+                "only structs with named fields are supported",
+            )
+        })?
+        .fields;
+
+    let mut format = Vec::with_capacity(fields.len());
+    let mut key_parts = Vec::new();
+    for field in &fields {
+        for name in UNSUPPORTED_FIELD_ATTRS {
+            if let Some(meta) = serde_meta(&field.attrs, name)? {
+                return Err(unsupported(&meta, name));
+            }
+        }
+        // Fields which are never serialized are not a part of the tuple.
+        let mut skip = None;
+        for name in ["skip", "skip_serializing"] {
+            skip = skip.or(serde_meta(&field.attrs, name)?);
+        }
+        if let Some(meta) = skip {
+            if field.primary_key.is_present() {
+                return Err(syn::Error::new(
+                    meta.span(),
+                    "skipped field can't be a part of the primary key",
+                ));
+            }
+            continue;
+        }
+
+        // The field names must be the same as the ones used by serde,
+        // otherwise the format wouldn't match the encoded tuples.
+        let name = match serde_attr(&field.attrs, "rename")? {
+            Some(lit) => lit.value(),
+            None => {
+                let ident = field.ident.as_ref().ok_or_else(|| {
+                    syn::Error::new(field.ty.span(), "only named fields are supported")
+                })?;
+                let name = ident.to_string().trim_start_matches("r#").to_owned();
+                match &rename_all {
+                    Some(rule) => rule.apply(&name),
+                    None => name,
+                }
+            }
+        };
+        let ty = &field.ty;
+        let field_type = match &field.field_type {
+            Some(lit) => {
+                let variant = FIELD_TYPES
+                    .iter()
+                    .find(|(s, _)| *s == lit.value())
+                    .map(|(_, v)| syn::Ident::new(v, lit.span()))
+                    .ok_or_else(|| {
+                        syn::Error::new(lit.span(), format!("unknown field type '{}'", lit.value()))
+                    })?;
+                quote! { #tarantool::space::FieldType::#variant }
+            }
+            None => quote_spanned! {ty.span()=>
+                <#ty as #tarantool::space::KnownFieldType>::FIELD_TYPE
+            },
+        };
+        let is_nullable = match field.is_nullable {
+            Some(v) => quote! { #v },
+            None if field.field_type.is_some() => quote! { false },
+            None => quote_spanned! {ty.span()=>
+                <#ty as #tarantool::space::KnownFieldType>::IS_NULLABLE
+            },
+        };
+        format.push(quote! {
+            #tarantool::space::Field {
+                name: ::std::string::String::from(#name),
+                field_type: #field_type,
+                is_nullable: #is_nullable,
+            }
+        });
+        if field.primary_key.is_present() {
+            key_parts.push(quote! { #tarantool::index::Part::from(#name) });
+        }
+    }
+
+    let primary_key = if key_parts.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn primary_key() -> ::std::option::Option<::std::vec::Vec<#tarantool::index::Part>> {
+                ::std::option::Option::Some(::std::vec![#(#key_parts),*])
+            }
+        }
+    };
+
+    let name = &args.ident;
+    let (impl_generics, ty_generics, where_clause) = args.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #tarantool::space::SpaceFormat for #name #ty_generics #where_clause {
+            fn format() -> ::std::vec::Vec<#tarantool::space::Field> {
+                ::std::vec![#(#format),*]
+            }
+
+            #primary_key
+        }
+    })
+}
+
+/// Serde container attributes which change the layout of the encoded tuple
+/// in a way the space format can't describe.
+const UNSUPPORTED_CONTAINER_ATTRS: &[&str] = &["transparent", "tag", "into"];
+
+/// Serde field attributes which make the number of fields in the encoded
+/// tuple differ from the one in the space format.
+const UNSUPPORTED_FIELD_ATTRS: &[&str] = &["flatten", "skip_serializing_if"];
+
+fn unsupported(meta: &syn::Meta, name: &str) -> syn::Error {
+    syn::Error::new(
+        meta.span(),
+        format!("`#[serde({})]` is not supported by `SpaceFormat`", name),
+    )
+}
+
+/// Returns the `#[serde(name...)]` attribute if any.
+fn serde_meta(attrs: &[syn::Attribute], name: &str) -> syn::Result<Option<syn::Meta>> {
+    for attr in attrs {
+        let list = match attr.parse_meta()? {
+            syn::Meta::List(list) => list,
+            _ => continue,
+        };
+        for nested in list.nested {
+            match nested {
+                syn::NestedMeta::Meta(meta) if meta.path().is_ident(name) => {
+                    return Ok(Some(meta));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the value of `#[serde(name = "value")]` or
+/// `#[serde(name(serialize = "value"))]` attribute if any.
+fn serde_attr(attrs: &[syn::Attribute], name: &str) -> syn::Result<Option<syn::LitStr>> {
+    match serde_meta(attrs, name)? {
+        Some(syn::Meta::NameValue(nv)) => lit_str(nv.lit).map(Some),
+        Some(syn::Meta::List(list)) => {
+            for nested in list.nested {
+                match nested {
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(nv))
+                        if nv.path.is_ident("serialize") =>
+                    {
+                        return lit_str(nv.lit).map(Some);
+                    }
+                    _ => {}
+                }
+            }
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
+
+fn lit_str(lit: syn::Lit) -> syn::Result<syn::LitStr> {
+    match lit {
+        syn::Lit::Str(lit) => Ok(lit),
+        lit => Err(syn::Error::new(lit.span(), "expected a string literal")),
+    }
+}
+
+/// Subset of serde's `rename_all` rules applicable to snake_case field names.
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_lit(lit: &syn::LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" | "snake_case" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            other => {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    format!("unknown rename rule '{}'", other),
+                ))
+            }
+        })
+    }
+
+    fn apply(&self, field: &str) -> String {
+        match self {
+            Self::Lower => field.to_owned(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal | Self::Camel => {
+                let mut res = String::with_capacity(field.len());
+                let mut capitalize = matches!(self, Self::Pascal);
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        res.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        res.push(c);
+                    }
+                }
+                res
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_str(input: DeriveInput) -> syn::Result<String> {
+        let args = Args::from_derive_input(&input)?;
+        expand(args).map(|ts| ts.to_string())
+    }
+
+    fn expand_err(input: DeriveInput) -> String {
+        expand_str(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn skipped_fields() {
+        let ts = expand_str(syn::parse_quote! {
+            struct S {
+                a: u32,
+                #[serde(skip)]
+                b: u32,
+                #[serde(skip_serializing)]
+                c: u32,
+                #[serde(skip_deserializing)]
+                d: u32,
+            }
+        })
+        .unwrap();
+        assert!(ts.contains(r#"from ("a")"#));
+        assert!(!ts.contains(r#"from ("b")"#));
+        assert!(!ts.contains(r#"from ("c")"#));
+        assert!(ts.contains(r#"from ("d")"#));
+
+        let err = expand_err(syn::parse_quote! {
+            struct S {
+                #[space_format(primary_key)]
+                #[serde(skip)]
+                a: u32,
+            }
+        });
+        assert_eq!(err, "skipped field can't be a part of the primary key");
+    }
+
+    #[test]
+    fn flatten() {
+        let err = expand_err(syn::parse_quote! {
+            struct S {
+                #[serde(flatten)]
+                a: Inner,
+            }
+        });
+        assert_eq!(err, "`#[serde(flatten)]` is not supported by `SpaceFormat`");
+    }
+
+    #[test]
+    fn skip_serializing_if() {
+        let err = expand_err(syn::parse_quote! {
+            struct S {
+                #[serde(skip_serializing_if = "Option::is_none")]
+                a: Option<u32>,
+            }
+        });
+        assert_eq!(
+            err,
+            "`#[serde(skip_serializing_if)]` is not supported by `SpaceFormat`"
+        );
+    }
+
+    #[test]
+    fn container_attrs() {
+        let err = expand_err(syn::parse_quote! {
+            #[serde(transparent)]
+            struct S {
+                a: u32,
+            }
+        });
+        assert_eq!(
+            err,
+            "`#[serde(transparent)]` is not supported by `SpaceFormat`"
+        );
+
+        let err = expand_err(syn::parse_quote! {
+            #[serde(tag = "type")]
+            struct S {
+                a: u32,
+            }
+        });
+        assert_eq!(err, "`#[serde(tag)]` is not supported by `SpaceFormat`");
+    }
+}
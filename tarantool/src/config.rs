@@ -0,0 +1,326 @@
+//! Box: configuration
+//!
+//! Typed counterpart of `box.cfg{...}`. Only the options which are set in a
+//! [`BoxCfg`] are passed to tarantool, the rest keep their current values.
+//!
+//! ```no_run
+//! use tarantool::config::{BoxCfg, WalMode};
+//!
+//! let cfg = BoxCfg {
+//!     listen: Some("127.0.0.1:3301".into()),
+//!     memtx_memory: Some(256 * 1024 * 1024),
+//!     wal_mode: Some(WalMode::Write),
+//!     ..Default::default()
+//! };
+//! cfg.apply().unwrap();
+//!
+//! let current = BoxCfg::current().unwrap();
+//! assert_eq!(current.memtx_memory, Some(256 * 1024 * 1024));
+//! ```
+//!
+//! See also:
+//! - [Lua reference: Configuration reference](https://www.tarantool.io/en/doc/latest/reference/configuration/)
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::util::NumOrStr;
+
+crate::define_str_enum! {
+    /// Write-ahead log mode, see [`BoxCfg::wal_mode`].
+    pub enum WalMode {
+        /// Write-ahead log is not maintained.
+        None = "none",
+        /// Fibers wait for their data to be written to the write-ahead log.
+        Write = "write",
+        /// Fibers wait for their data, `fsync` follows each write.
+        Fsync = "fsync",
+    }
+}
+
+crate::define_str_enum! {
+    /// Leader election mode, see [`BoxCfg::election_mode`].
+    pub enum ElectionMode {
+        Off = "off",
+        Voter = "voter",
+        Candidate = "candidate",
+        Manual = "manual",
+    }
+}
+
+crate::define_str_enum! {
+    /// Format of the log messages, see [`BoxCfg::log_format`].
+    pub enum LogFormat {
+        Plain = "plain",
+        Json = "json",
+    }
+}
+
+/// Value of [`BoxCfg::listen`] or an entry of [`BoxCfg::replication`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Listen {
+    /// Port number.
+    Port(u16),
+    /// URI, e.g. `"127.0.0.1:3301"` or `"/tmp/tarantool.sock"`.
+    Uri(String),
+    /// Any other value, e.g. a table with `uri` and `params` or a list of
+    /// URIs, which is passed to `box.cfg` as is.
+    Other(rmpv::Value),
+}
+
+impl From<u16> for Listen {
+    #[inline(always)]
+    fn from(port: u16) -> Self {
+        Self::Port(port)
+    }
+}
+
+impl From<String> for Listen {
+    #[inline(always)]
+    fn from(uri: String) -> Self {
+        Self::Uri(uri)
+    }
+}
+
+impl From<&str> for Listen {
+    #[inline(always)]
+    fn from(uri: &str) -> Self {
+        Self::Uri(uri.into())
+    }
+}
+
+/// Instance configuration, see
+/// [box.cfg](https://www.tarantool.io/en/doc/latest/reference/configuration/).
+///
+/// Every option is optional, `None` means the option is not passed to
+/// `box.cfg` when the configuration is [applied](BoxCfg::apply). Options
+/// which don't have a dedicated field can be passed via [`BoxCfg::other`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoxCfg {
+    // Basic parameters
+    /// URI or port to listen on for binary protocol connections.
+    pub listen: Option<Listen>,
+    pub background: Option<bool>,
+    pub custom_proc_title: Option<String>,
+    pub pid_file: Option<String>,
+    pub read_only: Option<bool>,
+    pub username: Option<String>,
+    pub work_dir: Option<String>,
+    pub memtx_dir: Option<String>,
+    pub vinyl_dir: Option<String>,
+    pub wal_dir: Option<String>,
+    pub instance_uuid: Option<String>,
+    pub replicaset_uuid: Option<String>,
+    pub too_long_threshold: Option<f64>,
+    pub net_msg_max: Option<u64>,
+    pub readahead: Option<u64>,
+
+    // Storage
+    /// Memory in bytes allocated for memtx tuples.
+    pub memtx_memory: Option<u64>,
+    pub memtx_min_tuple_size: Option<u64>,
+    pub memtx_max_tuple_size: Option<u64>,
+    pub slab_alloc_factor: Option<f64>,
+    /// Memory in bytes allocated for vinyl in-memory storage.
+    pub vinyl_memory: Option<u64>,
+    pub vinyl_cache: Option<u64>,
+    pub vinyl_max_tuple_size: Option<u64>,
+    pub vinyl_read_threads: Option<u32>,
+    pub vinyl_write_threads: Option<u32>,
+    pub vinyl_timeout: Option<f64>,
+
+    // Checkpoints & write-ahead log
+    pub checkpoint_interval: Option<f64>,
+    pub checkpoint_count: Option<u64>,
+    pub checkpoint_wal_threshold: Option<u64>,
+    pub wal_mode: Option<WalMode>,
+    pub wal_max_size: Option<u64>,
+    pub wal_dir_rescan_delay: Option<f64>,
+    pub force_recovery: Option<bool>,
+
+    // Replication
+    /// URIs of the replication sources.
+    pub replication: Option<Vec<Listen>>,
+    pub replication_anon: Option<bool>,
+    pub replication_connect_quorum: Option<u32>,
+    pub replication_connect_timeout: Option<f64>,
+    pub replication_skip_conflict: Option<bool>,
+    pub replication_sync_lag: Option<f64>,
+    pub replication_sync_timeout: Option<f64>,
+    pub replication_timeout: Option<f64>,
+    /// Either a number or a formula, e.g. `"N / 2 + 1"`.
+    pub replication_synchro_quorum: Option<NumOrStr>,
+    pub replication_synchro_timeout: Option<f64>,
+    pub election_mode: Option<ElectionMode>,
+    pub election_timeout: Option<f64>,
+
+    // Logging
+    /// Log destination, e.g. a file name, `pipe: ...` or `syslog: ...`.
+    pub log: Option<String>,
+    /// Log level, see [`SayLevel`](crate::log::SayLevel) for values.
+    pub log_level: Option<u8>,
+    pub log_nonblock: Option<bool>,
+    pub log_format: Option<LogFormat>,
+
+    /// Any other options, passed to `box.cfg` as is.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, rmpv::Value>,
+}
+
+impl BoxCfg {
+    /// Apply the configuration, i.e. call `box.cfg` with all of the options
+    /// which are set. The first call initializes the database, subsequent
+    /// calls change the dynamic options.
+    #[inline]
+    pub fn apply(&self) -> Result<(), Error> {
+        let data = rmp_serde::to_vec_named(self)?;
+        crate::lua_state()
+            .exec_with(
+                "local msgpack = require('msgpack')
+                local cfg = {}
+                for k, v in pairs(msgpack.decode(...)) do
+                    if v ~= nil then cfg[k] = v end
+                end
+                box.cfg(cfg)",
+                tlua::AnyLuaString(data),
+            )
+            .map_err(tlua::LuaError::from)?;
+        Ok(())
+    }
+
+    /// Read the current effective configuration. Returns an error if `box.cfg`
+    /// has not been called yet, see [`is_configured`].
+    #[inline]
+    pub fn current() -> Result<Self, Error> {
+        crate::util::lua_eval_decode(
+            "(function()
+                if type(box.cfg) == 'function' then
+                    error('box.cfg() was not called yet')
+                end
+                local levels = {
+                    fatal = 0, syserror = 1, error = 2, crit = 3,
+                    warn = 4, info = 5, verbose = 6, debug = 7,
+                }
+                local cfg = {}
+                for k, v in pairs(box.cfg) do cfg[k] = v end
+                local replication = cfg.replication
+                if replication ~= nil and
+                    (type(replication) ~= 'table' or replication.uri ~= nil) then
+                    cfg.replication = {replication}
+                end
+                if type(cfg.log_level) == 'string' then
+                    cfg.log_level = levels[cfg.log_level]
+                end
+                return cfg
+            end)()",
+        )
+    }
+}
+
+/// Returns `true` if `box.cfg` has already been called.
+#[inline]
+pub fn is_configured() -> bool {
+    crate::lua_state()
+        .eval("return type(box.cfg) ~= 'function'")
+        .unwrap_or(false)
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+
+    #[crate::test(tarantool = "crate")]
+    fn read_and_apply() {
+        assert!(is_configured());
+
+        let cfg = BoxCfg::current().unwrap();
+        assert!(cfg.memtx_memory.is_some());
+        assert!(cfg.wal_mode.is_some());
+        assert!(cfg.log_level.is_some());
+        assert_eq!(cfg.other.get("listen"), None);
+
+        let listen = rmpv::Value::Map(vec![
+            ("uri".into(), "localhost:3301".into()),
+            ("params".into(), rmpv::Value::Map(vec![])),
+        ]);
+        let data = rmp_serde::to_vec(&listen).unwrap();
+        let decoded: Listen = rmp_serde::from_slice(&data).unwrap();
+        assert_eq!(decoded, Listen::Other(listen));
+        let data = rmp_serde::to_vec(&3301).unwrap();
+        let decoded: Listen = rmp_serde::from_slice(&data).unwrap();
+        assert_eq!(decoded, Listen::Port(3301));
+
+        // Replication sources can be given in the table form too.
+        let source = rmpv::Value::Map(vec![
+            ("uri".into(), "localhost:3302".into()),
+            ("params".into(), rmpv::Value::Map(vec![])),
+        ]);
+        let cfg_value = rmpv::Value::Map(vec![(
+            "replication".into(),
+            rmpv::Value::Array(vec!["localhost:3301".into(), source.clone()]),
+        )]);
+        let data = rmp_serde::to_vec(&cfg_value).unwrap();
+        let decoded: BoxCfg = rmp_serde::from_slice(&data).unwrap();
+        assert_eq!(
+            decoded.replication,
+            Some(vec![
+                Listen::Uri("localhost:3301".into()),
+                Listen::Other(source)
+            ])
+        );
+
+        let old_threshold = cfg.too_long_threshold.unwrap();
+        BoxCfg {
+            too_long_threshold: Some(old_threshold + 1.0),
+            ..Default::default()
+        }
+        .apply()
+        .unwrap();
+        let new_cfg = BoxCfg::current().unwrap();
+        assert_eq!(new_cfg.too_long_threshold, Some(old_threshold + 1.0));
+        assert_eq!(new_cfg.memtx_memory, cfg.memtx_memory);
+
+        // Options without dedicated fields are passed as is.
+        let threads = cfg.other["worker_pool_threads"].as_u64().unwrap();
+        let mut other = BTreeMap::new();
+        other.insert("worker_pool_threads".into(), (threads + 1).into());
+        BoxCfg {
+            other,
+            ..Default::default()
+        }
+        .apply()
+        .unwrap();
+        let new_cfg = BoxCfg::current().unwrap();
+        assert_eq!(
+            new_cfg.other["worker_pool_threads"],
+            rmpv::Value::from(threads + 1)
+        );
+
+        let mut other = BTreeMap::new();
+        other.insert("worker_pool_threads".into(), threads.into());
+        BoxCfg {
+            too_long_threshold: Some(old_threshold),
+            other,
+            ..Default::default()
+        }
+        .apply()
+        .unwrap();
+        let new_cfg = BoxCfg::current().unwrap();
+        assert_eq!(new_cfg.too_long_threshold, Some(old_threshold));
+        assert_eq!(
+            new_cfg.other["worker_pool_threads"],
+            rmpv::Value::from(threads)
+        );
+
+        let err = BoxCfg {
+            memtx_memory: Some(1),
+            ..Default::default()
+        }
+        .apply()
+        .unwrap_err();
+        assert!(matches!(err, Error::LuaError(_)), "{}", err);
+    }
+}
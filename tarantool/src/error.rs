@@ -84,6 +84,14 @@ pub enum Error {
 
     #[error("space metadata not found")]
     MetaNotFound,
+
+    /// The current fiber was cancelled, returned from [`fiber::r#yield`],
+    /// [`fiber::check_cancelled`] & co.
+    ///
+    /// [`fiber::r#yield`]: crate::fiber::r#yield
+    /// [`fiber::check_cancelled`]: crate::fiber::check_cancelled
+    #[error("fiber is cancelled")]
+    FiberCancelled,
 }
 
 impl From<rmp_serde::encode::Error> for Error {
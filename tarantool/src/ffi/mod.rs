@@ -58,3 +58,42 @@ pub fn has_tuple_field_by_path() -> bool {
 pub fn has_datetime() -> bool {
     unsafe { helper::has_dyn_symbol(crate::c_str!("tnt_mp_encode_datetime")) }
 }
+
+/// Check whether the current tarantool executable supports iterator positions
+/// (available since tarantool 2.11).
+/// If this function returns `false` using [`Index::select_after`],
+/// [`Index::select_page`], [`Index::tuple_position`] or
+/// [`IndexIterator::position`] will result in a **panic**.
+///
+/// [`Index::select_after`]: crate::index::Index::select_after
+/// [`Index::select_page`]: crate::index::Index::select_page
+/// [`Index::tuple_position`]: crate::index::Index::tuple_position
+/// [`IndexIterator::position`]: crate::index::IndexIterator::position
+pub fn has_iterator_position() -> bool {
+    unsafe {
+        let name = crate::c_str!("box_index_iterator_after");
+        helper::tnt_internal_symbol::<*const ()>(name).is_some() || helper::has_dyn_symbol(name)
+    }
+}
+
+/// Check whether the current tarantool executable supports the fiber
+/// introspection api (`fiber_id`, `fiber_csw`, `fiber_find`, `fiber_name` &
+/// `fiber_set_name_n`).
+/// If this function returns `false` functions like [`fiber::id`] and
+/// [`fiber::csw`] fall back to the slower lua implementation.
+///
+/// [`fiber::id`]: crate::fiber::id
+/// [`fiber::csw`]: crate::fiber::csw
+pub fn has_fiber_id() -> bool {
+    static mut RESULT: Option<bool> = None;
+    unsafe {
+        if let Some(res) = RESULT {
+            return res;
+        }
+        let name = crate::c_str!("fiber_id");
+        let res = helper::tnt_internal_symbol::<*const ()>(name).is_some()
+            || helper::has_dyn_symbol(name);
+        RESULT = Some(res);
+        res
+    }
+}
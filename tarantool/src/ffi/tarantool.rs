@@ -302,6 +302,41 @@ extern "C" {
     pub fn fiber_cond_wait(cond: *mut FiberCond) -> c_int;
 }
 
+crate::define_dlsym_reloc! {
+    /// Return the id of the fiber `f` or of the current fiber if `f` is null.
+    ///
+    /// Not available in older tarantool versions, see
+    /// [`has_fiber_id`](crate::ffi::has_fiber_id).
+    pub fn fiber_id(f: *const Fiber) -> u64;
+
+    /// Return the number of context switches of the fiber `f` or of the
+    /// current fiber if `f` is null.
+    ///
+    /// Not available in older tarantool versions, see
+    /// [`has_fiber_id`](crate::ffi::has_fiber_id).
+    pub fn fiber_csw(f: *const Fiber) -> u64;
+
+    /// Find a fiber by its id, returns null if there's no such fiber.
+    ///
+    /// Not available in older tarantool versions, see
+    /// [`has_fiber_id`](crate::ffi::has_fiber_id).
+    pub fn fiber_find(fid: u64) -> *mut Fiber;
+
+    /// Return the name of the fiber `f` or of the current fiber if `f` is
+    /// null. The name is a nul-terminated string owned by the fiber.
+    ///
+    /// Not available in older tarantool versions, see
+    /// [`has_fiber_id`](crate::ffi::has_fiber_id).
+    pub fn fiber_name(f: *const Fiber) -> *const c_char;
+
+    /// Set the name of the fiber `f` or of the current fiber if `f` is null.
+    /// The name is truncated if it's too long.
+    ///
+    /// Not available in older tarantool versions, see
+    /// [`has_fiber_id`](crate::ffi::has_fiber_id).
+    pub fn fiber_set_name_n(f: *mut Fiber, name: *const c_char, len: u32);
+}
+
 /// list entry and head structure
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -758,6 +793,51 @@ extern "C" {
     pub fn box_iterator_free(iterator: *mut BoxIterator);
 }
 
+crate::define_dlsym_reloc! {
+    /// Allocate and initialize iterator for index, which starts right after
+    /// the tuple described by the position `packed_pos`. Passing a null
+    /// position is equivalent to calling [`box_index_iterator`].
+    ///
+    /// Available since tarantool 2.11.
+    pub fn box_index_iterator_after(
+        space_id: u32,
+        index_id: u32,
+        type_: c_int,
+        key: *const c_char,
+        key_end: *const c_char,
+        packed_pos: *const c_char,
+        packed_pos_end: *const c_char,
+    ) -> *mut BoxIterator;
+
+    /// Return the position of the last tuple returned by the iterator. The
+    /// position is allocated on the box region.
+    ///
+    /// Returns 0 on success and -1 on error.
+    ///
+    /// Available since tarantool 2.11.
+    pub fn box_iterator_position(
+        iterator: *mut BoxIterator,
+        packed_pos: *mut *const c_char,
+        packed_pos_end: *mut *const c_char,
+    ) -> c_int;
+
+    /// Return the position of the `tuple` in the index. The tuple doesn't
+    /// have to be present in the index. The position is allocated on the box
+    /// region.
+    ///
+    /// Returns 0 on success and -1 on error.
+    ///
+    /// Available since tarantool 2.11.
+    pub fn box_index_tuple_position(
+        space_id: u32,
+        index_id: u32,
+        tuple: *const c_char,
+        tuple_end: *const c_char,
+        packed_pos: *mut *const c_char,
+        packed_pos_end: *mut *const c_char,
+    ) -> c_int;
+}
+
 #[cfg(not(feature = "picodata"))]
 #[repr(C)]
 pub struct BoxTuple {
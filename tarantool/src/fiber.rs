@@ -2,21 +2,29 @@
 //!
 //! With the fiber module, you can:
 //! - create, run and manage [fibers](struct.Fiber.html),
+//! - inspect the running fibers (see [`current`], [`find`] & [`info`]),
+//! - store per fiber values (see [`fiber_local!`](crate::fiber_local)),
+//! - execute jobs on a [`Pool`] of worker fibers with bounded concurrency,
+//! - wait on several [channels](Channel) at once (see [`select!`](macro@select)),
 //! - use a synchronization mechanism for fibers, similar to “condition variables” and similar to operating-system
 //! functions such as `pthread_cond_wait()` plus `pthread_cond_signal()`,
+//! - use the synchronization primitives built on top of it: [`Mutex`],
+//!   [`RwLock`], [`Semaphore`], [`Barrier`] & [`Once`],
 //! - spawn a fiber based [async runtime](async).
 //!
 //! See also:
 //! - [Threads, fibers and yields](https://www.tarantool.io/en/doc/latest/book/box/atomic/#threads-fibers-and-yields)
 //! - [Lua reference: Module fiber](https://www.tarantool.io/en/doc/latest/reference/reference_lua/fiber/)
 //! - [C API reference: Module fiber](https://www.tarantool.io/en/doc/latest/dev_guide/reference_capi/fiber/)
-use std::cell::UnsafeCell;
+use std::cell::{Cell, RefCell, UnsafeCell};
 use std::ffi::CString;
 use std::future::Future;
+use std::io;
 use std::marker::PhantomData;
 use std::os::raw::c_void;
 use std::ptr::NonNull;
-use std::time::Duration;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use crate::tlua::{self as tlua, AsLua};
 
@@ -26,10 +34,10 @@ use ::va_list::{VaList, VaPrimitive};
 #[cfg(all(target_arch = "aarch64", target_os = "macos"))]
 use crate::va_list::{VaList, VaPrimitive};
 
-use crate::error::{TarantoolError, TarantoolErrorCode};
+use crate::c_ptr;
+use crate::error::{Error, TarantoolError};
 use crate::ffi::{lua, tarantool as ffi};
 use crate::Result;
-use crate::{c_ptr, set_error};
 
 pub mod r#async;
 pub mod channel;
@@ -41,13 +49,40 @@ pub use channel::{
 pub mod mutex;
 use crate::ffi::tarantool::fiber_sleep;
 pub use mutex::Mutex;
+
+pub mod pool;
+pub use pool::Pool;
+
+pub mod select;
+pub use crate::fiber_select as select;
+pub use select::{Select, SelectedOperation};
+
+pub mod barrier;
+pub mod once;
+pub mod rwlock;
+pub mod semaphore;
+pub use barrier::Barrier;
+pub use once::Once;
 pub use r#async::block_on;
+pub use rwlock::RwLock;
+pub use semaphore::Semaphore;
 
 mod csw;
 pub use csw::check_yield;
 pub use csw::csw;
 pub use csw::YieldResult;
 
+mod introspection;
+pub use introspection::{
+    current, find, id, info, name, set_name, FiberId, FiberInfo, FiberMemory, FiberRef,
+};
+
+mod local;
+pub use local::LocalKey;
+
+mod scope;
+pub use scope::{scope, Scope};
+
 macro_rules! impl_debug_stub {
     ($t:ident $($p:tt)*) => {
         impl $($p)* ::std::fmt::Debug for $t $($p)* {
@@ -470,6 +505,7 @@ where
         let a = C::parse_args(args);
         I::before_callee();
         C::invoke(a);
+        local::clear_current();
         0
     }
 }
@@ -562,6 +598,7 @@ where
 
         // call f and drop it afterwards
         let res = f();
+        local::clear_current();
 
         // return results to lua
         C::save_result(l, res)
@@ -572,6 +609,11 @@ where
 /// LuaJoinHandle
 ////////////////////////////////////////////////////////////////////////////////
 
+/// An owned permission to join on a fiber created with the lua api, see
+/// [`defer`].
+///
+/// Unlike [`JoinHandle`] it can't be cancelled and panics if dropped without
+/// being joined.
 #[derive(PartialEq, Eq, Hash)]
 pub struct LuaJoinHandle<'f, T> {
     fiber_ref: Option<i32>,
@@ -619,6 +661,11 @@ impl<'f, T> Drop for LuaJoinHandle<'f, T> {
 /// LuaUnitJoinHandle
 ////////////////////////////////////////////////////////////////////////////////
 
+/// An owned permission to join on a fiber created with the lua api, see
+/// [`defer_proc`].
+///
+/// Unlike [`UnitJoinHandle`] it can't be cancelled and panics if dropped
+/// without being joined.
 #[derive(PartialEq, Eq, Hash)]
 pub struct LuaUnitJoinHandle<'f> {
     fiber_ref: Option<i32>,
@@ -940,10 +987,15 @@ where
     F: 'f,
 {
     f: Box<F>,
-    result: Box<UnsafeCell<Option<T>>>,
+    result: FiberResult<T>,
     marker: PhantomData<&'f ()>,
 }
 
+/// The slot for the result of a fiber function. It's shared between the fiber
+/// and its [`JoinHandle`], because the handle of a fiber spawned in a
+/// [`Scope`] can be dropped before the fiber finishes.
+type FiberResult<T> = Rc<UnsafeCell<Option<T>>>;
+
 impl<'f, F, T> Callee for FiberFunc<'f, F, T>
 where
     F: FnOnce() -> T,
@@ -951,22 +1003,27 @@ where
     T: 'f,
 {
     type JoinHandle = JoinHandle<'f, T>;
-    type Args = (Box<F>, *mut Option<T>);
+    type Args = (Box<F>, FiberResult<T>, Rc<FiberState>);
 
     unsafe fn start_fiber(self, inner: NonNull<ffi::Fiber>) -> Self::JoinHandle {
-        let (f, result): Self::Args = (self.f, self.result.get());
-        ffi::fiber_start(inner.as_ptr(), Box::into_raw(f), result);
-        JoinHandle::new(inner, self.result)
+        let state = Rc::new(FiberState::default());
+        let result_ptr = Rc::into_raw(self.result.clone());
+        let state_ptr = Rc::into_raw(state.clone());
+        ffi::fiber_start(inner.as_ptr(), Box::into_raw(self.f), result_ptr, state_ptr);
+        JoinHandle::new(inner, self.result, state)
     }
 
     unsafe fn parse_args(mut args: VaList) -> Self::Args {
         let f = args.get_boxed::<F>();
-        let result = args.get_ptr::<Option<T>>();
-        (f, result)
+        let result = Rc::from_raw(args.get_ptr::<UnsafeCell<Option<T>>>());
+        let state = Rc::from_raw(args.get_ptr::<FiberState>());
+        state.start();
+        (f, result, state)
     }
 
-    unsafe fn invoke((f, result): Self::Args) {
-        std::ptr::write(result, Some(f()))
+    unsafe fn invoke((f, result, state): Self::Args) {
+        *result.get() = Some(f());
+        state.finish();
     }
 }
 
@@ -986,20 +1043,25 @@ where
     F: 'f,
 {
     type JoinHandle = UnitJoinHandle<'f>;
-    type Args = Box<F>;
+    type Args = (Box<F>, Rc<FiberState>);
 
     unsafe fn start_fiber(self, inner: NonNull<ffi::Fiber>) -> Self::JoinHandle {
-        let f: Self::Args = self.f;
-        ffi::fiber_start(inner.as_ptr(), Box::into_raw(f));
-        UnitJoinHandle::new(inner)
+        let state = Rc::new(FiberState::default());
+        let state_ptr = Rc::into_raw(state.clone());
+        ffi::fiber_start(inner.as_ptr(), Box::into_raw(self.f), state_ptr);
+        UnitJoinHandle::new(inner, state)
     }
 
     unsafe fn parse_args(mut args: VaList) -> Self::Args {
-        args.get_boxed::<F>()
+        let f = args.get_boxed::<F>();
+        let state = Rc::from_raw(args.get_ptr::<FiberState>());
+        state.start();
+        (f, state)
     }
 
-    unsafe fn invoke(f: Self::Args) {
-        f()
+    unsafe fn invoke((f, state): Self::Args) {
+        f();
+        state.finish();
     }
 }
 
@@ -1052,14 +1114,152 @@ impl Invocation for Deferred {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// FiberState
+////////////////////////////////////////////////////////////////////////////////
+
+/// State of a fiber started from rust, shared between the fiber, its join
+/// handle and the [`Scope`] it was spawned in.
+#[doc(hidden)]
+#[derive(Default)]
+pub struct FiberState {
+    is_finished: Cell<bool>,
+    is_joined: Cell<bool>,
+    /// The fiber was spawned in a [`Scope`], which cancels and joins it if
+    /// the join handle is dropped.
+    is_scoped: Cell<bool>,
+    /// Id of the fiber, set by [`FiberState::start`] so that it's available
+    /// after the fiber is joined and its `struct fiber` is recycled.
+    id: Cell<Option<FiberId>>,
+    /// Name of the fiber as of the moment the fiber function returned, set
+    /// by [`FiberState::finish`] for the same reason.
+    name: RefCell<Option<String>>,
+    /// Created lazily by the first [`FiberState::wait_finished`] call.
+    cond: RefCell<Option<Rc<Cond>>>,
+}
+
+impl FiberState {
+    /// Called from the fiber before anything else, even before a deferred
+    /// fiber yields.
+    fn start(&self) {
+        self.id.set(Some(id()));
+    }
+
+    /// Returns the id of the fiber.
+    fn id(&self) -> FiberId {
+        // `start` is called before `fiber_start` returns
+        self.id.get().expect("fiber id is set at start")
+    }
+
+    /// Returns the name of the fiber.
+    ///
+    /// # Safety
+    /// `f` must point to the fiber this state belongs to.
+    unsafe fn name(&self, f: NonNull<ffi::Fiber>) -> String {
+        if let Some(name) = &*self.name.borrow() {
+            // The fiber may already be joined, don't touch it
+            return name.clone();
+        }
+        if crate::ffi::has_fiber_id() {
+            introspection::name_of(f.as_ptr())
+        } else {
+            FiberRef::from_id(self.id()).name().unwrap_or_default()
+        }
+    }
+
+    /// Called from the fiber right after the fiber function returns.
+    fn finish(&self) {
+        *self.name.borrow_mut() = Some(name());
+        self.is_finished.set(true);
+        if let Some(cond) = &*self.cond.borrow() {
+            cond.broadcast();
+        }
+    }
+
+    /// Waits until the fiber function returns or the deadline is reached.
+    /// Returns early if the current fiber is cancelled.
+    fn wait_finished(&self, deadline: Option<Instant>) -> bool {
+        if self.is_finished.get() {
+            return true;
+        }
+        let cond = self
+            .cond
+            .borrow_mut()
+            .get_or_insert_with(|| Rc::new(Cond::new()))
+            .clone();
+        while !self.is_finished.get() {
+            match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    cond.wait_timeout(deadline - now);
+                }
+                None => {
+                    cond.wait();
+                }
+            }
+            if is_cancelled() {
+                break;
+            }
+        }
+        self.is_finished.get()
+    }
+
+    /// Same as [`FiberState::wait_finished`], but returns the reason why the
+    /// fiber hasn't finished: [`Error::FiberCancelled`] or a timeout.
+    fn wait_finished_timeout(&self, timeout: Duration) -> Result<()> {
+        if self.wait_finished(Instant::now().checked_add(timeout)) {
+            return Ok(());
+        }
+        if is_cancelled() {
+            return Err(Error::FiberCancelled);
+        }
+        Err(io::Error::from(io::ErrorKind::TimedOut).into())
+    }
+
+    /// Cancels the fiber unless it has already been joined.
+    ///
+    /// # Safety
+    /// `f` must point to the fiber this state belongs to.
+    unsafe fn cancel(&self, f: NonNull<ffi::Fiber>) {
+        if !self.is_joined.get() {
+            ffi::fiber_cancel(f.as_ptr())
+        }
+    }
+
+    /// Joins the fiber. If the fiber is already being joined from another
+    /// place (i.e. by its [`Scope`]), waits until the fiber function returns.
+    ///
+    /// # Safety
+    /// `f` must point to the fiber this state belongs to.
+    unsafe fn join(&self, f: NonNull<ffi::Fiber>) {
+        if !self.is_joined.replace(true) {
+            // TODO: add error handling
+            let _code = ffi::fiber_join(f.as_ptr());
+            return;
+        }
+        while !self.wait_finished(None) {}
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // JoinHandle
 ////////////////////////////////////////////////////////////////////////////////
 
 /// An owned permission to join on an immediate fiber (block on its termination).
+///
+/// If the handle is dropped without being joined, the fiber is
+/// [cancelled](JoinHandle::cancel) and the current fiber waits until it's
+/// finished. Note that the cancellation is cooperative, so the dropping fiber
+/// may wait for a long time if the cancelled fiber doesn't check
+/// [`is_cancelled`]. Fibers spawned in a [`scope`] are cancelled and joined at
+/// the end of the scope instead.
 pub struct JoinHandle<'f, T> {
     inner: Option<NonNull<ffi::Fiber>>,
-    result: Box<UnsafeCell<Option<T>>>,
+    result: FiberResult<T>,
+    state: Rc<FiberState>,
     marker: PhantomData<&'f ()>,
 }
 
@@ -1067,10 +1267,11 @@ impl_debug_stub! {JoinHandle<'f, T>}
 impl_eq_hash! {JoinHandle<'f, T>}
 
 impl<'f, T> JoinHandle<'f, T> {
-    fn new(inner: NonNull<ffi::Fiber>, result: Box<UnsafeCell<Option<T>>>) -> Self {
+    fn new(inner: NonNull<ffi::Fiber>, result: FiberResult<T>, state: Rc<FiberState>) -> Self {
         Self {
             inner: Some(inner),
             result,
+            state,
             marker: PhantomData,
         }
     }
@@ -1079,17 +1280,67 @@ impl<'f, T> JoinHandle<'f, T> {
     pub fn join(mut self) -> T {
         // It's safe to unwrap because join will only be called once after the
         // join handle was created
-        let inner_raw = self.inner.take().unwrap().as_ptr();
-        // TODO: add error handling
-        let _code = unsafe { ffi::fiber_join(inner_raw) };
-        self.result.get_mut().take().unwrap()
+        let inner = self.inner.take().unwrap();
+        unsafe {
+            self.state.join(inner);
+            // The fiber has finished, so nobody else accesses the result
+            (*self.result.get()).take().unwrap()
+        }
+    }
+
+    /// Block until the fiber's termination or until the `timeout` expires.
+    ///
+    /// Returns the fiber's result value or a [`JoinTimeoutError`] holding the
+    /// handle itself if the timeout expired or the current fiber was cancelled
+    /// while waiting.
+    pub fn join_timeout(self, timeout: Duration) -> std::result::Result<T, JoinTimeoutError<Self>> {
+        if let Err(error) = self.state.wait_finished_timeout(timeout) {
+            return Err(JoinTimeoutError {
+                error,
+                handle: self,
+            });
+        }
+        Ok(self.join())
+    }
+
+    /// Cancel the fiber. The fiber must check for the cancellation itself,
+    /// e.g. via [`is_cancelled`] or [`r#yield`], and then return.
+    ///
+    /// This doesn't wait for the fiber to finish, it still needs to be joined.
+    pub fn cancel(&self) {
+        if let Some(inner) = self.inner {
+            unsafe { self.state.cancel(inner) }
+        }
+    }
+
+    /// Returns `true` if the fiber function has returned, in which case
+    /// [`JoinHandle::join`] doesn't block.
+    pub fn is_finished(&self) -> bool {
+        self.state.is_finished.get()
+    }
+
+    /// Returns the id of the fiber.
+    pub fn id(&self) -> FiberId {
+        self.state.id()
+    }
+
+    /// Returns the name of the fiber.
+    pub fn name(&self) -> String {
+        // `inner` is only taken when the handle is consumed
+        unsafe { self.state.name(self.inner.unwrap()) }
     }
 }
 
 impl<'f, T> Drop for JoinHandle<'f, T> {
     fn drop(&mut self) {
-        if self.inner.is_some() {
-            panic!("JoinHandle dropped before being joined")
+        if let Some(inner) = self.inner.take() {
+            if self.state.is_scoped.get() {
+                return;
+            }
+            unsafe {
+                self.state.cancel(inner);
+                self.state.join(inner);
+            }
         }
     }
 }
@@ -1103,6 +1354,7 @@ impl<'f, T> Drop for JoinHandle<'f, T> {
 /// This is an optimized case of [`JoinHandle`]`<()>`.
 pub struct UnitJoinHandle<'f> {
     inner: Option<NonNull<ffi::Fiber>>,
+    state: Rc<FiberState>,
     marker: PhantomData<&'f ()>,
 }
 
@@ -1110,9 +1362,10 @@ impl_debug_stub! {UnitJoinHandle<'f>}
 impl_eq_hash! {UnitJoinHandle<'f>}
 
 impl<'f> UnitJoinHandle<'f> {
-    fn new(inner: NonNull<ffi::Fiber>) -> Self {
+    fn new(inner: NonNull<ffi::Fiber>, state: Rc<FiberState>) -> Self {
         Self {
             inner: Some(inner),
+            state,
             marker: PhantomData,
         }
     }
@@ -1121,19 +1374,99 @@ impl<'f> UnitJoinHandle<'f> {
     pub fn join(mut self) {
         // It's safe to unwrap because join will only be called once after the
         // join handle was created
-        let inner_raw = self.inner.take().unwrap().as_ptr();
-        let _code = unsafe { ffi::fiber_join(inner_raw) };
+        let inner = self.inner.take().unwrap();
+        unsafe { self.state.join(inner) };
+    }
+
+    /// Block until the fiber's termination or until the `timeout` expires.
+    ///
+    /// Returns a [`JoinTimeoutError`] holding the handle itself if the timeout
+    /// expired or the current fiber was cancelled while waiting.
+    pub fn join_timeout(
+        self,
+        timeout: Duration,
+    ) -> std::result::Result<(), JoinTimeoutError<Self>> {
+        if let Err(error) = self.state.wait_finished_timeout(timeout) {
+            return Err(JoinTimeoutError {
+                error,
+                handle: self,
+            });
+        }
+        self.join();
+        Ok(())
+    }
+
+    /// Cancel the fiber. See [`JoinHandle::cancel`] for details.
+    pub fn cancel(&self) {
+        if let Some(inner) = self.inner {
+            unsafe { self.state.cancel(inner) }
+        }
+    }
+
+    /// Returns `true` if the fiber function has returned, in which case
+    /// [`UnitJoinHandle::join`] doesn't block.
+    pub fn is_finished(&self) -> bool {
+        self.state.is_finished.get()
+    }
+
+    /// Returns the id of the fiber.
+    pub fn id(&self) -> FiberId {
+        self.state.id()
+    }
+
+    /// Returns the name of the fiber.
+    pub fn name(&self) -> String {
+        // `inner` is only taken when the handle is consumed
+        unsafe { self.state.name(self.inner.unwrap()) }
+    }
+
+    /// Schedule the fiber for execution, interrupting a yielding call it may
+    /// be blocked in. Must not be called after the fiber has finished.
+    pub(crate) fn wakeup(&self) {
+        if let Some(inner) = self.inner {
+            unsafe { ffi::fiber_wakeup(inner.as_ptr()) }
+        }
     }
 }
 
 impl<'f> Drop for UnitJoinHandle<'f> {
     fn drop(&mut self) {
-        if self.inner.is_some() {
-            panic!("UnitJoinHandle dropped before being joined")
+        if let Some(inner) = self.inner.take() {
+            if self.state.is_scoped.get() {
+                return;
+            }
+            unsafe {
+                self.state.cancel(inner);
+                self.state.join(inner);
+            }
         }
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// JoinTimeoutError
+////////////////////////////////////////////////////////////////////////////////
+
+/// Returned from [`JoinHandle::join_timeout`] &
+/// [`UnitJoinHandle::join_timeout`] if the fiber hasn't finished in time.
+///
+/// The `handle` can still be used to join or cancel the fiber.
+#[derive(Debug, thiserror::Error)]
+#[error("{error}")]
+pub struct JoinTimeoutError<H> {
+    /// [`Error::FiberCancelled`] if the current fiber was cancelled while
+    /// waiting, otherwise an [`io::ErrorKind::TimedOut`] error.
+    pub error: Error,
+    pub handle: H,
+}
+
+impl<H> From<JoinTimeoutError<H>> for Error {
+    #[inline(always)]
+    fn from(e: JoinTimeoutError<H>) -> Self {
+        e.error
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // TrampolineArgs
 ////////////////////////////////////////////////////////////////////////////////
@@ -1180,10 +1513,11 @@ impl TrampolineArgs for VaList {
 /// **NOTE**: The argument `f` is a function that returns `T`. In case when `T =
 /// ()` (no return value) one should instead use [`start_proc`].
 ///
-/// The join handle will implicitly *detach* the child fiber upon being
-/// dropped. In this case, the child fiber may outlive the parent. Additionally,
-/// the join handle provides a [`JoinHandle::join`] method that can be used to
-/// join the child fiber and acquire the result value of the fiber function.
+/// The join handle will *cancel* the child fiber and wait for it to finish upon
+/// being dropped. The join handle provides a [`JoinHandle::join`] method that
+/// can be used to join the child fiber and acquire the result value of the
+/// fiber function. See also [`scope`] for spawning several fibers which are
+/// cancelled and joined together.
 ///
 /// This will create a fiber using default parameters of [`Builder`], if you
 /// want to specify the stack size or the name of the thread, use builder's API
@@ -1305,6 +1639,29 @@ pub fn is_cancelled() -> bool {
     unsafe { ffi::fiber_is_cancelled() }
 }
 
+/// Returns [`Error::FiberCancelled`] if the current fiber is cancelled, so
+/// that the cancellation can be propagated with `?`.
+///
+/// ```no_run
+/// use tarantool::fiber;
+///
+/// let jh = fiber::start(|| -> tarantool::Result<()> {
+///     loop {
+///         fiber::sleep(std::time::Duration::from_millis(100));
+///         fiber::check_cancelled()?;
+///     }
+/// });
+/// jh.cancel();
+/// assert!(jh.join().is_err());
+/// ```
+#[inline]
+pub fn check_cancelled() -> Result<()> {
+    if is_cancelled() {
+        return Err(Error::FiberCancelled);
+    }
+    Ok(())
+}
+
 /// Put the current fiber to sleep for at least `time` seconds.
 ///
 /// Yield control to the scheduler and sleep for the specified number of seconds.
@@ -1358,16 +1715,13 @@ pub fn fiber_yield() {
 }
 
 /// Returns control to the scheduler.
-/// Works likewise [`fiber::sleep`]`(Duration::ZERO)` but return error if fiber was canceled by another routine.
+/// Works likewise [`fiber::sleep`]`(Duration::ZERO)` but returns
+/// [`Error::FiberCancelled`] if fiber was canceled by another routine.
 ///
 /// [`fiber::sleep`]: crate::fiber::sleep
 pub fn r#yield() -> Result<()> {
     unsafe { fiber_sleep(0f64) };
-    if is_cancelled() {
-        set_error!(TarantoolErrorCode::ProcLua, "fiber is cancelled");
-        return Err(TarantoolError::last().into());
-    }
-    Ok(())
+    check_cancelled()
 }
 
 /// Reschedule fiber to end of event loop cycle.
@@ -1620,4 +1974,78 @@ mod tests {
         jh.join();
         assert_eq!(*res.borrow(), 1);
     }
+
+    #[crate::test(tarantool = "crate")]
+    fn cancel_join_handle() {
+        let jh = start(|| {
+            while check_cancelled().is_ok() {
+                sleep(Duration::from_millis(1));
+            }
+            r#yield()
+        });
+        assert!(!jh.is_finished());
+        jh.cancel();
+        let err = jh.join().unwrap_err();
+        assert!(matches!(err, Error::FiberCancelled), "{}", err);
+
+        let jh = start_proc(|| ());
+        assert!(jh.is_finished());
+        jh.cancel();
+        jh.join();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn join_timeout() {
+        let cond = Rc::new(Cond::new());
+        let cond_2 = cond.clone();
+        let jh = start(move || {
+            cond_2.wait();
+            42
+        });
+        let err = jh.join_timeout(Duration::from_millis(10)).unwrap_err();
+        assert!(
+            matches!(&err.error, Error::IO(e) if e.kind() == io::ErrorKind::TimedOut),
+            "{}",
+            err
+        );
+        let jh = err.handle;
+        assert!(!jh.is_finished());
+        cond.signal();
+        assert_eq!(jh.join_timeout(Duration::from_secs(1)).ok(), Some(42));
+
+        let cond_2 = cond.clone();
+        let jh = start_proc(move || {
+            cond_2.wait();
+        });
+        let jh = jh.join_timeout(Duration::ZERO).unwrap_err().handle;
+        cond.signal();
+        assert!(jh.join_timeout(Duration::from_secs(1)).is_ok());
+
+        // The waiting fiber is cancelled.
+        let waiter = start(|| {
+            let cond_2 = cond.clone();
+            let jh = start_proc(move || {
+                cond_2.wait();
+            });
+            let err = jh.join_timeout(Duration::from_secs(10)).unwrap_err();
+            cond.signal();
+            err.handle.join();
+            err.error
+        });
+        waiter.cancel();
+        assert!(matches!(waiter.join(), Error::FiberCancelled));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn drop_cancels_and_joins() {
+        let is_cancelled = Cell::new(false);
+        let jh = start_proc(|| {
+            while !super::is_cancelled() {
+                sleep(Duration::from_secs(1));
+            }
+            is_cancelled.set(true);
+        });
+        drop(jh);
+        assert!(is_cancelled.get());
+    }
 }
@@ -18,9 +18,19 @@
 //! See also:
 //! - Synchronization Primitives:
 //!   - [`mutex`]
+//!   - [`rwlock`]
+//!   - [`semaphore`]
+//!   - [`notify`]
 //! - Channels
 //!   - [`oneshot`]
 //!   - [`watch`]
+//!   - [`mpsc`]
+//!   - [`broadcast`]
+//! - Executor for running many tasks on a fiber
+//!   - [`executor`]
+//! - Timers
+//!   - [`time::sleep`]
+//!   - [`time::interval`]
 //! - Extension Traits:
 //!   - [`timeout::IntoTimeout`]
 //!   - [`IntoOnDrop`]
@@ -35,12 +45,25 @@ use std::{
 
 use futures::pin_mut;
 
+pub mod broadcast;
+pub mod executor;
+pub mod mpsc;
 pub mod mutex;
+pub mod notify;
 pub mod oneshot;
+mod reactor;
+pub mod rwlock;
+pub mod semaphore;
+pub mod time;
 pub mod timeout;
 pub mod watch;
 
+pub use executor::{join_all, spawn, spawn_local, Executor};
 pub use mutex::Mutex;
+pub use notify::Notify;
+pub use rwlock::RwLock;
+pub use semaphore::Semaphore;
+pub use time::{interval, sleep};
 
 /// Error that happens on the receiver side of the channel.
 #[derive(thiserror::Error, Debug, PartialEq, Eq)]
@@ -131,6 +154,23 @@ pub(crate) mod coio {
         pub res: Rc<Cell<*mut libc::addrinfo>>,
         pub err: Rc<Cell<bool>>,
     }
+
+    /// Execute the address resolution request, blocking the current fiber
+    /// for at most `timeout`.
+    pub fn getaddrinfo(req: &GetAddrInfo, timeout: std::time::Duration) {
+        let mut res = std::ptr::null_mut();
+        let out = unsafe {
+            crate::ffi::tarantool::coio_getaddrinfo(
+                req.host.as_ptr(),
+                std::ptr::null(),
+                &req.hints as *const _,
+                &mut res as *mut _,
+                timeout.as_secs_f64(),
+            )
+        };
+        req.err.set(out != 0);
+        req.res.set(res);
+    }
 }
 
 pub(crate) mod context {
@@ -157,9 +197,9 @@ pub(crate) mod context {
         /// async executor will use `Duration::MAX` value as a timeout.
         pub(super) deadline: Option<Instant>,
 
-        /// Wait an event on a file descriptor rather than on a
+        /// Wait for events on file descriptors in addition to the
         /// `fiber::Cond` (that is under the hood of a `Waker`).
-        pub(super) coio_wait: Option<(RawFd, ffi::CoIOFlags)>,
+        pub(super) coio_wait: Vec<(RawFd, ffi::CoIOFlags)>,
 
         /// Wait for address resolution rather than on a
         /// `fiber::Cond` (that is under the hood of a `Waker`).
@@ -172,7 +212,7 @@ pub(crate) mod context {
             Self {
                 cx: Context::from_waker(waker),
                 deadline: None,
-                coio_wait: None,
+                coio_wait: Vec::new(),
                 coio_getaddrinfo: None,
             }
         }
@@ -202,10 +242,17 @@ pub(crate) mod context {
             }
         }
 
+        /// Request to wake up when `event` happens on `fd`. Can be called
+        /// several times, e.g. by each of the futures in a `select!`, the
+        /// task is woken up when any of the events happens.
+        ///
         /// SAFETY: `cx` must really be the `ContextExt`
         pub unsafe fn set_coio_wait(cx: &mut Context<'_>, fd: RawFd, event: ffi::CoIOFlags) {
             let cx = Self::as_context_ext(cx);
-            cx.coio_wait = Some((fd, event));
+            match cx.coio_wait.iter_mut().find(|(f, _)| *f == fd) {
+                Some((_, events)) => *events |= event,
+                None => cx.coio_wait.push((fd, event)),
+            }
         }
 
         /// SAFETY: `cx` must really be the `ContextExt`
@@ -290,24 +337,9 @@ pub fn block_on<F: Future>(f: F) -> F::Output {
         };
 
         if let Some(getaddrinfo) = cx.coio_getaddrinfo {
-            let mut res = std::ptr::null_mut();
-            let out = unsafe {
-                crate::ffi::tarantool::coio_getaddrinfo(
-                    getaddrinfo.host.as_ptr(),
-                    std::ptr::null(),
-                    &getaddrinfo.hints as *const _,
-                    &mut res as *mut _,
-                    timeout.as_secs_f64(),
-                )
-            };
-            getaddrinfo.err.set(out != 0);
-            getaddrinfo.res.set(res);
-        } else if let Some((fd, event)) = cx.coio_wait {
-            unsafe {
-                crate::ffi::tarantool::coio_wait(fd, event.bits(), timeout.as_secs_f64());
-            }
+            coio::getaddrinfo(&getaddrinfo, timeout);
         } else {
-            rcw.cond().wait_timeout(timeout);
+            reactor::wait(rcw.cond(), &cx.coio_wait, timeout);
         }
     }
 }
@@ -316,8 +348,14 @@ pub fn block_on<F: Future>(f: F) -> F::Output {
 mod tests {
     use std::cell::Cell;
 
+    use std::io::{Read as _, Write as _};
+    use std::os::unix::io::AsRawFd as _;
+    use std::os::unix::net::UnixStream;
+
     use super::timeout::IntoTimeout as _;
     use super::*;
+    use crate::ffi::tarantool::CoIOFlags;
+    use crate::fiber;
     use crate::test::util::{always_pending, ok};
 
     #[crate::test(tarantool = "crate")]
@@ -353,4 +391,53 @@ mod tests {
         });
         assert!(executed.get());
     }
+
+    /// Reads a byte from whichever of the sockets becomes readable first and
+    /// returns its index.
+    fn read_any<'a>(
+        socks: &'a [&'a UnixStream],
+    ) -> impl Future<Output = std::io::Result<usize>> + 'a {
+        futures::future::poll_fn(move |cx| {
+            for (i, mut sock) in socks.iter().copied().enumerate() {
+                match sock.read(&mut [0]) {
+                    Ok(_) => return Poll::Ready(Ok(i)),
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => unsafe {
+                        let fd = sock.as_raw_fd();
+                        context::ContextExt::set_coio_wait(cx, fd, CoIOFlags::READ)
+                    },
+                    Err(e) => return Poll::Ready(Err(e)),
+                }
+            }
+            Poll::Pending
+        })
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn wait_many_fds() {
+        let (a_rx, mut a_tx) = UnixStream::pair().unwrap();
+        let (b_rx, _b_tx) = UnixStream::pair().unwrap();
+        a_rx.set_nonblocking(true).unwrap();
+        b_rx.set_nonblocking(true).unwrap();
+
+        // The first of the sockets becomes readable.
+        let jh = fiber::start(move || {
+            fiber::sleep(Duration::from_millis(10));
+            a_tx.write_all(b"x").unwrap();
+        });
+        let socks = [&a_rx, &b_rx];
+        let res = block_on(read_any(&socks).timeout(Duration::from_secs(1)));
+        assert_eq!(res.unwrap(), 0);
+        jh.join();
+
+        // A socket and a waker at the same time.
+        let (tx, rx) = oneshot::channel();
+        let jh = fiber::start(move || {
+            fiber::sleep(Duration::from_millis(10));
+            tx.send(()).unwrap();
+        });
+        let select = futures::future::select(Box::pin(read_any(&socks)), rx);
+        let res = block_on(async { ok(select.await) }.timeout(Duration::from_secs(1)));
+        assert!(matches!(res, Ok(futures::future::Either::Right(_))));
+        jh.join();
+    }
 }
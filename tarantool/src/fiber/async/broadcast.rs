@@ -0,0 +1,339 @@
+//! A multi-producer, multi-consumer broadcast channel. Each sent value is
+//! seen by all of the receivers.
+//!
+//! [`channel`] creates a [`Sender`] / [`Receiver`] pair, further receivers are
+//! created with [`Sender::subscribe`] or by cloning a receiver. A receiver
+//! only sees the values sent after it was created.
+//!
+//! The channel retains at most `capacity` values. If a receiver falls behind
+//! by more than that, the oldest values are dropped and the receiver's next
+//! [`Receiver::recv`] fails with [`RecvError::Lagged`], after which it
+//! continues with the oldest retained value.
+//!
+//! # Example
+//! ```no_run
+//! use tarantool::fiber::r#async::broadcast;
+//! use tarantool::fiber;
+//!
+//! let (tx, mut rx_1) = broadcast::channel::<i32>(16);
+//! let mut rx_2 = tx.subscribe();
+//! tx.send(10).unwrap();
+//! fiber::block_on(async {
+//!     assert_eq!(rx_1.recv().await, Ok(10));
+//!     assert_eq!(rx_2.recv().await, Ok(10));
+//! });
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    fmt::Debug,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+/// Error returned by [`Sender::send`] if there are no receivers. Contains the
+/// value which wasn't sent.
+#[derive(thiserror::Error, Clone, Copy, PartialEq, Eq)]
+#[error("no receivers")]
+pub struct SendError<T>(pub T);
+
+impl<T> Debug for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+/// Error returned by [`Receiver::recv`].
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// All of the senders have been dropped and the receiver has seen all
+    /// of the values.
+    #[error("sender dropped")]
+    Closed,
+    /// The receiver fell behind and the given number of values were dropped
+    /// before it could see them.
+    #[error("receiver lagged behind by {0} values")]
+    Lagged(u64),
+}
+
+/// Error returned by [`Receiver::try_recv`].
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// There are no new values, but the senders still exist.
+    #[error("channel is empty")]
+    Empty,
+    /// See [`RecvError::Closed`].
+    #[error("sender dropped")]
+    Closed,
+    /// See [`RecvError::Lagged`].
+    #[error("receiver lagged behind by {0} values")]
+    Lagged(u64),
+}
+
+struct Shared<T> {
+    buffer: RefCell<VecDeque<T>>,
+    capacity: usize,
+    /// Position of the first value in `buffer`. Positions are counted from
+    /// the channel's creation.
+    head: Cell<u64>,
+    wakers: RefCell<Vec<Waker>>,
+    senders: Cell<usize>,
+    receivers: Cell<usize>,
+}
+
+impl<T> Shared<T> {
+    /// Position of the next value to be sent.
+    fn tail(&self) -> u64 {
+        self.head.get() + self.buffer.borrow().len() as u64
+    }
+
+    fn wake_all(&self) {
+        let wakers = std::mem::take(&mut *self.wakers.borrow_mut());
+        for waker in wakers {
+            waker.wake()
+        }
+    }
+}
+
+/// Creates a broadcast channel retaining at most `capacity` values.
+///
+/// # Panics
+/// If `capacity` is zero.
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "broadcast channel capacity must be non-zero");
+    let shared = Rc::new(Shared {
+        buffer: RefCell::new(VecDeque::with_capacity(capacity)),
+        capacity,
+        head: Cell::new(0),
+        wakers: Default::default(),
+        senders: Cell::new(1),
+        receivers: Cell::new(1),
+    });
+    let rx = Receiver {
+        shared: shared.clone(),
+        next: 0,
+    };
+    (Sender(shared), rx)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Sender
+////////////////////////////////////////////////////////////////////////////////
+
+/// Sends values to all of the associated [`Receiver`]s.
+///
+/// Instances are created by the [`channel`](fn@channel) function.
+pub struct Sender<T>(Rc<Shared<T>>);
+
+impl<T: Clone> Sender<T> {
+    /// Sends a value to all of the receivers, returning the number of them.
+    /// Never waits, if the channel is full the oldest value is dropped.
+    ///
+    /// Fails if there are no receivers.
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        let shared = &self.0;
+        let receivers = shared.receivers.get();
+        if receivers == 0 {
+            return Err(SendError(value));
+        }
+        let mut buffer = shared.buffer.borrow_mut();
+        if buffer.len() == shared.capacity {
+            buffer.pop_front();
+            shared.head.set(shared.head.get() + 1);
+        }
+        buffer.push_back(value);
+        drop(buffer);
+        shared.wake_all();
+        Ok(receivers)
+    }
+
+    /// Creates a new [`Receiver`] which will see the values sent after this
+    /// call.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let shared = &self.0;
+        shared.receivers.set(shared.receivers.get() + 1);
+        Receiver {
+            shared: shared.clone(),
+            next: shared.tail(),
+        }
+    }
+
+    /// Returns the number of receivers.
+    #[inline]
+    pub fn receiver_count(&self) -> usize {
+        self.0.receivers.get()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.0.senders.set(self.0.senders.get() + 1);
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let senders = self.0.senders.get() - 1;
+        self.0.senders.set(senders);
+        if senders == 0 {
+            self.0.wake_all();
+        }
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Receiver
+////////////////////////////////////////////////////////////////////////////////
+
+/// Receives values from the associated [`Sender`]s.
+///
+/// Cloning a receiver creates a new one at the same position.
+pub struct Receiver<T> {
+    shared: Rc<Shared<T>>,
+    /// Position of the next value to be received.
+    next: u64,
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Receives the next value, waiting until there is one.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        futures::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempts to receive a value without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let shared = &self.shared;
+        let head = shared.head.get();
+        if self.next < head {
+            let lagged = head - self.next;
+            self.next = head;
+            return Err(TryRecvError::Lagged(lagged));
+        }
+        let buffer = shared.buffer.borrow();
+        match buffer.get((self.next - head) as usize) {
+            Some(value) => {
+                self.next += 1;
+                Ok(value.clone())
+            }
+            None if shared.senders.get() == 0 => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Poll for the next value, see [`Receiver::recv`].
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+        match self.try_recv() {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError::Closed)),
+            Err(TryRecvError::Lagged(n)) => Poll::Ready(Err(RecvError::Lagged(n))),
+            Err(TryRecvError::Empty) => {
+                let mut wakers = self.shared.wakers.borrow_mut();
+                if !wakers.iter().any(|w| cx.waker().will_wake(w)) {
+                    wakers.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Returns the number of values the receiver hasn't seen yet.
+    #[inline]
+    pub fn len(&self) -> usize {
+        (self.shared.tail() - self.next.max(self.shared.head.get())) as usize
+    }
+
+    /// Returns `true` if the receiver has seen all of the values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.shared.receivers.set(self.shared.receivers.get() + 1);
+        Self {
+            shared: self.shared.clone(),
+            next: self.next,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.receivers.set(self.shared.receivers.get() - 1);
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver")
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use crate::fiber::r#async::timeout::IntoTimeout as _;
+    use futures::join;
+    use std::time::Duration;
+
+    const _1_SEC: Duration = Duration::from_secs(1);
+
+    #[crate::test(tarantool = "crate")]
+    fn all_receivers_see_values() {
+        let (tx, mut rx_1) = channel::<i32>(4);
+        let mut rx_2 = tx.subscribe();
+        let jh = fiber::start_async(async move {
+            let (a, b) = join!(rx_1.recv(), rx_2.recv());
+            (a, b, rx_1, rx_2)
+        });
+        assert_eq!(tx.send(1), Ok(2));
+        let (a, b, mut rx_1, mut rx_2) = jh.join();
+        assert_eq!((a, b), (Ok(1), Ok(1)));
+
+        tx.send(2).unwrap();
+        let mut rx_3 = rx_1.clone();
+        // Only sees the values sent after it was created.
+        let mut rx_4 = tx.subscribe();
+        assert_eq!(rx_1.try_recv(), Ok(2));
+        assert_eq!(rx_2.try_recv(), Ok(2));
+        assert_eq!(rx_3.try_recv(), Ok(2));
+        assert_eq!(rx_4.try_recv(), Err(TryRecvError::Empty));
+
+        drop(tx);
+        assert_eq!(
+            fiber::block_on(rx_4.recv().timeout(_1_SEC)),
+            Err(RecvError::Closed.into())
+        );
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn lagged() {
+        let (tx, mut rx) = channel::<i32>(2);
+        for i in 0..5 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(rx.len(), 2);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Lagged(3)));
+        assert_eq!(rx.try_recv(), Ok(3));
+        assert_eq!(fiber::block_on(rx.recv()), Ok(4));
+        assert!(rx.is_empty());
+
+        drop(rx);
+        assert_eq!(tx.send(5), Err(SendError(5)));
+    }
+}
@@ -0,0 +1,700 @@
+//! Executor running many async tasks on one or several fibers.
+//!
+//! [`block_on`] drives a single future on the current fiber. An [`Executor`]
+//! instead multiplexes any number of tasks, which are spawned with
+//! [`Executor::spawn`] and run concurrently on the fiber calling
+//! [`Executor::block_on`] and on the executor's worker fibers (see
+//! [`Executor::start_workers`]). Spawning a task is much cheaper than starting
+//! a fiber for it with [`fiber::start_async`].
+//!
+//! A task spawned with [`spawn`] runs on a global executor driven by a
+//! dedicated fiber, so it doesn't require anyone to block on it.
+//!
+//! ```no_run
+//! use tarantool::fiber::r#async::executor::{self, Executor};
+//!
+//! let executor = Executor::new();
+//! let a = executor.spawn(async { 1 });
+//! let b = executor.spawn(async { 2 });
+//! let res = executor.block_on(executor::join_all([a, b]));
+//! assert_eq!(res, [Ok(1), Ok(2)]);
+//! ```
+//!
+//! Tasks waiting for file descriptors (e.g. network futures) are supported,
+//! the executor waits for the descriptors of all of the pending tasks at once
+//! and only wakes up the tasks whose descriptors became ready.
+//!
+//! [`block_on`]: super::block_on
+//! [`fiber::start_async`]: crate::fiber::start_async
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::os::unix::io::RawFd;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use futures::pin_mut;
+use futures::task::ArcWake;
+
+use super::context::ContextExt;
+use super::reactor;
+use crate::ffi::tarantool as ffi;
+use crate::fiber;
+
+type TaskId = u64;
+type BoxFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Max number of tasks polled in a row before yielding the fiber to let other
+/// fibers run.
+const POLL_BUDGET: usize = 128;
+
+thread_local! {
+    /// Executor which is currently polling a task, see [`spawn_local`].
+    static CURRENT: RefCell<Option<Weak<Shared>>> = const { RefCell::new(None) };
+}
+
+/// Error returned by [`JoinHandle`] if the task was cancelled before
+/// completion.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("task was cancelled")]
+pub struct Cancelled;
+
+////////////////////////////////////////////////////////////////////////////////
+// Executor
+////////////////////////////////////////////////////////////////////////////////
+
+/// Executor of async tasks, see [module documentation](self) for details.
+///
+/// When the executor is dropped all of the unfinished tasks are cancelled and
+/// the worker fibers are joined.
+pub struct Executor {
+    shared: Rc<Shared>,
+    workers: RefCell<Vec<fiber::UnitJoinHandle<'static>>>,
+}
+
+impl Executor {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self {
+            shared: Rc::new(Shared {
+                tasks: Default::default(),
+                ready: Default::default(),
+                cond: Default::default(),
+                next_id: Cell::new(0),
+                is_shut_down: Cell::new(false),
+            }),
+            workers: Default::default(),
+        }
+    }
+
+    /// Spawn a new task on the executor. The task starts running once the
+    /// executor is driven by [`Executor::block_on`] or a worker fiber.
+    ///
+    /// The returned [`JoinHandle`] can be awaited to get the task's result.
+    /// Dropping the handle detaches the task, it keeps running.
+    #[inline(always)]
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        self.shared.spawn(future)
+    }
+
+    /// Start `count` fibers which run the executor's tasks in background
+    /// until the executor is dropped.
+    ///
+    /// Several worker fibers let other tasks run while one of them blocks the
+    /// fiber, e.g. waiting for a transaction commit.
+    pub fn start_workers(&self, count: usize) -> crate::Result<()> {
+        for _ in 0..count {
+            let shared = Rc::downgrade(&self.shared);
+            let jh = fiber::Builder::new()
+                .name("executor")
+                .proc(move || worker(shared))
+                .start()?;
+            self.workers.borrow_mut().push(jh);
+        }
+        Ok(())
+    }
+
+    /// Run `future` to completion on the current fiber, running the
+    /// executor's tasks in the meantime.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let shared = &self.shared;
+        let main = Arc::new(MainWaker {
+            is_woken: Cell::new(true),
+            cond: shared.cond.clone(),
+        });
+        let waker = futures::task::waker(main.clone());
+        let mut main_wait = Wait::default();
+
+        pin_mut!(future);
+        loop {
+            if main.is_woken.replace(false) {
+                let mut cx = ContextExt::from_waker(&waker);
+                if let Poll::Ready(v) = with_current(shared, || future.as_mut().poll(cx.cx())) {
+                    return v;
+                }
+                if let Some(getaddrinfo) = cx.coio_getaddrinfo.take() {
+                    super::coio::getaddrinfo(&getaddrinfo, Wait::timeout(cx.deadline));
+                    main.is_woken.set(true);
+                }
+                main_wait = Wait::new(&mut cx);
+            }
+
+            shared.wake_expired();
+            shared.run_ready();
+            if !shared.ready.borrow().is_empty() {
+                fiber::reschedule();
+                continue;
+            }
+            if main.is_woken.get() {
+                continue;
+            }
+
+            let ready = shared.wait(&main_wait);
+            if main_wait.is_expired(Instant::now()) || main_wait.is_ready(&ready) {
+                main.is_woken.set(true);
+            }
+        }
+    }
+
+    /// Returns the number of unfinished tasks.
+    #[inline]
+    pub fn task_count(&self) -> usize {
+        self.shared.tasks.borrow().len()
+    }
+}
+
+impl Default for Executor {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Executor {
+    fn drop(&mut self) {
+        self.shared.is_shut_down.set(true);
+        self.shared.ready.borrow_mut().clear();
+        let tasks = std::mem::take(&mut *self.shared.tasks.borrow_mut());
+        drop(tasks);
+        self.shared.cond.broadcast();
+        for worker in self.workers.get_mut().drain(..) {
+            worker.join();
+        }
+    }
+}
+
+impl std::fmt::Debug for Executor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Executor")
+            .field("task_count", &self.task_count())
+            .field("worker_count", &self.workers.borrow().len())
+            .finish()
+    }
+}
+
+/// Spawn a task on the global executor, which runs on a dedicated fiber.
+/// See [`Executor::spawn`].
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    thread_local! {
+        static GLOBAL: &'static Executor = {
+            // The global executor lives until the end of the process.
+            let executor = Box::leak(Box::new(Executor::new()));
+            executor
+                .start_workers(1)
+                .expect("failed to start the global executor fiber");
+            executor
+        };
+    }
+    GLOBAL.with(|executor| executor.spawn(future))
+}
+
+/// Spawn a task on the executor which is running the current task.
+///
+/// # Panicking
+/// Will panic if called outside of a task or [`Executor::block_on`].
+pub fn spawn_local<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    let shared = CURRENT
+        .with(|current| current.borrow().as_ref().and_then(Weak::upgrade))
+        .expect("spawn_local must be called from within an executor task");
+    shared.spawn(future)
+}
+
+/// Wait for all of the futures to complete and return their results in the
+/// same order. Usually used with [`JoinHandle`]s.
+pub async fn join_all<I>(futures: I) -> Vec<<I::Item as Future>::Output>
+where
+    I: IntoIterator,
+    I::Item: Future,
+{
+    futures::future::join_all(futures).await
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// JoinHandle
+////////////////////////////////////////////////////////////////////////////////
+
+/// An owned permission to await the result of a task spawned on an
+/// [`Executor`].
+///
+/// Resolves to `Ok(value)` once the task completes or to `Err(`[`Cancelled`]`)`
+/// if the task was cancelled or the executor was dropped.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct JoinHandle<T> {
+    id: TaskId,
+    executor: Weak<Shared>,
+    state: Rc<JoinState<T>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Cancel the task. Its future is dropped without being polled again.
+    ///
+    /// If the task is cancelled from within itself, the future is dropped once
+    /// the current poll is over.
+    pub fn cancel(&self) {
+        if let Some(shared) = self.executor.upgrade() {
+            shared.cancel(self.id);
+        }
+    }
+
+    /// Returns `true` if the task has completed or was cancelled.
+    #[inline(always)]
+    pub fn is_finished(&self) -> bool {
+        self.state.is_closed.get()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, Cancelled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(v) = self.state.result.take() {
+            return Poll::Ready(Ok(v));
+        }
+        if self.state.is_closed.get() {
+            return Poll::Ready(Err(Cancelled));
+        }
+        self.state.waker.replace(Some(cx.waker().clone()));
+        Poll::Pending
+    }
+}
+
+impl<T> std::fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("id", &self.id)
+            .field("is_finished", &self.is_finished())
+            .finish()
+    }
+}
+
+struct JoinState<T> {
+    result: Cell<Option<T>>,
+    is_closed: Cell<bool>,
+    waker: Cell<Option<Waker>>,
+}
+
+/// Marks the task as finished when its future is dropped.
+struct CloseOnDrop<T>(Rc<JoinState<T>>);
+
+impl<T> Drop for CloseOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.is_closed.set(true);
+        if let Some(waker) = self.0.waker.take() {
+            waker.wake()
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// impl details
+////////////////////////////////////////////////////////////////////////////////
+
+struct Shared {
+    tasks: RefCell<HashMap<TaskId, Task>>,
+    ready: RefCell<VecDeque<TaskId>>,
+    cond: Rc<fiber::Cond>,
+    next_id: Cell<TaskId>,
+    is_shut_down: Cell<bool>,
+}
+
+struct Task {
+    /// `None` while the task is being polled.
+    future: Option<BoxFuture>,
+    /// The task is in the ready queue.
+    is_queued: bool,
+    /// The task was woken up while being polled.
+    is_woken: bool,
+    is_cancelled: bool,
+    wait: Wait,
+}
+
+/// Wakeup conditions of a pending task.
+#[derive(Default)]
+struct Wait {
+    deadline: Option<Instant>,
+    fds: Vec<(RawFd, ffi::CoIOFlags)>,
+}
+
+impl Wait {
+    fn new(cx: &mut ContextExt) -> Self {
+        Self {
+            deadline: cx.deadline,
+            fds: std::mem::take(&mut cx.coio_wait),
+        }
+    }
+
+    fn timeout(deadline: Option<Instant>) -> Duration {
+        match deadline {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+            None => Duration::MAX,
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        matches!(self.deadline, Some(deadline) if deadline <= now)
+    }
+
+    /// Returns `true` if any of the file descriptors is in `ready`.
+    fn is_ready(&self, ready: &[RawFd]) -> bool {
+        self.fds.iter().any(|(fd, _)| ready.contains(fd))
+    }
+}
+
+impl Shared {
+    fn spawn<F>(self: &Rc<Self>, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let state = Rc::new(JoinState {
+            result: Cell::new(None),
+            is_closed: Cell::new(false),
+            waker: Cell::new(None),
+        });
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        let handle = JoinHandle {
+            id,
+            executor: Rc::downgrade(self),
+            state: state.clone(),
+        };
+        if self.is_shut_down.get() {
+            state.is_closed.set(true);
+            return handle;
+        }
+
+        let guard = CloseOnDrop(state.clone());
+        let future = async move {
+            let _guard = guard;
+            let v = future.await;
+            state.result.set(Some(v));
+        };
+        let task = Task {
+            future: Some(Box::pin(future)),
+            is_queued: true,
+            is_woken: false,
+            is_cancelled: false,
+            wait: Wait::default(),
+        };
+        self.tasks.borrow_mut().insert(id, task);
+        self.ready.borrow_mut().push_back(id);
+        self.cond.broadcast();
+        handle
+    }
+
+    fn wake(&self, id: TaskId) {
+        let mut tasks = self.tasks.borrow_mut();
+        let task = match tasks.get_mut(&id) {
+            Some(task) => task,
+            None => return,
+        };
+        if task.future.is_none() {
+            task.is_woken = true;
+        } else if !task.is_queued {
+            task.is_queued = true;
+            self.ready.borrow_mut().push_back(id);
+            self.cond.broadcast();
+        }
+    }
+
+    fn cancel(&self, id: TaskId) {
+        let mut tasks = self.tasks.borrow_mut();
+        let task = match tasks.get_mut(&id) {
+            Some(task) => task,
+            None => return,
+        };
+        if task.future.is_none() {
+            task.is_cancelled = true;
+            return;
+        }
+        let task = tasks.remove(&id);
+        // The future must be dropped after the borrow is released, because
+        // dropping it can wake up other tasks.
+        drop(tasks);
+        drop(task);
+    }
+
+    /// Poll the tasks from the ready queue, at most [`POLL_BUDGET`] of them.
+    fn run_ready(self: &Rc<Self>) {
+        for _ in 0..POLL_BUDGET {
+            let id = self.ready.borrow_mut().pop_front();
+            match id {
+                Some(id) => self.poll_task(id),
+                None => break,
+            }
+        }
+    }
+
+    fn poll_task(self: &Rc<Self>, id: TaskId) {
+        let mut future = {
+            let mut tasks = self.tasks.borrow_mut();
+            let task = match tasks.get_mut(&id) {
+                Some(task) => task,
+                None => return,
+            };
+            task.is_queued = false;
+            match task.future.take() {
+                Some(future) => future,
+                // Being polled by another fiber.
+                None => return,
+            }
+        };
+
+        let waker = futures::task::waker(Arc::new(TaskWaker {
+            id,
+            shared: Rc::downgrade(self),
+        }));
+        let mut cx = ContextExt::from_waker(&waker);
+        let poll = with_current(self, || future.as_mut().poll(cx.cx()));
+        let mut is_woken = false;
+        if let Some(getaddrinfo) = cx.coio_getaddrinfo.take() {
+            super::coio::getaddrinfo(&getaddrinfo, Wait::timeout(cx.deadline));
+            is_woken = true;
+        }
+
+        let mut tasks = self.tasks.borrow_mut();
+        let task = match tasks.get_mut(&id) {
+            Some(task) if poll.is_pending() && !task.is_cancelled => task,
+            _ => {
+                let task = tasks.remove(&id);
+                drop(tasks);
+                drop(task);
+                drop(future);
+                return;
+            }
+        };
+        task.future = Some(future);
+        task.wait = Wait::new(&mut cx);
+        if std::mem::take(&mut task.is_woken) || is_woken {
+            task.is_queued = true;
+            self.ready.borrow_mut().push_back(id);
+        }
+    }
+
+    /// Wake up the tasks whose deadline has expired.
+    fn wake_expired(&self) {
+        let now = Instant::now();
+        self.wake_if(|wait| wait.is_expired(now));
+    }
+
+    fn wake_if(&self, f: impl Fn(&Wait) -> bool) {
+        let woken: Vec<_> = self
+            .tasks
+            .borrow()
+            .iter()
+            .filter(|(_, task)| !task.is_queued && f(&task.wait))
+            .map(|(&id, _)| id)
+            .collect();
+        for id in woken {
+            self.wake(id);
+        }
+    }
+
+    /// Wait until any of the tasks is woken up, their nearest deadline
+    /// expires or any of the file descriptors they wait for becomes ready.
+    /// The tasks waiting for the ready descriptors are woken up, the
+    /// descriptors are returned to check the `extra` wait conditions.
+    fn wait(&self, extra: &Wait) -> Vec<RawFd> {
+        let mut deadline = None::<Instant>;
+        let mut fds = Vec::<(RawFd, ffi::CoIOFlags)>::new();
+        {
+            let tasks = self.tasks.borrow();
+            let waits = tasks.values().map(|task| &task.wait).chain(Some(extra));
+            for wait in waits {
+                deadline = match (deadline, wait.deadline) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+                for &(fd, events) in &wait.fds {
+                    match fds.iter_mut().find(|(f, _)| *f == fd) {
+                        Some((_, e)) => *e |= events,
+                        None => fds.push((fd, events)),
+                    }
+                }
+            }
+        }
+
+        let ready = reactor::wait(&self.cond, &fds, Wait::timeout(deadline));
+        if !ready.is_empty() {
+            self.wake_if(|wait| wait.is_ready(&ready));
+        }
+        ready
+    }
+}
+
+fn with_current<R>(shared: &Rc<Shared>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Weak<Shared>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT.with(|current| *current.borrow_mut() = self.0.take());
+        }
+    }
+
+    let prev = CURRENT.with(|current| current.replace(Some(Rc::downgrade(shared))));
+    let _restore = Restore(prev);
+    f()
+}
+
+fn worker(shared: Weak<Shared>) {
+    loop {
+        let shared = match shared.upgrade() {
+            Some(shared) if !shared.is_shut_down.get() => shared,
+            _ => return,
+        };
+        shared.wake_expired();
+        shared.run_ready();
+        if shared.ready.borrow().is_empty() {
+            shared.wait(&Wait::default());
+        } else {
+            drop(shared);
+            fiber::reschedule();
+        }
+    }
+}
+
+struct TaskWaker {
+    id: TaskId,
+    shared: Weak<Shared>,
+}
+
+// SAFETY: wakers are only used within the tx thread, see `FiberWaker`.
+unsafe impl Send for TaskWaker {}
+unsafe impl Sync for TaskWaker {}
+
+impl ArcWake for TaskWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        if let Some(shared) = arc_self.shared.upgrade() {
+            shared.wake(arc_self.id);
+        }
+    }
+}
+
+struct MainWaker {
+    is_woken: Cell<bool>,
+    cond: Rc<fiber::Cond>,
+}
+
+// SAFETY: wakers are only used within the tx thread, see `FiberWaker`.
+unsafe impl Send for MainWaker {}
+unsafe impl Sync for MainWaker {}
+
+impl ArcWake for MainWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.is_woken.set(true);
+        arc_self.cond.broadcast();
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber::r#async::timeout::IntoTimeout as _;
+    use crate::fiber::r#async::{oneshot, IntoOnDrop as _};
+    use crate::test::util::always_pending;
+
+    #[crate::test(tarantool = "crate")]
+    fn spawn_and_join() {
+        let executor = Executor::new();
+        let (tx, rx) = oneshot::channel();
+        let a = executor.spawn(async { rx.await.unwrap() + 1 });
+        let b = executor.spawn(async move {
+            tx.send(1).unwrap();
+            2
+        });
+        assert_eq!(executor.task_count(), 2);
+        let res = executor.block_on(join_all([a, b]));
+        assert_eq!(res, [Ok(2), Ok(2)]);
+        assert_eq!(executor.task_count(), 0);
+
+        // Deadlines of the tasks are respected.
+        let a = executor.spawn(async {
+            always_pending()
+                .timeout(Duration::from_millis(10))
+                .await
+                .is_err()
+        });
+        assert_eq!(executor.block_on(a), Ok(true));
+
+        // Tasks can spawn other tasks.
+        let a = executor.spawn(async {
+            let b = spawn_local(async { 3 });
+            b.await.unwrap() * 2
+        });
+        assert_eq!(executor.block_on(a), Ok(6));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn cancel() {
+        let executor = Executor::new();
+        let dropped = Rc::new(Cell::new(false));
+        let (_tx, rx) = oneshot::channel::<()>();
+        let flag = dropped.clone();
+        let a = executor.spawn(rx.on_drop(move || flag.set(true)));
+        executor.block_on(async {});
+        assert!(!a.is_finished());
+        a.cancel();
+        assert!(dropped.get());
+        assert!(a.is_finished());
+        assert_eq!(executor.block_on(a), Err(Cancelled));
+
+        // Dropping the executor cancels the tasks.
+        let (_tx, rx) = oneshot::channel::<()>();
+        let a = executor.spawn(rx);
+        drop(executor);
+        assert_eq!(crate::fiber::block_on(a), Err(Cancelled));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn workers() {
+        let executor = Executor::new();
+        executor.start_workers(2).unwrap();
+        let (tx, rx) = oneshot::channel();
+        let a = executor.spawn(async { rx.await.unwrap() });
+        let b = executor.spawn(async move {
+            crate::fiber::sleep(Duration::from_millis(10));
+            tx.send(42).unwrap();
+        });
+        // The tasks are run by the workers, nobody blocks on the executor.
+        assert_eq!(crate::fiber::block_on(a), Ok(42));
+        assert_eq!(crate::fiber::block_on(b), Ok(()));
+
+        // Global executor.
+        let a = spawn(async { 7 });
+        assert_eq!(crate::fiber::block_on(a), Ok(7));
+    }
+}
@@ -0,0 +1,482 @@
+//! A multi-producer, single-consumer queue for sending values between
+//! asynchronous tasks.
+//!
+//! [`channel`] creates a bounded channel, sending to which waits while the
+//! channel is full. [`unbounded_channel`] creates a channel without a limit on
+//! the number of buffered values, so its [`UnboundedSender::send`] is not async
+//! and can be used from non-async code.
+//!
+//! Both kinds of senders can be cloned to send from several places. The
+//! [`Receiver`] fails with [`RecvError`] once all of the senders are dropped
+//! or the channel is [closed](Receiver::close) and the buffered values are
+//! received.
+//!
+//! # Example
+//! ```no_run
+//! use tarantool::fiber::r#async::mpsc;
+//! use tarantool::fiber;
+//!
+//! let (tx, mut rx) = mpsc::channel::<i32>(16);
+//! let jh = fiber::start_async(async move {
+//!     for i in 0..10 {
+//!         tx.send(i).await.unwrap();
+//!     }
+//! });
+//! fiber::block_on(async {
+//!     while let Ok(v) = rx.recv().await {
+//!         println!("received {}", v);
+//!     }
+//! });
+//! jh.join();
+//! ```
+
+use super::RecvError;
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use futures::Stream;
+
+/// Error returned by [`Sender::send`] and [`UnboundedSender::send`] if the
+/// receiver has been dropped or closed. Contains the value which wasn't sent.
+#[derive(thiserror::Error, Clone, Copy, PartialEq, Eq)]
+#[error("receiver dropped")]
+pub struct SendError<T>(pub T);
+
+impl<T> Debug for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+/// Error returned by [`Sender::try_send`].
+#[derive(thiserror::Error, Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The channel is full.
+    #[error("channel is full")]
+    Full(T),
+    /// The receiver has been dropped or closed.
+    #[error("receiver dropped")]
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Returns the value which wasn't sent.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(v) | Self::Closed(v) => v,
+        }
+    }
+}
+
+impl<T> Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("Full(..)"),
+            Self::Closed(_) => f.write_str("Closed(..)"),
+        }
+    }
+}
+
+/// Error returned by [`Receiver::try_recv`].
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// The channel is empty, but the senders still exist.
+    #[error("channel is empty")]
+    Empty,
+    /// The channel is empty and all of the senders have been dropped or the
+    /// channel was closed.
+    #[error("sender dropped")]
+    Disconnected,
+}
+
+struct Chan<T> {
+    queue: RefCell<VecDeque<T>>,
+    /// `None` for unbounded channels.
+    capacity: Option<usize>,
+    recv_waker: RefCell<Option<Waker>>,
+    send_wakers: RefCell<Vec<Waker>>,
+    senders: Cell<usize>,
+    is_closed: Cell<bool>,
+}
+
+impl<T> Chan<T> {
+    fn new(capacity: Option<usize>) -> Rc<Self> {
+        Rc::new(Self {
+            queue: Default::default(),
+            capacity,
+            recv_waker: Default::default(),
+            send_wakers: Default::default(),
+            senders: Cell::new(1),
+            is_closed: Cell::new(false),
+        })
+    }
+
+    fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        if self.is_closed.get() {
+            return Err(TrySendError::Closed(value));
+        }
+        let mut queue = self.queue.borrow_mut();
+        if matches!(self.capacity, Some(capacity) if queue.len() >= capacity) {
+            return Err(TrySendError::Full(value));
+        }
+        queue.push_back(value);
+        drop(queue);
+        self.wake_receiver();
+        Ok(())
+    }
+
+    fn try_recv(&self) -> Result<T, TryRecvError> {
+        let value = self.queue.borrow_mut().pop_front();
+        match value {
+            Some(value) => {
+                self.wake_senders();
+                Ok(value)
+            }
+            None if self.is_closed.get() || self.senders.get() == 0 => {
+                Err(TryRecvError::Disconnected)
+            }
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    fn wake_receiver(&self) {
+        if let Some(waker) = self.recv_waker.borrow_mut().take() {
+            waker.wake()
+        }
+    }
+
+    fn wake_senders(&self) {
+        // All of the senders are woken up, because a woken up one may be
+        // dropped without sending anything.
+        let wakers = std::mem::take(&mut *self.send_wakers.borrow_mut());
+        for waker in wakers {
+            waker.wake()
+        }
+    }
+
+    fn add_send_waker(&self, waker: &Waker) {
+        let mut wakers = self.send_wakers.borrow_mut();
+        if !wakers.iter().any(|w| waker.will_wake(w)) {
+            wakers.push(waker.clone());
+        }
+    }
+
+    fn drop_sender(&self) {
+        let senders = self.senders.get() - 1;
+        self.senders.set(senders);
+        if senders == 0 {
+            self.wake_receiver();
+        }
+    }
+}
+
+/// Creates a bounded channel which can buffer up to `capacity` values.
+///
+/// # Panics
+/// If `capacity` is zero.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "mpsc channel capacity must be non-zero");
+    let chan = Chan::new(Some(capacity));
+    (Sender(chan.clone()), Receiver(chan))
+}
+
+/// Creates a channel without a limit on the number of buffered values.
+pub fn unbounded_channel<T>() -> (UnboundedSender<T>, Receiver<T>) {
+    let chan = Chan::new(None);
+    (UnboundedSender(chan.clone()), Receiver(chan))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Sender
+////////////////////////////////////////////////////////////////////////////////
+
+/// Sends values to the associated [`Receiver`] of a bounded channel.
+///
+/// Instances are created by the [`channel`](fn@channel) function.
+pub struct Sender<T>(Rc<Chan<T>>);
+
+impl<T> Sender<T> {
+    /// Sends a value, waiting until there is capacity for it.
+    ///
+    /// Fails if the receiver has been dropped or closed.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        struct Send<'a, T> {
+            chan: &'a Chan<T>,
+            value: Option<T>,
+        }
+
+        impl<T> Unpin for Send<'_, T> {}
+
+        impl<T> Future for Send<'_, T> {
+            type Output = Result<(), SendError<T>>;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let value = self.value.take().expect("polled after completion");
+                match self.chan.try_send(value) {
+                    Ok(()) => Poll::Ready(Ok(())),
+                    Err(TrySendError::Closed(value)) => Poll::Ready(Err(SendError(value))),
+                    Err(TrySendError::Full(value)) => {
+                        self.value = Some(value);
+                        self.chan.add_send_waker(cx.waker());
+                        Poll::Pending
+                    }
+                }
+            }
+        }
+
+        Send {
+            chan: &self.0,
+            value: Some(value),
+        }
+        .await
+    }
+
+    /// Attempts to send a value without waiting.
+    #[inline]
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        self.0.try_send(value)
+    }
+
+    /// Returns `true` if the receiver has been dropped or closed.
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed.get()
+    }
+
+    /// Returns the number of values which can be sent without waiting.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        let capacity = self.0.capacity.expect("bounded channel");
+        capacity.saturating_sub(self.0.queue.borrow().len())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.0.senders.set(self.0.senders.get() + 1);
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.0.drop_sender()
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// Sends values to the associated [`Receiver`] of an unbounded channel.
+///
+/// Instances are created by the [`unbounded_channel`] function.
+pub struct UnboundedSender<T>(Rc<Chan<T>>);
+
+impl<T> UnboundedSender<T> {
+    /// Sends a value. It is not marked async because sending a value to an
+    /// unbounded channel never requires any form of waiting.
+    ///
+    /// Fails if the receiver has been dropped or closed.
+    #[inline]
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.0
+            .try_send(value)
+            .map_err(|e| SendError(e.into_inner()))
+    }
+
+    /// Returns `true` if the receiver has been dropped or closed.
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed.get()
+    }
+}
+
+impl<T> Clone for UnboundedSender<T> {
+    fn clone(&self) -> Self {
+        self.0.senders.set(self.0.senders.get() + 1);
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Drop for UnboundedSender<T> {
+    fn drop(&mut self) {
+        self.0.drop_sender()
+    }
+}
+
+impl<T> Debug for UnboundedSender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UnboundedSender").finish_non_exhaustive()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Receiver
+////////////////////////////////////////////////////////////////////////////////
+
+/// Receives values from the associated [`Sender`]s or [`UnboundedSender`]s.
+///
+/// Also implements [`Stream`] which ends once all of the senders are dropped
+/// or the channel is closed.
+pub struct Receiver<T>(Rc<Chan<T>>);
+
+impl<T> Receiver<T> {
+    /// Receives the next value, waiting until there is one.
+    ///
+    /// Fails with [`RecvError`] if the channel is empty and all of the senders
+    /// have been dropped or the channel was closed.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        futures::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempts to receive a value without waiting.
+    #[inline]
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.0.try_recv()
+    }
+
+    /// Poll for the next value, see [`Receiver::recv`].
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+        match self.0.try_recv() {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(Err(RecvError)),
+            Err(TryRecvError::Empty) => {
+                *self.0.recv_waker.borrow_mut() = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Closes the channel without dropping the receiver. Subsequent sends
+    /// fail, but the buffered values can still be received.
+    pub fn close(&mut self) {
+        self.0.is_closed.set(true);
+        self.0.wake_senders();
+    }
+
+    /// Returns the number of buffered values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.queue.borrow().len()
+    }
+
+    /// Returns `true` if there are no buffered values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.queue.borrow().is_empty()
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().poll_recv(cx).map(Result::ok)
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use crate::fiber::r#async::timeout::{self, IntoTimeout as _};
+    use futures::StreamExt as _;
+    use std::time::Duration;
+
+    const _1_SEC: Duration = Duration::from_secs(1);
+
+    #[crate::test(tarantool = "crate")]
+    fn bounded() {
+        let (tx, mut rx) = channel::<i32>(2);
+        assert_eq!(tx.capacity(), 2);
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+        assert_eq!(rx.len(), 2);
+
+        // The sender waits for capacity.
+        let tx_2 = tx.clone();
+        let jh = fiber::start_async(async move { tx_2.send(3).timeout(_1_SEC).await });
+        assert_eq!(fiber::block_on(rx.recv()), Ok(1));
+        assert_eq!(jh.join(), Ok(()));
+        assert_eq!(rx.try_recv(), Ok(2));
+        assert_eq!(rx.try_recv(), Ok(3));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+        // The receiver waits for a value.
+        let jh = fiber::start_async(async move {
+            let v = rx.recv().timeout(_1_SEC).await;
+            (v, rx)
+        });
+        fiber::block_on(tx.send(4)).unwrap();
+        let (v, mut rx) = jh.join();
+        assert_eq!(v, Ok(4));
+
+        drop(tx);
+        assert_eq!(fiber::block_on(rx.recv()), Err(RecvError));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn timeout_while_full() {
+        let (tx, _rx) = channel::<i32>(1);
+        tx.try_send(1).unwrap();
+        let res = fiber::block_on(tx.send(2).timeout(Duration::from_millis(10)));
+        assert_eq!(res, Err(timeout::Error::Expired));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn close() {
+        let (tx, mut rx) = channel::<i32>(2);
+        tx.try_send(1).unwrap();
+        rx.close();
+        assert!(tx.is_closed());
+        assert_eq!(tx.try_send(2), Err(TrySendError::Closed(2)));
+        assert_eq!(rx.try_recv(), Ok(1));
+        // Nothing can be sent anymore, even though `tx` is alive.
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(fiber::block_on(rx.recv()), Err(RecvError));
+        drop(tx);
+
+        let (tx, rx) = unbounded_channel::<i32>();
+        drop(rx);
+        assert_eq!(tx.send(1), Err(SendError(1)));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn unbounded_stream() {
+        let (tx, rx) = unbounded_channel::<i32>();
+        let tx_2 = tx.clone();
+        for i in 0..100 {
+            tx.send(i).unwrap();
+        }
+        let jh = fiber::start_async(rx.collect::<Vec<_>>());
+        tx_2.send(100).unwrap();
+        drop(tx);
+        drop(tx_2);
+        assert_eq!(jh.join(), (0..=100).collect::<Vec<_>>());
+    }
+}
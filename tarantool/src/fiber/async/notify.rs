@@ -0,0 +1,259 @@
+//! See [`Notify`] for examples and docs.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+/// Notifies a single future or all of the futures waiting on it, doesn't
+/// carry any data.
+///
+/// [`Notify::notify_one`] wakes up the oldest of the waiting futures. If
+/// nobody waits, a permit is stored and the next [`Notify::notified`] call
+/// completes immediately (at most one permit is stored).
+/// [`Notify::notify_waiters`] wakes up all of the waiting futures and stores
+/// no permit.
+///
+/// # Example
+/// ```no_run
+/// use std::rc::Rc;
+/// use tarantool::fiber::r#async::Notify;
+/// use tarantool::fiber;
+///
+/// let notify = Rc::new(Notify::new());
+/// let notify_2 = notify.clone();
+/// let jh = fiber::start_async(async move {
+///     notify_2.notified().await;
+///     println!("notified");
+/// });
+/// notify.notify_one();
+/// jh.join();
+/// ```
+#[derive(Default)]
+pub struct Notify {
+    permit: Cell<bool>,
+    /// Incremented by each [`Notify::notify_waiters`] call.
+    epoch: Cell<u64>,
+    next_id: Cell<u64>,
+    waiters: RefCell<VecDeque<(u64, Waker)>>,
+    /// Waiters woken up by [`Notify::notify_one`] which haven't completed
+    /// yet.
+    notified: RefCell<Vec<u64>>,
+}
+
+impl Notify {
+    /// Creates a new `Notify` without a stored permit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a future which completes when notified.
+    ///
+    /// The future is registered as a waiter when it's polled for the first
+    /// time, so [`Notify::notify_waiters`] calls made before that are not
+    /// seen by it.
+    #[inline]
+    pub fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            state: State::Init,
+        }
+    }
+
+    /// Wakes up the oldest waiting future or stores a permit if nobody waits.
+    pub fn notify_one(&self) {
+        let waiter = self.waiters.borrow_mut().pop_front();
+        match waiter {
+            Some((id, waker)) => {
+                self.notified.borrow_mut().push(id);
+                waker.wake();
+            }
+            None => self.permit.set(true),
+        }
+    }
+
+    /// Wakes up all of the waiting futures.
+    pub fn notify_waiters(&self) {
+        self.epoch.set(self.epoch.get() + 1);
+        let waiters = std::mem::take(&mut *self.waiters.borrow_mut());
+        for (_, waker) in waiters {
+            waker.wake();
+        }
+    }
+
+    fn take_notified(&self, id: u64) -> bool {
+        let mut notified = self.notified.borrow_mut();
+        match notified.iter().position(|&n| n == id) {
+            Some(i) => {
+                notified.swap_remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notify")
+            .field("permit", &self.permit.get())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Init,
+    Waiting { id: u64, epoch: u64 },
+    Done,
+}
+
+/// Future returned by [`Notify::notified`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Notified<'a> {
+    notify: &'a Notify,
+    state: State,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let notify = self.notify;
+        match self.state {
+            State::Init => {
+                if notify.permit.replace(false) {
+                    self.state = State::Done;
+                    return Poll::Ready(());
+                }
+                let id = notify.next_id.get();
+                notify.next_id.set(id + 1);
+                let waker = cx.waker().clone();
+                notify.waiters.borrow_mut().push_back((id, waker));
+                self.state = State::Waiting {
+                    id,
+                    epoch: notify.epoch.get(),
+                };
+                Poll::Pending
+            }
+            State::Waiting { id, epoch } => {
+                if notify.take_notified(id) || notify.epoch.get() != epoch {
+                    self.state = State::Done;
+                    return Poll::Ready(());
+                }
+                let mut waiters = notify.waiters.borrow_mut();
+                if let Some((_, waker)) = waiters.iter_mut().find(|(i, _)| *i == id) {
+                    if !waker.will_wake(cx.waker()) {
+                        *waker = cx.waker().clone();
+                    }
+                }
+                Poll::Pending
+            }
+            State::Done => Poll::Ready(()),
+        }
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        if let State::Waiting { id, .. } = self.state {
+            let notify = self.notify;
+            notify.waiters.borrow_mut().retain(|(i, _)| *i != id);
+            // The notification must not be lost if the future is dropped
+            // after being woken up.
+            if notify.take_notified(id) {
+                notify.notify_one();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use crate::fiber::r#async::timeout::{self, IntoTimeout as _};
+    use crate::test::util::ok;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    const _1_SEC: Duration = Duration::from_secs(1);
+
+    #[crate::test(tarantool = "crate")]
+    fn notify_one() {
+        let notify = Rc::new(Notify::new());
+
+        // The permit is stored.
+        notify.notify_one();
+        fiber::block_on(notify.notified());
+
+        let res = fiber::block_on(
+            async {
+                notify.notified().await;
+                ok(())
+            }
+            .timeout(Duration::from_millis(10)),
+        );
+        assert_eq!(res, Err(timeout::Error::Expired));
+
+        let jhs: Vec<_> = (0..2)
+            .map(|_| {
+                let notify = notify.clone();
+                fiber::start_async(async move {
+                    async {
+                        notify.notified().await;
+                        ok(())
+                    }
+                    .timeout(_1_SEC)
+                    .await
+                })
+            })
+            .collect();
+        notify.notify_one();
+        notify.notify_one();
+        for jh in jhs {
+            assert_eq!(jh.join(), Ok(()));
+        }
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn notify_waiters() {
+        let notify = Rc::new(Notify::new());
+        let jhs: Vec<_> = (0..3)
+            .map(|_| {
+                let notify = notify.clone();
+                fiber::start_async(async move {
+                    async {
+                        notify.notified().await;
+                        ok(())
+                    }
+                    .timeout(_1_SEC)
+                    .await
+                })
+            })
+            .collect();
+        notify.notify_waiters();
+        for jh in jhs {
+            assert_eq!(jh.join(), Ok(()));
+        }
+        // No permit is stored.
+        assert!(!notify.permit.get());
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn dropped_waiter_passes_notification() {
+        let notify = Notify::new();
+        let mut a = Box::pin(notify.notified());
+        let mut b = Box::pin(notify.notified());
+        fiber::block_on(async {
+            assert!(futures::poll!(a.as_mut()).is_pending());
+            assert!(futures::poll!(b.as_mut()).is_pending());
+        });
+        notify.notify_one();
+        drop(a);
+        fiber::block_on(b);
+    }
+}
@@ -0,0 +1,107 @@
+//! Reactor used by the async executors to wait for several events at once.
+//!
+//! `coio_wait` can only wait for a single file descriptor and can't be
+//! interrupted by a [`Waker`](std::task::Waker). So while the fiber driving
+//! the futures waits on the executor's [`fiber::Cond`], every requested file
+//! descriptor is watched by a helper fiber, which signals the cond once the
+//! descriptor becomes ready. When the wait is over the remaining helpers are
+//! woken up and joined, so no descriptor is watched while the futures are
+//! being polled (and possibly close their descriptors).
+//!
+//! This means that every wait for file descriptors starts a helper fiber per
+//! descriptor, even if there is only one of them (e.g. a single socket in
+//! [`block_on`](super::block_on)). Tarantool reuses the memory of finished
+//! fibers, so starting one is cheap compared to a syscall, but it isn't free.
+//! Waits without file descriptors don't start any fibers.
+//!
+//! If a helper fiber can't be started (e.g. there's not enough memory), the
+//! reactor falls back to waiting for the first descriptor directly for at
+//! most `FALLBACK_TIMEOUT`. The other descriptors and the cond are not
+//! watched during that time, so the caller just polls a bit less often.
+use std::cell::Cell;
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+
+use crate::ffi::tarantool as ffi;
+use crate::fiber;
+
+/// Longest wait for a single descriptor if the helper fibers can't be
+/// started.
+const FALLBACK_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// Wait until any of the file descriptors in `fds` becomes ready, `cond` is
+/// signaled or `timeout` expires. Returns the descriptors which became ready.
+pub(crate) fn wait(
+    cond: &fiber::Cond,
+    fds: &[(RawFd, ffi::CoIOFlags)],
+    timeout: Duration,
+) -> Vec<RawFd> {
+    if fds.is_empty() {
+        cond.wait_timeout(timeout);
+        return Vec::new();
+    }
+
+    let ready = Cell::new(false);
+    let states: Vec<Helper> = fds.iter().map(|_| Helper::default()).collect();
+    let mut helpers = Vec::with_capacity(fds.len());
+    for (&(fd, events), state) in fds.iter().zip(&states) {
+        let ready = &ready;
+        let jh = fiber::Builder::new()
+            .name("coio_wait")
+            .proc(move || {
+                let res = unsafe { ffi::coio_wait(fd, events.bits(), timeout.as_secs_f64()) };
+                if res > 0 {
+                    state.is_ready.set(true);
+                    ready.set(true);
+                    cond.broadcast();
+                }
+                state.is_done.set(true);
+            })
+            .start();
+        match jh {
+            Ok(jh) => helpers.push(jh),
+            Err(_) => {
+                join_helpers(helpers, &states);
+                return wait_first(fds, timeout.min(FALLBACK_TIMEOUT));
+            }
+        }
+    }
+
+    if !ready.get() {
+        cond.wait_timeout(timeout);
+    }
+
+    join_helpers(helpers, &states);
+    fds.iter()
+        .zip(&states)
+        .filter(|(_, state)| state.is_ready.get())
+        .map(|(&(fd, _), _)| fd)
+        .collect()
+}
+
+/// Wait for the first descriptor in `fds` without the helper fibers.
+fn wait_first(fds: &[(RawFd, ffi::CoIOFlags)], timeout: Duration) -> Vec<RawFd> {
+    let (fd, events) = fds[0];
+    let res = unsafe { ffi::coio_wait(fd, events.bits(), timeout.as_secs_f64()) };
+    if res > 0 {
+        vec![fd]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Wake up the helpers which are still waiting and join all of them.
+fn join_helpers(helpers: Vec<fiber::UnitJoinHandle<'_>>, states: &[Helper]) {
+    for (jh, state) in helpers.into_iter().zip(states) {
+        if !state.is_done.get() {
+            jh.wakeup();
+        }
+        jh.join();
+    }
+}
+
+#[derive(Default)]
+struct Helper {
+    is_ready: Cell<bool>,
+    is_done: Cell<bool>,
+}
@@ -0,0 +1,307 @@
+//! See [`RwLock`] for examples and docs.
+
+use std::cell::{Cell, RefCell, UnsafeCell};
+use std::fmt;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+/// An asynchronous reader-writer lock.
+///
+/// Allows any number of readers or at most one writer at a time. Similarly
+/// to [`Mutex`](super::Mutex) the locking methods are async and the guards
+/// can be held across `.await` points.
+///
+/// Writers are preferred: once a writer waits for the lock, new readers wait
+/// until it gets and releases the lock, so writers are not starved by a steady
+/// stream of readers.
+///
+/// # Example
+/// ```no_run
+/// use tarantool::fiber::r#async::RwLock;
+/// use tarantool::fiber;
+///
+/// let lock = RwLock::new(5);
+/// fiber::block_on(async {
+///     {
+///         let r1 = lock.read().await;
+///         let r2 = lock.read().await;
+///         assert_eq!(*r1 + *r2, 10);
+///     }
+///     *lock.write().await += 1;
+///     assert_eq!(*lock.read().await, 6);
+/// });
+/// ```
+pub struct RwLock<T: ?Sized> {
+    readers: Cell<usize>,
+    is_writing: Cell<bool>,
+    waiting_writers: Cell<usize>,
+    wakers: RefCell<Vec<Waker>>,
+    data: UnsafeCell<T>,
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Creates a new lock in an unlocked state ready for use.
+    pub fn new(t: T) -> Self
+    where
+        T: Sized,
+    {
+        Self {
+            readers: Cell::new(0),
+            is_writing: Cell::new(false),
+            waiting_writers: Cell::new(0),
+            wakers: Default::default(),
+            data: UnsafeCell::new(t),
+        }
+    }
+
+    /// Locks this lock with shared read access, waiting until there are no
+    /// writers holding or waiting for the lock.
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        futures::future::poll_fn(|cx| match self.try_read() {
+            Some(guard) => Poll::Ready(guard),
+            None => {
+                self.add_waker(cx.waker());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Locks this lock with exclusive write access, waiting until there are
+    /// no other readers or writers holding the lock.
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        struct Write<'a, T: ?Sized> {
+            lock: &'a RwLock<T>,
+            is_waiting: bool,
+        }
+
+        impl<'a, T: ?Sized> Future for Write<'a, T> {
+            type Output = RwLockWriteGuard<'a, T>;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let lock = self.lock;
+                if lock.is_writing.get() || lock.readers.get() > 0 {
+                    if !self.is_waiting {
+                        self.is_waiting = true;
+                        lock.waiting_writers.set(lock.waiting_writers.get() + 1);
+                    }
+                    lock.add_waker(cx.waker());
+                    return Poll::Pending;
+                }
+                if self.is_waiting {
+                    self.is_waiting = false;
+                    lock.waiting_writers.set(lock.waiting_writers.get() - 1);
+                }
+                Poll::Ready(RwLockWriteGuard::new(lock))
+            }
+        }
+
+        impl<T: ?Sized> Drop for Write<'_, T> {
+            fn drop(&mut self) {
+                if self.is_waiting {
+                    let lock = self.lock;
+                    lock.waiting_writers.set(lock.waiting_writers.get() - 1);
+                    // Readers may have been waiting for this writer.
+                    lock.wake_all();
+                }
+            }
+        }
+
+        Write {
+            lock: self,
+            is_waiting: false,
+        }
+        .await
+    }
+
+    /// Attempts to acquire this lock with shared read access without waiting.
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        if self.is_writing.get() || self.waiting_writers.get() > 0 {
+            return None;
+        }
+        self.readers.set(self.readers.get() + 1);
+        Some(RwLockReadGuard { lock: self })
+    }
+
+    /// Attempts to acquire this lock with exclusive write access without
+    /// waiting.
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        if self.is_writing.get() || self.readers.get() > 0 {
+            return None;
+        }
+        Some(RwLockWriteGuard::new(self))
+    }
+
+    /// Consumes this lock, returning the underlying data.
+    pub fn into_inner(self) -> T
+    where
+        T: Sized,
+    {
+        self.data.into_inner()
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// Since this call borrows the `RwLock` mutably, no actual locking needs
+    /// to take place.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    fn add_waker(&self, waker: &Waker) {
+        let mut wakers = self.wakers.borrow_mut();
+        if !wakers.iter().any(|w| waker.will_wake(w)) {
+            wakers.push(waker.clone());
+        }
+    }
+
+    fn wake_all(&self) {
+        let wakers = std::mem::take(&mut *self.wakers.borrow_mut());
+        for waker in wakers {
+            waker.wake()
+        }
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    fn from(t: T) -> Self {
+        Self::new(t)
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RwLock")
+            .field("readers", &self.readers.get())
+            .field("is_writing", &self.is_writing.get())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A handle to a [`RwLock`] held with shared read access.
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        let readers = self.lock.readers.get() - 1;
+        self.lock.readers.set(readers);
+        if readers == 0 {
+            self.lock.wake_all();
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// A handle to a [`RwLock`] held with exclusive write access.
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T: ?Sized> RwLockWriteGuard<'a, T> {
+    fn new(lock: &'a RwLock<T>) -> Self {
+        lock.is_writing.set(true);
+        Self { lock }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.is_writing.set(false);
+        self.lock.wake_all();
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use crate::fiber::r#async::timeout::IntoTimeout as _;
+    use crate::test::util::ok;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    const _1_SEC: Duration = Duration::from_secs(1);
+
+    #[crate::test(tarantool = "crate")]
+    fn readers_and_writers() {
+        let lock = RwLock::new(0);
+        let r1 = lock.try_read().unwrap();
+        let r2 = lock.try_read().unwrap();
+        assert!(lock.try_write().is_none());
+        drop((r1, r2));
+
+        let mut w = lock.try_write().unwrap();
+        *w = 1;
+        assert!(lock.try_read().is_none());
+        assert!(lock.try_write().is_none());
+        drop(w);
+        assert_eq!(*lock.try_read().unwrap(), 1);
+        assert_eq!(lock.into_inner(), 1);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn writer_is_preferred() {
+        let lock = Rc::new(RwLock::new(0));
+        let r = lock.try_read().unwrap();
+
+        let lock_2 = lock.clone();
+        let writer = fiber::start_async(async move {
+            let res = async { ok(lock_2.write().await) }.timeout(_1_SEC).await;
+            *res.unwrap() += 1;
+        });
+        // The writer waits, so new readers wait too.
+        assert!(lock.try_read().is_none());
+        let lock_2 = lock.clone();
+        let reader = fiber::start_async(async move {
+            let res = async { ok(lock_2.read().await) }.timeout(_1_SEC).await;
+            *res.unwrap()
+        });
+
+        drop(r);
+        writer.join();
+        assert_eq!(reader.join(), 1);
+    }
+}
@@ -0,0 +1,211 @@
+//! See [`Semaphore`] for examples and docs.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+/// An asynchronous counting semaphore, limits the number of futures which
+/// can access a resource at the same time.
+///
+/// A semaphore holds a number of permits, [`Semaphore::acquire`] waits until
+/// a permit is available and takes it. The permit is returned to the
+/// semaphore when the returned [`SemaphorePermit`] is dropped.
+///
+/// # Example
+/// ```no_run
+/// use tarantool::fiber::r#async::Semaphore;
+/// use tarantool::fiber;
+///
+/// let semaphore = Semaphore::new(2);
+/// fiber::block_on(async {
+///     let _a = semaphore.acquire().await;
+///     let _b = semaphore.acquire().await;
+///     assert!(semaphore.try_acquire().is_none());
+/// });
+/// ```
+pub struct Semaphore {
+    permits: Cell<usize>,
+    wakers: RefCell<Vec<Waker>>,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the given number of permits.
+    pub fn new(permits: usize) -> Self {
+        Self {
+            permits: Cell::new(permits),
+            wakers: Default::default(),
+        }
+    }
+
+    /// Acquires a permit, waiting until one is available.
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        self.acquire_many(1).await
+    }
+
+    /// Acquires `n` permits at once, waiting until enough are available.
+    pub async fn acquire_many(&self, n: usize) -> SemaphorePermit<'_> {
+        struct Acquire<'a> {
+            semaphore: &'a Semaphore,
+            n: usize,
+        }
+
+        impl<'a> Future for Acquire<'a> {
+            type Output = SemaphorePermit<'a>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                match self.semaphore.try_acquire_many(self.n) {
+                    Some(permit) => Poll::Ready(permit),
+                    None => {
+                        self.semaphore.add_waker(cx.waker());
+                        Poll::Pending
+                    }
+                }
+            }
+        }
+
+        Acquire { semaphore: self, n }.await
+    }
+
+    /// Attempts to acquire a permit without waiting.
+    #[inline]
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.try_acquire_many(1)
+    }
+
+    /// Attempts to acquire `n` permits without waiting.
+    pub fn try_acquire_many(&self, n: usize) -> Option<SemaphorePermit<'_>> {
+        let permits = self.permits.get();
+        if permits < n {
+            return None;
+        }
+        self.permits.set(permits - n);
+        Some(SemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Adds `n` new permits to the semaphore.
+    pub fn add_permits(&self, n: usize) {
+        self.permits.set(self.permits.get() + n);
+        self.wake_all();
+    }
+
+    /// Returns the number of permits which can be acquired without waiting.
+    #[inline]
+    pub fn available_permits(&self) -> usize {
+        self.permits.get()
+    }
+
+    fn add_waker(&self, waker: &Waker) {
+        let mut wakers = self.wakers.borrow_mut();
+        if !wakers.iter().any(|w| waker.will_wake(w)) {
+            wakers.push(waker.clone());
+        }
+    }
+
+    fn wake_all(&self) {
+        // All of the waiters are woken up, because they may wait for a
+        // different number of permits.
+        let wakers = std::mem::take(&mut *self.wakers.borrow_mut());
+        for waker in wakers {
+            waker.wake()
+        }
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("permits", &self.permits.get())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Permits acquired from a [`Semaphore`], returned to it when dropped.
+#[must_use = "the permits are released immediately if the guard is dropped"]
+#[derive(Debug)]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+}
+
+impl SemaphorePermit<'_> {
+    /// Drops the guard without returning the permits to the semaphore.
+    #[inline]
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+
+    /// Returns the number of permits held by the guard.
+    #[inline(always)]
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.add_permits(self.permits);
+        }
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use crate::fiber::r#async::timeout::{self, IntoTimeout as _};
+    use crate::test::util::ok;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[crate::test(tarantool = "crate")]
+    fn limits_concurrency() {
+        let semaphore = Rc::new(Semaphore::new(2));
+        let active = Rc::new(Cell::new(0));
+        let max_active = Rc::new(Cell::new(0));
+        let jhs: Vec<_> = (0..5)
+            .map(|_| {
+                let semaphore = semaphore.clone();
+                let active = active.clone();
+                let max_active = max_active.clone();
+                fiber::start_async(async move {
+                    let _permit = semaphore.acquire().await;
+                    active.set(active.get() + 1);
+                    max_active.set(max_active.get().max(active.get()));
+                    fiber::sleep(Duration::from_millis(1));
+                    active.set(active.get() - 1);
+                })
+            })
+            .collect();
+        for jh in jhs {
+            jh.join();
+        }
+        assert_eq!(max_active.get(), 2);
+        assert_eq!(semaphore.available_permits(), 2);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn acquire_many() {
+        let semaphore = Semaphore::new(3);
+        let permit = semaphore.try_acquire_many(2).unwrap();
+        assert_eq!(permit.num_permits(), 2);
+        assert!(semaphore.try_acquire_many(2).is_none());
+
+        let res = fiber::block_on(
+            async { ok(semaphore.acquire_many(2).await) }.timeout(Duration::from_millis(10)),
+        );
+        assert!(matches!(res, Err(timeout::Error::Expired)));
+
+        permit.forget();
+        assert_eq!(semaphore.available_permits(), 1);
+        semaphore.add_permits(1);
+        let permit = fiber::block_on(semaphore.acquire_many(2));
+        drop(permit);
+        assert_eq!(semaphore.available_permits(), 2);
+    }
+}
@@ -0,0 +1,309 @@
+//! Async timers: [`sleep`] and [`interval`].
+//!
+//! Timers don't start any fibers, they set the wakeup deadline of the
+//! executor polling them (see [`block_on`](crate::fiber::block_on) and
+//! [`Executor`](super::Executor)).
+//!
+//! ```no_run
+//! use tarantool::fiber::r#async::time;
+//! use tarantool::fiber;
+//! use std::time::Duration;
+//!
+//! fiber::block_on(async {
+//!     let mut interval = time::interval(Duration::from_millis(100));
+//!     for _ in 0..3 {
+//!         interval.tick().await;
+//!         println!("tick");
+//!     }
+//!     time::sleep(Duration::from_secs(1)).await;
+//! });
+//! ```
+//!
+//! See also [`timeout`](super::timeout) to limit the time a future can take.
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use futures::Stream;
+
+use super::context::ContextExt;
+
+/// Poll a timer expiring at `deadline`. `None` means the timer never
+/// expires.
+fn poll_deadline(deadline: Option<Instant>, cx: &mut Context<'_>) -> Poll<()> {
+    match deadline {
+        Some(deadline) if Instant::now() >= deadline => Poll::Ready(()),
+        Some(deadline) => {
+            // SAFETY: This is safe as long as the `Context` really
+            // is the `ContextExt`. It's always true within provided
+            // `block_on` async runtime.
+            unsafe { ContextExt::set_deadline(cx, deadline) };
+            Poll::Pending
+        }
+        None => Poll::Pending,
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Sleep
+////////////////////////////////////////////////////////////////////////////////
+
+/// Future returned by [`sleep`] and [`sleep_until`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Sleep {
+    deadline: Option<Instant>,
+}
+
+/// Returns a future which completes after `duration` has elapsed.
+///
+/// Unlike [`fiber::sleep`](crate::fiber::sleep) this doesn't block the fiber,
+/// so other futures can make progress in the meantime. A `duration` equal to
+/// [`Duration::ZERO`] completes without a fiber yield.
+#[inline]
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: Instant::now().checked_add(duration),
+    }
+}
+
+/// Returns a future which completes once the `deadline` is reached.
+#[inline]
+pub fn sleep_until(deadline: Instant) -> Sleep {
+    Sleep {
+        deadline: Some(deadline),
+    }
+}
+
+impl Sleep {
+    /// Returns the instant at which the future completes. `None` if the
+    /// duration passed to [`sleep`] was too large to represent an [`Instant`],
+    /// in which case the future never completes.
+    #[inline(always)]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns `true` if the deadline has been reached.
+    #[inline]
+    pub fn is_elapsed(&self) -> bool {
+        matches!(self.deadline, Some(deadline) if Instant::now() >= deadline)
+    }
+
+    /// Change the deadline of the future, even if it has already completed.
+    #[inline(always)]
+    pub fn reset(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        poll_deadline(self.deadline, cx)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Interval
+////////////////////////////////////////////////////////////////////////////////
+
+/// A timer ticking periodically, see [`interval`].
+///
+/// Also implements [`Stream`] yielding the instants of the ticks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval {
+    /// `None` if the next tick is too far to represent an [`Instant`], in
+    /// which case the interval never ticks again.
+    next: Option<Instant>,
+    period: Duration,
+}
+
+/// Returns an [`Interval`] ticking every `period`, the first tick completes
+/// immediately.
+///
+/// If a tick is missed (e.g. because the fiber was busy for longer than
+/// `period`), the next tick completes immediately and the following ones are
+/// scheduled `period` apart from it, i.e. missed ticks are not made up for.
+/// If the next tick is too far to represent an [`Instant`] (e.g. `period` is
+/// [`Duration::MAX`]), the interval never ticks again.
+///
+/// # Panics
+/// If `period` is zero.
+#[inline]
+pub fn interval(period: Duration) -> Interval {
+    interval_at(Instant::now(), period)
+}
+
+/// Same as [`interval`], but the first tick completes at `start`.
+///
+/// # Panics
+/// If `period` is zero.
+#[inline]
+pub fn interval_at(start: Instant, period: Duration) -> Interval {
+    assert!(period > Duration::ZERO, "interval period must be non-zero");
+    Interval {
+        next: Some(start),
+        period,
+    }
+}
+
+impl Interval {
+    /// Returns a future which completes at the next tick with the instant the
+    /// tick was scheduled at.
+    #[inline(always)]
+    pub fn tick(&mut self) -> Tick<'_> {
+        Tick(self)
+    }
+
+    /// Poll for the next tick, see [`Interval::tick`].
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Instant> {
+        let tick = match self.next {
+            Some(tick) if poll_deadline(Some(tick), cx).is_ready() => tick,
+            _ => return Poll::Pending,
+        };
+        let now = Instant::now();
+        self.next = match tick.checked_add(self.period) {
+            Some(next) if next > now => Some(next),
+            _ => now.checked_add(self.period),
+        };
+        Poll::Ready(tick)
+    }
+
+    /// Returns the period of the interval.
+    #[inline(always)]
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Schedule the next tick `period` from now.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.next = Instant::now().checked_add(self.period);
+    }
+}
+
+impl Stream for Interval {
+    type Item = Instant;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Instant>> {
+        self.get_mut().poll_tick(cx).map(Some)
+    }
+}
+
+/// Future returned by [`Interval::tick`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Tick<'a>(&'a mut Interval);
+
+impl Future for Tick<'_> {
+    type Output = Instant;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Instant> {
+        self.get_mut().0.poll_tick(cx)
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use crate::fiber::check_yield;
+    use crate::fiber::r#async::timeout::{self, IntoTimeout as _};
+    use crate::fiber::YieldResult::{DidntYield, Yielded};
+    use crate::test::util::ok;
+    use futures::StreamExt as _;
+
+    #[crate::test(tarantool = "crate")]
+    fn sleep_completes() {
+        assert_eq!(
+            check_yield(|| fiber::block_on(sleep(Duration::ZERO))),
+            DidntYield(())
+        );
+
+        let start = Instant::now();
+        assert_eq!(
+            check_yield(|| fiber::block_on(sleep(Duration::from_millis(10)))),
+            Yielded(())
+        );
+        assert!(start.elapsed() >= Duration::from_millis(10));
+
+        let mut s = sleep(Duration::MAX);
+        assert_eq!(s.deadline(), None);
+        assert!(!s.is_elapsed());
+        s.reset(Instant::now());
+        assert!(s.is_elapsed());
+        fiber::block_on(s);
+
+        // Sleep is cancelled by a timeout.
+        let res = fiber::block_on(
+            async {
+                sleep(Duration::from_secs(10)).await;
+                ok(())
+            }
+            .timeout(Duration::from_millis(10)),
+        );
+        assert_eq!(res, Err(timeout::Error::Expired));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn interval_ticks() {
+        let period = Duration::from_millis(10);
+        let start = Instant::now();
+        let mut interval = interval(period);
+        let ticks = fiber::block_on(async {
+            let mut ticks = vec![];
+            for _ in 0..3 {
+                ticks.push(interval.tick().await);
+            }
+            ticks
+        });
+        assert!(ticks[0] >= start);
+        assert_eq!(ticks[1] - ticks[0], period);
+        assert_eq!(ticks[2] - ticks[1], period);
+        assert!(start.elapsed() >= period * 2);
+
+        // Missed ticks are skipped.
+        fiber::sleep(period * 3);
+        let tick = fiber::block_on(interval.next()).unwrap();
+        assert_eq!(tick, ticks[2] + period);
+        let next = fiber::block_on(interval.next()).unwrap();
+        assert!(next >= tick + period * 3);
+
+        // The next tick of a huge period never comes.
+        let mut interval = self::interval(Duration::MAX);
+        fiber::block_on(interval.tick());
+        let res = fiber::block_on(async { ok(interval.tick().await) }.timeout(period));
+        assert_eq!(res, Err(timeout::Error::Expired));
+        interval.reset();
+        let res = fiber::block_on(async { ok(interval.tick().await) }.timeout(period));
+        assert_eq!(res, Err(timeout::Error::Expired));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn timeout_at_deadline() {
+        let deadline = Instant::now() + Duration::from_millis(10);
+        let res = fiber::block_on(timeout::timeout_at(deadline, async {
+            sleep(Duration::from_secs(10)).await;
+            ok(())
+        }));
+        assert_eq!(res, Err(timeout::Error::Expired));
+        assert!(Instant::now() >= deadline);
+
+        let res = fiber::block_on(
+            async {
+                sleep_until(deadline).await;
+                ok(1)
+            }
+            .timeout_at(Instant::now() + Duration::from_secs(1)),
+        );
+        assert_eq!(res, Ok(1));
+    }
+}
@@ -23,7 +23,7 @@ pub enum Error<E> {
 
 pub type Result<T, E> = std::result::Result<T, Error<E>>;
 
-/// Future returned by [`timeout`](timeout).
+/// Future returned by [`timeout`](timeout) and [`timeout_at`].
 #[derive(Debug)]
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Timeout<F> {
@@ -60,7 +60,27 @@ pub fn timeout<F: Future>(timeout: Duration, f: F) -> Timeout<F> {
     }
 }
 
+/// Requires a `Future` to complete before the `deadline`.
+///
+/// Same as [`timeout()`], but takes an [`Instant`] instead of a [`Duration`],
+/// which is convenient when several futures must complete before a common
+/// deadline.
+#[inline]
+pub fn timeout_at<F: Future>(deadline: Instant, f: F) -> Timeout<F> {
+    Timeout {
+        future: f,
+        deadline: Some(deadline),
+    }
+}
+
 impl<F: Future> Timeout<F> {
+    /// Returns the instant at which the future expires. `None` if it never
+    /// expires.
+    #[inline(always)]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
     #[inline]
     fn pin_get_future(self: Pin<&mut Self>) -> Pin<&mut F> {
         // This is okay because `future` is pinned when `self` is.
@@ -114,6 +134,12 @@ pub trait IntoTimeout: Future + Sized {
     fn timeout(self, timeout: Duration) -> Timeout<Self> {
         self::timeout(timeout, self)
     }
+
+    /// Adds a deadline to a future. See [`Timeout`].
+    #[inline]
+    fn timeout_at(self, deadline: Instant) -> Timeout<Self> {
+        self::timeout_at(deadline, self)
+    }
 }
 
 impl<T> IntoTimeout for T where T: Future + Sized {}
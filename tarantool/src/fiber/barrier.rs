@@ -0,0 +1,140 @@
+use std::{cell::Cell, fmt};
+
+use crate::fiber::Cond;
+
+////////////////////////////////////////////////////////////////////////////////
+// Barrier
+////////////////////////////////////////////////////////////////////////////////
+
+/// A barrier enables multiple fibers to synchronize the beginning of some
+/// computation.
+///
+/// The barrier can be reused: once all of the fibers have reached it, it
+/// starts counting the fibers anew.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::rc::Rc;
+/// use tarantool::fiber::{start_proc, barrier::Barrier};
+///
+/// let barrier = Rc::new(Barrier::new(10));
+/// let jhs: Vec<_> = (0..10)
+///     .map(|_| {
+///         let barrier = barrier.clone();
+///         start_proc(move || {
+///             println!("before wait");
+///             barrier.wait();
+///             // All of the "before wait" lines are printed before any of
+///             // the "after wait" lines.
+///             println!("after wait");
+///         })
+///     })
+///     .collect();
+/// for jh in jhs {
+///     jh.join();
+/// }
+/// ```
+pub struct Barrier {
+    n: usize,
+    count: Cell<usize>,
+    generation: Cell<u64>,
+    cond: Cond,
+}
+
+impl Barrier {
+    /// Creates a new barrier that can block a given number of fibers.
+    ///
+    /// A barrier will block `n - 1` fibers which call [`Barrier::wait`] and
+    /// then wake up all of them at once when the `n`-th fiber calls it.
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            count: Cell::new(0),
+            generation: Cell::new(0),
+            cond: Cond::new(),
+        }
+    }
+
+    /// Yields the current fiber until all fibers have rendezvoused here.
+    ///
+    /// A single (arbitrary) fiber will receive a [`BarrierWaitResult`] that
+    /// returns `true` from [`BarrierWaitResult::is_leader`] when returning
+    /// from this function, and all other fibers will receive a result that
+    /// will return `false`.
+    pub fn wait(&self) -> BarrierWaitResult {
+        let generation = self.generation.get();
+        let count = self.count.get() + 1;
+        if count < self.n {
+            self.count.set(count);
+            while self.generation.get() == generation {
+                self.cond.wait();
+            }
+            BarrierWaitResult(false)
+        } else {
+            self.count.set(0);
+            self.generation.set(generation + 1);
+            self.cond.broadcast();
+            BarrierWaitResult(true)
+        }
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Barrier")
+            .field("n", &self.n)
+            .field("count", &self.count.get())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returned by [`Barrier::wait`] when all fibers in the barrier have
+/// rendezvoused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult(bool);
+
+impl BarrierWaitResult {
+    /// Returns `true` if this fiber is the "leader fiber" for the call to
+    /// [`Barrier::wait`].
+    #[inline(always)]
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use std::rc::Rc;
+
+    #[crate::test(tarantool = "crate")]
+    fn rendezvous() {
+        let barrier = Rc::new(Barrier::new(3));
+        let arrived = Rc::new(Cell::new(0));
+        for _ in 0..2 {
+            let jhs: Vec<_> = (0..2)
+                .map(|_| {
+                    let barrier = barrier.clone();
+                    let arrived = arrived.clone();
+                    fiber::start(move || {
+                        arrived.set(arrived.get() + 1);
+                        let res = barrier.wait();
+                        // Everybody has arrived.
+                        assert_eq!(arrived.get(), 3);
+                        res.is_leader()
+                    })
+                })
+                .collect();
+            assert_eq!(arrived.get(), 2);
+            arrived.set(3);
+            // The last fiber doesn't yield.
+            assert!(barrier.wait().is_leader());
+            for jh in jhs {
+                assert!(!jh.join());
+            }
+            arrived.set(0);
+        }
+    }
+}
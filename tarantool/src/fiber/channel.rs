@@ -1,5 +1,8 @@
-use std::{marker::PhantomData, mem::MaybeUninit, ptr::NonNull, rc::Rc, time::Duration};
+use std::{
+    cell::RefCell, marker::PhantomData, mem::MaybeUninit, ptr::NonNull, rc::Rc, time::Duration,
+};
 
+use super::Cond;
 use crate::{error::TarantoolErrorCode, ffi::tarantool as ffi};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -41,6 +44,7 @@ impl<T> Channel<T> {
             .expect("Memory allocation failure when creating fiber::Channel");
         Self(Rc::new(ChannelBox {
             inner,
+            watchers: RefCell::new(Vec::new()),
             marker: PhantomData,
         }))
     }
@@ -51,6 +55,7 @@ impl<T> Channel<T> {
 
     pub fn close(self) {
         unsafe { ffi::fiber_channel_close(self.as_ptr()) }
+        self.notify_watchers();
     }
 
     pub fn is_closed(&self) -> bool {
@@ -76,6 +81,44 @@ impl<T> Channel<T> {
     pub fn has_writers(&self) -> bool {
         unsafe { ffi::fiber_channel_has_writers(self.as_ptr()) }
     }
+
+    /// Returns `true` if [`Channel::try_recv`] wouldn't return
+    /// [`TryRecvError::Empty`].
+    pub(crate) fn is_recv_ready(&self) -> bool {
+        self.count() > 0 || self.has_writers() || self.is_closed()
+    }
+
+    /// Returns `true` if [`Channel::try_send`] wouldn't return
+    /// [`TrySendError::Full`].
+    pub(crate) fn is_send_ready(&self) -> bool {
+        self.count() < self.size() || self.has_readers() || self.is_closed()
+    }
+
+    /// Returns a pointer identifying the underlying channel, which is the same
+    /// for all of the clones of the `Channel`.
+    pub(crate) fn id(&self) -> *const () {
+        Rc::as_ptr(&self.0).cast()
+    }
+
+    /// Registers a cond which is broadcast every time the state of the
+    /// channel may have changed. Used to implement
+    /// [`Select`](crate::fiber::Select).
+    pub(crate) fn add_watcher(&self, cond: &Rc<Cond>) {
+        self.0.watchers.borrow_mut().push(cond.clone());
+    }
+
+    pub(crate) fn remove_watcher(&self, cond: &Rc<Cond>) {
+        self.0
+            .watchers
+            .borrow_mut()
+            .retain(|w| !Rc::ptr_eq(w, cond));
+    }
+
+    fn notify_watchers(&self) {
+        for cond in self.0.watchers.borrow().iter() {
+            cond.broadcast();
+        }
+    }
 }
 
 impl<T> SendTimeout<T> for Channel<T> {
@@ -90,6 +133,9 @@ impl<T> SendTimeout<T> for Channel<T> {
             ipc_value.data_union.data = t_box_ptr.cast();
             ipc_value.base.destroy = Some(Self::destroy_msg);
 
+            // The selecting fibers must know about a blocked writer, otherwise
+            // they would wait for the message forever.
+            self.notify_watchers();
             let ret_code = ffi::fiber_channel_put_msg_timeout(
                 self.as_ptr(),
                 ipc_value_ptr.cast(),
@@ -114,6 +160,7 @@ impl<T> SendTimeout<T> for Channel<T> {
                     Err(SendError::Disconnected(t))
                 }
             } else {
+                self.notify_watchers();
                 Ok(())
             }
         }
@@ -124,6 +171,9 @@ impl<T> RecvTimeout<T> for Channel<T> {
     fn recv_maybe_timeout(&self, timeout: Option<Duration>) -> Result<T, RecvError> {
         unsafe {
             let mut ipc_msg_ptr_uninit = MaybeUninit::uninit();
+            // Same as in `send_maybe_timeout`, a blocked reader makes the
+            // channel ready for sending.
+            self.notify_watchers();
             let ret_code = ffi::fiber_channel_get_msg_timeout(
                 self.as_ptr(),
                 ipc_msg_ptr_uninit.as_mut_ptr(),
@@ -148,6 +198,7 @@ impl<T> RecvTimeout<T> for Channel<T> {
                 let ipc_value = &mut *ipc_msg_ptr.cast::<ffi::ipc_value>();
                 let t_box = Box::from_raw(ipc_value.data_union.data.cast());
                 ffi::ipc_value_delete(ipc_msg_ptr);
+                self.notify_watchers();
                 Ok(*t_box)
             }
         }
@@ -372,6 +423,8 @@ impl<T> IntoIterator for Channel<T> {
 
 struct ChannelBox<T> {
     inner: NonNull<ffi::fiber_channel>,
+    /// Conds of the fibers waiting in [`Select`](crate::fiber::Select).
+    watchers: RefCell<Vec<Rc<Cond>>>,
     marker: PhantomData<T>,
 }
 
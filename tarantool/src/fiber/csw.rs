@@ -3,7 +3,14 @@
 //! Those are mostly used for testing.
 
 /// Returns the number of context switches of the calling fiber.
+///
+/// Uses the native tarantool api if it's supported (see
+/// [`has_fiber_id`](crate::ffi::has_fiber_id)), otherwise falls back to lua.
 pub fn csw() -> i32 {
+    if crate::ffi::has_fiber_id() {
+        return unsafe { crate::ffi::tarantool::fiber_csw(std::ptr::null()) } as _;
+    }
+
     static mut FUNCTION_DEFINED: bool = false;
     let lua = crate::lua_state();
 
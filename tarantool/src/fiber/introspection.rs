@@ -0,0 +1,283 @@
+//! Fiber introspection: ids, names and statistics of the fibers.
+//!
+//! The native tarantool api is used if it's supported (see
+//! [`ffi::has_fiber_id`](crate::ffi::has_fiber_id)), otherwise the functions
+//! fall back to the lua `fiber` module.
+use std::ffi::CStr;
+
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::ffi::has_fiber_id;
+use crate::ffi::tarantool as ffi;
+
+/// Unique id of a fiber, same as the lua `fiber.id()`.
+pub type FiberId = u64;
+
+/// Returns the id of the current fiber.
+pub fn id() -> FiberId {
+    if has_fiber_id() {
+        unsafe { ffi::fiber_id(std::ptr::null()) }
+    } else {
+        // The id is requested on every fiber-local value access, so the
+        // function is compiled once.
+        thread_local! {
+            static LUA_ID: tlua::LuaFunction<tlua::PushGuard<tlua::LuaThread>> =
+                tlua::LuaFunction::load(crate::lua_state(), "return require('fiber').id()")
+                    .expect("fiber id code is valid lua");
+        }
+        LUA_ID.with(|f| f.call().unwrap())
+    }
+}
+
+/// Returns the name of the current fiber.
+pub fn name() -> String {
+    if has_fiber_id() {
+        unsafe { name_of(std::ptr::null()) }
+    } else {
+        crate::lua_state()
+            .eval("return require('fiber').name()")
+            .unwrap()
+    }
+}
+
+/// Sets the name of the current fiber. The name is truncated if it's too
+/// long.
+pub fn set_name(name: &str) {
+    if has_fiber_id() {
+        unsafe {
+            ffi::fiber_set_name_n(std::ptr::null_mut(), name.as_ptr().cast(), name.len() as _)
+        }
+    } else {
+        crate::lua_state()
+            .exec_with("require('fiber').self():name(..., {truncate = true})", name)
+            .unwrap()
+    }
+}
+
+/// Returns a reference to the current fiber, same as the lua `fiber.self()`.
+#[inline]
+pub fn current() -> FiberRef {
+    FiberRef { id: id() }
+}
+
+/// Returns a reference to the fiber with the given `id` or `None` if there's
+/// no such fiber.
+pub fn find(id: FiberId) -> Option<FiberRef> {
+    let exists = if has_fiber_id() {
+        unsafe { !ffi::fiber_find(id).is_null() }
+    } else {
+        crate::lua_state()
+            .eval_with("return require('fiber').find(...) ~= nil", id)
+            .unwrap()
+    };
+    exists.then(|| FiberRef { id })
+}
+
+pub(super) unsafe fn name_of(f: *const ffi::Fiber) -> String {
+    CStr::from_ptr(ffi::fiber_name(f))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// A reference to a fiber by its id, see [`current`] & [`find`].
+///
+/// The reference doesn't keep the fiber alive. The methods return `None` if
+/// the fiber has finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FiberRef {
+    id: FiberId,
+}
+
+impl FiberRef {
+    #[inline(always)]
+    pub(super) fn from_id(id: FiberId) -> Self {
+        Self { id }
+    }
+
+    /// Returns the id of the fiber.
+    #[inline(always)]
+    pub fn id(&self) -> FiberId {
+        self.id
+    }
+
+    /// Returns `true` if the fiber hasn't finished yet.
+    #[inline]
+    pub fn is_alive(&self) -> bool {
+        find(self.id).is_some()
+    }
+
+    /// Returns the name of the fiber.
+    pub fn name(&self) -> Option<String> {
+        if has_fiber_id() {
+            let f = unsafe { ffi::fiber_find(self.id) };
+            (!f.is_null()).then(|| unsafe { name_of(f) })
+        } else {
+            crate::lua_state()
+                .eval_with(
+                    "local f = require('fiber').find(...)
+                    return f and f:name()",
+                    self.id,
+                )
+                .unwrap()
+        }
+    }
+
+    /// Sets the name of the fiber. The name is truncated if it's too long.
+    ///
+    /// Returns `false` if the fiber has finished.
+    pub fn set_name(&self, name: &str) -> bool {
+        if has_fiber_id() {
+            let f = unsafe { ffi::fiber_find(self.id) };
+            if f.is_null() {
+                return false;
+            }
+            unsafe { ffi::fiber_set_name_n(f, name.as_ptr().cast(), name.len() as _) };
+            true
+        } else {
+            crate::lua_state()
+                .eval_with(
+                    "local id, name = ...
+                    local f = require('fiber').find(id)
+                    if f == nil then return false end
+                    f:name(name, {truncate = true})
+                    return true",
+                    (self.id, name),
+                )
+                .unwrap()
+        }
+    }
+
+    /// Returns the number of context switches of the fiber.
+    pub fn csw(&self) -> Option<u64> {
+        if has_fiber_id() {
+            let f = unsafe { ffi::fiber_find(self.id) };
+            (!f.is_null()).then(|| unsafe { ffi::fiber_csw(f) })
+        } else {
+            crate::lua_state()
+                .eval_with(
+                    "local info = require('fiber').info({bt = false})[...]
+                    return info and info.csw",
+                    self.id,
+                )
+                .unwrap()
+        }
+    }
+}
+
+/// Information about a fiber, see [`info`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FiberInfo {
+    pub id: FiberId,
+    pub name: String,
+    /// Number of context switches.
+    pub csw: u64,
+    pub memory: FiberMemory,
+    /// Frames of the fiber's stack, only if requested.
+    pub backtrace: Vec<String>,
+}
+
+/// Memory used by a fiber, see [`FiberInfo`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FiberMemory {
+    /// Total memory occupied by the fiber in bytes.
+    pub total: u64,
+    /// Memory actually used by the fiber in bytes.
+    pub used: u64,
+}
+
+/// Returns the information about all of the fibers ordered by id, see
+/// [fiber.info()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/fiber/#fiber-info).
+///
+/// Collecting the backtraces is slow, so only do it if `backtrace` is `true`.
+pub fn info(backtrace: bool) -> Result<Vec<FiberInfo>, Error> {
+    crate::util::lua_eval_decode(&format!(
+        "(function(bt)
+            local array_mt = require('msgpack').array_mt
+            local res = setmetatable({{}}, array_mt)
+            for id, f in pairs(require('fiber').info({{bt = bt}})) do
+                local backtrace = setmetatable({{}}, array_mt)
+                for _, frame in ipairs(f.backtrace or {{}}) do
+                    table.insert(backtrace, frame.L or frame.C or tostring(frame))
+                end
+                table.insert(res, {{
+                    id = f.fid or id,
+                    name = f.name,
+                    csw = f.csw,
+                    memory = f.memory,
+                    backtrace = backtrace,
+                }})
+            end
+            table.sort(res, function(a, b) return a.id < b.id end)
+            return res
+        end)({})",
+        backtrace
+    ))
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+
+    #[crate::test(tarantool = "crate")]
+    fn current_fiber() {
+        let me = current();
+        assert_eq!(me.id(), id());
+        assert!(me.is_alive());
+        assert_eq!(find(id()), Some(me));
+        assert_eq!(me.name(), Some(name()));
+
+        let old_name = name();
+        set_name("introspection");
+        assert_eq!(name(), "introspection");
+        assert!(me.set_name(&old_name));
+        assert_eq!(name(), old_name);
+
+        let csw = me.csw().unwrap();
+        fiber::reschedule();
+        assert_eq!(me.csw(), Some(csw + 1));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn other_fiber() {
+        let (tx, rx) = fiber::r#async::oneshot::channel();
+        let jh = fiber::Builder::new()
+            .name("other")
+            .proc(move || {
+                tx.send(current()).unwrap();
+                fiber::sleep(std::time::Duration::from_millis(10));
+            })
+            .start()
+            .unwrap();
+        let other = fiber::block_on(rx).unwrap();
+        assert_ne!(other.id(), id());
+        assert_eq!(other.name().as_deref(), Some("other"));
+
+        let all = info(true).unwrap();
+        let other_info = all.iter().find(|f| f.id == other.id()).unwrap();
+        assert_eq!(other_info.name, "other");
+        assert!(other_info.memory.total > 0);
+        assert!(all.iter().any(|f| f.id == id()));
+        assert!(info(false).unwrap().iter().all(|f| f.backtrace.is_empty()));
+        assert_eq!(jh.id(), other.id());
+        assert_eq!(jh.name(), "other");
+
+        jh.join();
+        assert!(!other.is_alive());
+        assert_eq!(other.name(), None);
+        assert_eq!(other.csw(), None);
+        assert_eq!(find(other.id()), None);
+
+        let jh = fiber::Builder::new()
+            .name("deferred")
+            .func(id)
+            .defer()
+            .unwrap();
+        let jh_id = jh.id();
+        assert_eq!(jh.name(), "deferred");
+        assert_eq!(jh.join(), jh_id);
+    }
+}
@@ -0,0 +1,445 @@
+//! Fiber-local storage, see [`fiber_local!`](crate::fiber_local).
+//!
+//! The values are stored in a per-thread map keyed by the [fiber
+//! id](super::id). They are dropped when the fiber ends:
+//! - immediately after the fiber function returns for the fibers started
+//!   with [`fiber::start`](super::start), [`fiber::Builder`](super::Builder)
+//!   & co.,
+//! - when the fiber's lua `storage` table is garbage collected for all the
+//!   other fibers (e.g. fibers started from lua or iproto request handlers).
+//!
+//! Tarantool releases the `storage` of a pooled iproto fiber after every
+//! request, so the values are bound to the request rather than to the fiber:
+//! they are dropped once the released `storage` is garbage collected and the
+//! next access from the fiber initializes them anew. Until the collection the
+//! next request handled by the same fiber may still see them.
+//!
+//! Only the first access from a fiber (or after its `storage` was collected)
+//! calls into lua, the subsequent ones are plain map lookups.
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use super::FiberId;
+use crate::log::{say, SayLevel};
+
+type Values = HashMap<usize, Rc<dyn Any>>;
+
+/// Values of a single fiber, which belong to the lua `storage` the guard with
+/// the same `generation` was put into.
+struct FiberValues {
+    generation: u64,
+    values: Values,
+}
+
+thread_local! {
+    static LOCALS: RefCell<HashMap<FiberId, FiberValues>> = RefCell::new(HashMap::new());
+    static NEXT_GENERATION: Cell<u64> = Cell::new(0);
+    /// Precompiled function putting a [`Guard`] into the current fiber's lua
+    /// `storage`.
+    static REGISTER_GUARD: tlua::LuaFunction<tlua::PushGuard<tlua::LuaThread>> =
+        tlua::LuaFunction::load(
+            crate::lua_state(),
+            "require('fiber').self().storage.__rust_fiber_local = ...",
+        )
+        .expect("fiber-local guard registration code is valid lua");
+}
+
+/// Number of [`Guard`]s registered in lua, i.e. of the accesses which called
+/// into lua.
+#[cfg(feature = "internal_test")]
+thread_local! {
+    static GUARDS_REGISTERED: Cell<u64> = Cell::new(0);
+}
+
+/// Declares new fiber-local storage keys of type [`fiber::LocalKey`].
+///
+/// The syntax is the same as for [`std::thread_local!`]: any number of
+/// `static` declarations with optional attributes and visibility. Each fiber
+/// gets its own copy of the value, which is lazily initialized with the given
+/// expression on the first access from that fiber.
+///
+/// Tarantool reuses the fibers which handle iproto requests, but releases
+/// their lua `storage` after every request. The values are bound to the
+/// `storage`, so they are dropped once it's garbage collected and the next
+/// access initializes them anew. Until then the next request handled by the
+/// same fiber may still see them.
+///
+/// # Example
+/// ```no_run
+/// use std::cell::Cell;
+/// use tarantool::fiber;
+///
+/// tarantool::fiber_local! {
+///     static TRACE_ID: Cell<u64> = Cell::new(0);
+/// }
+///
+/// TRACE_ID.set(42);
+/// fiber::start_proc(|| {
+///     // Each fiber has its own value.
+///     assert_eq!(TRACE_ID.get(), 0);
+///     TRACE_ID.set(13);
+/// })
+/// .join();
+/// assert_eq!(TRACE_ID.get(), 42);
+/// ```
+///
+/// [`fiber::LocalKey`]: crate::fiber::LocalKey
+#[macro_export]
+macro_rules! fiber_local {
+    () => {};
+    (
+        $(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr;
+        $($rest:tt)*
+    ) => {
+        $crate::fiber_local!($(#[$attr])* $vis static $name: $t = $init);
+        $crate::fiber_local!($($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr) => {
+        $(#[$attr])*
+        $vis static $name: $crate::fiber::LocalKey<$t> = {
+            fn __init() -> $t {
+                $init
+            }
+            $crate::fiber::LocalKey::new(__init)
+        };
+    };
+}
+
+/// A key for a fiber-local value, declared with the
+/// [`fiber_local!`](crate::fiber_local) macro.
+///
+/// Similarly to [`std::thread::LocalKey`] the value can only be accessed by
+/// reference via [`LocalKey::with`], so use a [`Cell`] or a [`RefCell`] for
+/// values which need to be modified. There are shortcuts for these two
+/// cases, e.g. [`LocalKey::set`].
+pub struct LocalKey<T: 'static> {
+    init: fn() -> T,
+}
+
+impl<T: 'static> LocalKey<T> {
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> T) -> Self {
+        Self { init }
+    }
+
+    /// Calls `f` with a reference to the current fiber's value, initializing
+    /// it first if this is the first access from the current fiber.
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let value = self.get_or_init();
+        f(&value)
+    }
+
+    fn get_or_init(&'static self) -> Rc<T> {
+        let fiber_id = super::id();
+        let key = self as *const Self as usize;
+        let (has_values, value) = LOCALS.with(|l| match l.borrow().get(&fiber_id) {
+            Some(f) => (true, f.values.get(&key).cloned()),
+            None => (false, None),
+        });
+        if let Some(value) = value {
+            return downcast(value);
+        }
+        if !has_values {
+            register_guard(fiber_id);
+        }
+
+        // The storage mustn't be borrowed while the user code is running,
+        // because it may access other fiber-local values.
+        let mut new_value: Option<Rc<dyn Any>> = Some(Rc::new((self.init)()));
+        let value = LOCALS.with(|l| {
+            l.borrow_mut()
+                .entry(fiber_id)
+                // The values may have been dropped while the initializer was
+                // running.
+                .or_insert_with(|| FiberValues {
+                    generation: next_generation(),
+                    values: Values::new(),
+                })
+                .values
+                .entry(key)
+                // The initializer may have set the value recursively.
+                .or_insert_with(|| new_value.take().unwrap())
+                .clone()
+        });
+        drop(new_value);
+        downcast(value)
+    }
+}
+
+impl<T: 'static> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKey").finish_non_exhaustive()
+    }
+}
+
+impl<T: Copy + 'static> LocalKey<Cell<T>> {
+    /// Returns a copy of the current fiber's value.
+    #[inline]
+    pub fn get(&'static self) -> T {
+        self.with(Cell::get)
+    }
+}
+
+impl<T: 'static> LocalKey<Cell<T>> {
+    /// Sets the current fiber's value.
+    #[inline]
+    pub fn set(&'static self, value: T) {
+        self.with(|cell| cell.set(value))
+    }
+
+    /// Replaces the current fiber's value, returning the old one.
+    #[inline]
+    pub fn replace(&'static self, value: T) -> T {
+        self.with(|cell| cell.replace(value))
+    }
+
+    /// Takes the current fiber's value, leaving `Default::default()` in its
+    /// place.
+    #[inline]
+    pub fn take(&'static self) -> T
+    where
+        T: Default,
+    {
+        self.with(Cell::take)
+    }
+}
+
+impl<T: 'static> LocalKey<RefCell<T>> {
+    /// Calls `f` with a shared borrow of the current fiber's value.
+    ///
+    /// # Panics
+    /// Panics if the value is currently mutably borrowed.
+    #[inline]
+    pub fn with_borrow<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.with(|cell| f(&cell.borrow()))
+    }
+
+    /// Calls `f` with a mutable borrow of the current fiber's value.
+    ///
+    /// # Panics
+    /// Panics if the value is currently borrowed.
+    #[inline]
+    pub fn with_borrow_mut<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        self.with(|cell| f(&mut cell.borrow_mut()))
+    }
+
+    /// Sets the current fiber's value.
+    ///
+    /// # Panics
+    /// Panics if the value is currently borrowed.
+    #[inline]
+    pub fn set(&'static self, value: T) {
+        self.with_borrow_mut(|v| *v = value)
+    }
+
+    /// Replaces the current fiber's value, returning the old one.
+    ///
+    /// # Panics
+    /// Panics if the value is currently borrowed.
+    #[inline]
+    pub fn replace(&'static self, value: T) -> T {
+        self.with(|cell| cell.replace(value))
+    }
+
+    /// Takes the current fiber's value, leaving `Default::default()` in its
+    /// place.
+    ///
+    /// # Panics
+    /// Panics if the value is currently borrowed.
+    #[inline]
+    pub fn take(&'static self) -> T
+    where
+        T: Default,
+    {
+        self.with(RefCell::take)
+    }
+}
+
+fn downcast<T: 'static>(value: Rc<dyn Any>) -> Rc<T> {
+    value
+        .downcast()
+        .unwrap_or_else(|_| unreachable!("fiber-local value has a different type"))
+}
+
+/// Drops all of the fiber-local values of the current fiber.
+///
+/// This function is called right after the fiber function returns.
+pub(crate) fn clear_current() {
+    let is_empty = LOCALS.try_with(|l| l.borrow().is_empty()).unwrap_or(true);
+    if !is_empty {
+        remove(super::id(), None);
+    }
+}
+
+/// Drops the values of the fiber, if they still belong to the given
+/// `generation` of the fiber's lua `storage`, or any values if it's `None`.
+fn remove(fiber_id: FiberId, generation: Option<u64>) {
+    // The values are dropped after the storage is released, because their
+    // destructors may access other fiber-local values.
+    let values = LOCALS
+        .try_with(|l| {
+            let mut l = l.borrow_mut();
+            let is_newer = matches!(
+                (l.get(&fiber_id), generation),
+                (Some(f), Some(generation)) if f.generation != generation
+            );
+            if is_newer {
+                None
+            } else {
+                l.remove(&fiber_id)
+            }
+        })
+        .ok()
+        .flatten();
+    drop(values);
+}
+
+fn next_generation() -> u64 {
+    NEXT_GENERATION.with(|g| {
+        let generation = g.get();
+        g.set(generation + 1);
+        generation
+    })
+}
+
+/// Puts a new [`Guard`] into the lua `fiber.self().storage`, so that the
+/// values are dropped along with the storage even if the fiber wasn't started
+/// from rust. Called on the first access from the fiber, or the first one
+/// after the values of the previous storage were dropped.
+///
+/// A guard of the previous storage or the one left after [`clear_current`]
+/// is replaced, it doesn't drop anything once collected, because its
+/// generation no longer matches.
+fn register_guard(fiber_id: FiberId) {
+    let generation = next_generation();
+    LOCALS.with(|l| {
+        l.borrow_mut().insert(
+            fiber_id,
+            FiberValues {
+                generation,
+                values: Values::new(),
+            },
+        )
+    });
+    #[cfg(feature = "internal_test")]
+    GUARDS_REGISTERED.with(|c| c.set(c.get() + 1));
+    let res = REGISTER_GUARD.with(|f| {
+        f.call_with_args::<(), _>(Guard {
+            fiber_id,
+            generation,
+        })
+    });
+    if let Err(e) = res {
+        let msg = format!("failed to register fiber-local storage guard: {}", e);
+        say(SayLevel::Warn, std::file!(), std::line!() as _, None, &msg);
+    }
+}
+
+/// Drops the values of a fiber when its lua `storage` is garbage collected.
+struct Guard {
+    fiber_id: FiberId,
+    generation: u64,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        // The fiber may be alive and have a newer storage by now, whose
+        // values must be kept.
+        remove(self.fiber_id, Some(self.generation));
+    }
+}
+
+impl<L: tlua::AsLua> tlua::PushInto<L> for Guard {
+    type Err = tlua::Void;
+
+    fn push_into_lua(self, lua: L) -> tlua::PushIntoResult<L, Self> {
+        Ok(tlua::push_userdata(self, lua, |_| {}))
+    }
+}
+
+impl<L: tlua::AsLua> tlua::PushOneInto<L> for Guard {}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use crate::fiber;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    crate::fiber_local! {
+        static COUNTER: Cell<u32> = Cell::new(0);
+        static NAMES: RefCell<Vec<String>> = RefCell::new(vec![]);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn value_per_fiber() {
+        COUNTER.set(1);
+        NAMES.with_borrow_mut(|names| names.push("main".into()));
+
+        let jh = fiber::start(|| {
+            assert_eq!(COUNTER.get(), 0);
+            COUNTER.set(2);
+            fiber::reschedule();
+            NAMES.with_borrow_mut(|names| names.push("other".into()));
+            (COUNTER.get(), NAMES.take())
+        });
+        assert_eq!(COUNTER.get(), 1);
+        assert_eq!(jh.join(), (2, vec!["other".to_string()]));
+
+        assert_eq!(COUNTER.replace(0), 1);
+        assert_eq!(NAMES.take(), vec!["main".to_string()]);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn lua_called_on_first_access_only() {
+        let registered = || super::GUARDS_REGISTERED.with(Cell::get);
+        fiber::start_proc(move || {
+            let before = registered();
+            COUNTER.set(1);
+            assert_eq!(registered(), before + 1);
+            for _ in 0..100 {
+                COUNTER.set(COUNTER.get() + 1);
+                NAMES.with_borrow_mut(|names| names.clear());
+            }
+            assert_eq!(COUNTER.get(), 101);
+            assert_eq!(registered(), before + 1);
+        })
+        .join();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn dropped_when_fiber_ends() {
+        struct DropFlag(Rc<Cell<bool>>);
+
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.set(true);
+            }
+        }
+
+        crate::fiber_local! {
+            static FLAG: RefCell<Option<DropFlag>> = RefCell::new(None);
+        }
+
+        let is_dropped = Rc::new(Cell::new(false));
+        let is_dropped_2 = is_dropped.clone();
+        let jh = fiber::start_proc(move || {
+            FLAG.set(Some(DropFlag(is_dropped_2)));
+            fiber::reschedule();
+        });
+        assert!(!is_dropped.get());
+        jh.join();
+        assert!(is_dropped.get());
+    }
+}
@@ -0,0 +1,177 @@
+use std::{cell::Cell, fmt};
+
+use crate::fiber::Cond;
+
+////////////////////////////////////////////////////////////////////////////////
+// Once
+////////////////////////////////////////////////////////////////////////////////
+
+/// A synchronization primitive which can be used to run a one-time
+/// initialization, which may yield.
+///
+/// Unlike [`std::sync::Once`], if the initialization yields, the other fibers
+/// calling [`Once::call_once`] wait until it's complete.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::rc::Rc;
+/// use std::time::Duration;
+/// use tarantool::fiber::{self, once::Once};
+///
+/// let once = Rc::new(Once::new());
+/// let c_once = once.clone();
+/// let jh = fiber::start_proc(move || {
+///     c_once.call_once(|| fiber::sleep(Duration::from_millis(10)));
+/// });
+/// // Waits until the initialization in the other fiber is complete.
+/// once.call_once(|| unreachable!());
+/// assert!(once.is_completed());
+/// jh.join();
+/// ```
+pub struct Once {
+    state: Cell<State>,
+    cond: Cond,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Incomplete,
+    Running,
+    Complete,
+}
+
+impl Once {
+    /// Creates a new `Once` value.
+    pub fn new() -> Self {
+        Self {
+            state: Cell::new(State::Incomplete),
+            cond: Cond::new(),
+        }
+    }
+
+    /// Performs an initialization routine once and only once. The given
+    /// closure will be executed if this is the first time `call_once` has
+    /// been called, and otherwise the routine will *not* be invoked.
+    ///
+    /// If the routine is being executed by another fiber, the current fiber
+    /// **yields** until it's complete.
+    ///
+    /// If the routine panics, the `Once` is left incomplete, so the next
+    /// `call_once` (e.g. in one of the waiting fibers) runs its own routine.
+    pub fn call_once<F>(&self, f: F)
+    where
+        F: FnOnce(),
+    {
+        loop {
+            match self.state.get() {
+                State::Complete => return,
+                State::Running => {
+                    self.cond.wait();
+                }
+                State::Incomplete => {
+                    self.state.set(State::Running);
+                    let guard = Finish(self);
+                    f();
+                    self.state.set(State::Complete);
+                    drop(guard);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if some [`Once::call_once`] call has completed
+    /// successfully.
+    #[inline]
+    pub fn is_completed(&self) -> bool {
+        self.state.get() == State::Complete
+    }
+}
+
+/// Wakes up the waiting fibers when the routine returns. Resets the state if
+/// the routine panics.
+struct Finish<'a>(&'a Once);
+
+impl Drop for Finish<'_> {
+    fn drop(&mut self) {
+        if self.0.state.get() == State::Running {
+            self.0.state.set(State::Incomplete);
+        }
+        self.0.cond.broadcast();
+    }
+}
+
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Once {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Once")
+            .field("state", &self.state.get())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use std::rc::Rc;
+
+    #[crate::test(tarantool = "crate")]
+    fn waits_for_running_initialization() {
+        let once = Rc::new(Once::new());
+        let calls = Rc::new(Cell::new(0));
+        let jhs: Vec<_> = (0..3)
+            .map(|_| {
+                let once = once.clone();
+                let calls = calls.clone();
+                fiber::start(move || {
+                    once.call_once(|| {
+                        fiber::reschedule();
+                        calls.set(calls.get() + 1);
+                    });
+                    // The initialization is complete in each of the fibers.
+                    calls.get()
+                })
+            })
+            .collect();
+        assert!(!once.is_completed());
+        for jh in jhs {
+            assert_eq!(jh.join(), 1);
+        }
+        assert!(once.is_completed());
+        once.call_once(|| unreachable!());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn panic_leaves_incomplete() {
+        let once = Rc::new(Once::new());
+        let c_once = once.clone();
+        // The deferred fiber starts waiting while the routine is running.
+        let jh = fiber::Builder::new()
+            .func(move || {
+                let mut called = false;
+                c_once.call_once(|| called = true);
+                called
+            })
+            .defer()
+            .unwrap();
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            once.call_once(|| {
+                fiber::reschedule();
+                panic!("initialization failed");
+            })
+        }));
+        assert!(res.is_err());
+        assert!(!once.is_completed());
+        // The waiting fiber runs its own routine instead.
+        assert!(jh.join());
+        assert!(once.is_completed());
+    }
+}
@@ -0,0 +1,340 @@
+//! A pool of worker fibers with a bounded job queue, see [`Pool`].
+use std::cell::Cell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+use std::time::Duration;
+
+use super::{Channel, SendError, UnitJoinHandle};
+use crate::error::{TarantoolError, TarantoolErrorCode};
+use crate::Result;
+
+type Job = Box<dyn FnOnce()>;
+
+/// Messages of the job queue. `None` tells a worker to stop.
+type Message = Option<Job>;
+
+////////////////////////////////////////////////////////////////////////////////
+// Builder
+////////////////////////////////////////////////////////////////////////////////
+
+/// Pool factory, which can be used in order to configure the properties of a
+/// new [`Pool`].
+#[derive(Debug, Clone)]
+pub struct Builder {
+    workers: usize,
+    queue_size: u32,
+    name: String,
+    stack_size: Option<usize>,
+}
+
+impl Builder {
+    /// Generates the base configuration for a pool of `workers` fibers.
+    ///
+    /// By default the job queue can hold as many jobs as there are workers
+    /// (but at most `u32::MAX`).
+    ///
+    /// `workers` must not be zero, otherwise [`Builder::build`] returns an
+    /// error.
+    pub fn new(workers: usize) -> Self {
+        Self {
+            workers,
+            queue_size: u32::try_from(workers).unwrap_or(u32::MAX),
+            name: "pool".into(),
+            stack_size: None,
+        }
+    }
+
+    /// Sets the maximum number of jobs waiting for a free worker. When the
+    /// queue is full [`Pool::execute`] waits.
+    pub fn queue_size(mut self, queue_size: u32) -> Self {
+        self.queue_size = queue_size;
+        self
+    }
+
+    /// Sets the name prefix of the worker fibers. The workers are named
+    /// `"<name>/<index>"`.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets the size of the stack (in bytes) for the worker fibers.
+    pub fn stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = Some(stack_size);
+        self
+    }
+
+    /// Starts the worker fibers and returns the pool.
+    ///
+    /// Returns an error if the number of workers is zero or a worker fiber
+    /// couldn't be started, e.g. because of an invalid stack size. The workers
+    /// started so far are stopped in this case.
+    pub fn build(self) -> Result<Pool> {
+        if self.workers == 0 {
+            return Err(crate::set_and_get_error!(
+                TarantoolErrorCode::IllegalParams,
+                "pool must have at least one worker"
+            )
+            .into());
+        }
+        let mut pool = Pool {
+            jobs: Channel::new(self.queue_size),
+            workers: Vec::with_capacity(self.workers),
+            stats: Default::default(),
+        };
+        for i in 0..self.workers {
+            let mut builder = super::Builder::new().name(format!("{}/{}", self.name, i));
+            if let Some(stack_size) = self.stack_size {
+                builder = builder.stack_size(stack_size)?;
+            }
+            let jobs = pool.jobs.clone();
+            let stats = pool.stats.clone();
+            let worker = builder.proc(move || worker(jobs, stats)).start()?;
+            pool.workers.push(worker);
+        }
+        Ok(pool)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Pool
+////////////////////////////////////////////////////////////////////////////////
+
+/// A fixed number of worker fibers executing jobs from a bounded queue.
+///
+/// Unlike starting a fiber per job, the pool limits the number of jobs
+/// executed concurrently and applies backpressure: [`Pool::execute`] waits
+/// while the queue is full.
+///
+/// The queue is implemented with a [`fiber::Channel`], see
+/// [`ffi::has_fiber_channel`] for the supported versions of tarantool.
+///
+/// When the pool is [shut down](Pool::shutdown) or dropped, the jobs which are
+/// already in the queue are executed and the worker fibers are joined.
+///
+/// # Example
+/// ```no_run
+/// use tarantool::fiber::pool::Pool;
+///
+/// let pool = Pool::new(4, 16).unwrap();
+/// for index_id in 0..10 {
+///     pool.execute(move || println!("rebuilding index {}", index_id))
+///         .unwrap();
+/// }
+/// println!("{:?}", pool.metrics());
+/// pool.shutdown();
+/// ```
+///
+/// [`fiber::Channel`]: crate::fiber::Channel
+/// [`ffi::has_fiber_channel`]: crate::ffi::has_fiber_channel
+pub struct Pool {
+    jobs: Channel<Message>,
+    workers: Vec<UnitJoinHandle<'static>>,
+    stats: Rc<Stats>,
+}
+
+#[derive(Default)]
+struct Stats {
+    busy_workers: Cell<usize>,
+    completed_jobs: Cell<u64>,
+}
+
+impl Pool {
+    /// Starts a pool with `workers` worker fibers and a queue of at most
+    /// `queue_size` jobs.
+    ///
+    /// Use [`Builder`] for more options.
+    #[inline]
+    pub fn new(workers: usize, queue_size: u32) -> Result<Self> {
+        Builder::new(workers).queue_size(queue_size).build()
+    }
+
+    /// Puts the job into the queue, waiting while the queue is full.
+    ///
+    /// Returns an error if the current fiber is cancelled while waiting.
+    pub fn execute<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce() + 'static,
+    {
+        self.jobs
+            .send(Some(Box::new(f)))
+            .map_err(|_| TarantoolError::last().into())
+    }
+
+    /// Puts the job into the queue, waiting at most `timeout` while the queue
+    /// is full.
+    ///
+    /// Returns an error if the timeout expires or the current fiber is
+    /// cancelled while waiting.
+    pub fn execute_timeout<F>(&self, f: F, timeout: Duration) -> Result<()>
+    where
+        F: FnOnce() + 'static,
+    {
+        self.jobs
+            .send_timeout(Some(Box::new(f)), timeout)
+            .map_err(|_: SendError<_>| TarantoolError::last().into())
+    }
+
+    /// Puts the job into the queue if it's not full, otherwise returns the job
+    /// back.
+    pub fn try_execute<F>(&self, f: F) -> std::result::Result<(), F>
+    where
+        F: FnOnce() + 'static,
+    {
+        // Sending doesn't yield if there is room in the queue or a worker
+        // waiting for a job, so nothing can change in between.
+        if self.jobs.count() >= self.jobs.size() && !self.jobs.has_readers() {
+            return Err(f);
+        }
+        if self.jobs.try_send(Some(Box::new(f))).is_err() {
+            unreachable!("the queue must have room for the job");
+        }
+        Ok(())
+    }
+
+    /// Returns the current metrics of the pool.
+    pub fn metrics(&self) -> PoolMetrics {
+        PoolMetrics {
+            workers: self.workers.len(),
+            busy_workers: self.stats.busy_workers.get(),
+            queue_len: self.jobs.count() as _,
+            queue_size: self.jobs.size() as _,
+            completed_jobs: self.stats.completed_jobs.get(),
+        }
+    }
+
+    /// Stops the pool: waits until the jobs already in the queue are executed
+    /// and joins the worker fibers.
+    #[inline]
+    pub fn shutdown(self) {
+        drop(self)
+    }
+
+    fn stop(&mut self) {
+        for _ in 0..self.workers.len() {
+            if self.jobs.send(None).is_err() {
+                // The current fiber is cancelled. The workers are stopped
+                // anyway, but the jobs in the queue are discarded.
+                self.jobs.clone().close();
+                break;
+            }
+        }
+        for worker in self.workers.drain(..) {
+            worker.join();
+        }
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        self.stop()
+    }
+}
+
+impl std::fmt::Debug for Pool {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Pool")
+            .field("metrics", &self.metrics())
+            .finish_non_exhaustive()
+    }
+}
+
+fn worker(jobs: Channel<Message>, stats: Rc<Stats>) {
+    // `recv` returns `None` if the queue is closed.
+    while let Some(Some(job)) = jobs.recv() {
+        stats.busy_workers.set(stats.busy_workers.get() + 1);
+        job();
+        stats.busy_workers.set(stats.busy_workers.get() - 1);
+        stats.completed_jobs.set(stats.completed_jobs.get() + 1);
+    }
+}
+
+/// A snapshot of the [`Pool`] metrics, see [`Pool::metrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolMetrics {
+    /// Total number of worker fibers.
+    pub workers: usize,
+    /// Number of workers executing a job at the moment.
+    pub busy_workers: usize,
+    /// Number of jobs waiting in the queue.
+    pub queue_len: usize,
+    /// Maximum number of jobs in the queue.
+    pub queue_size: usize,
+    /// Number of jobs executed since the pool was started.
+    pub completed_jobs: u64,
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use std::cell::RefCell;
+
+    #[crate::test(tarantool = "crate")]
+    fn bounded_concurrency() {
+        if !crate::ffi::has_fiber_channel() {
+            return;
+        }
+        let pool = Pool::new(2, 2).unwrap();
+        let done = Rc::new(RefCell::new(vec![]));
+        let cond = Rc::new(fiber::Cond::new());
+        for i in 0..4 {
+            let done = done.clone();
+            let cond = cond.clone();
+            pool.try_execute(move || {
+                cond.wait();
+                done.borrow_mut().push(i);
+            })
+            .unwrap_or_else(|_| panic!("job {} didn't fit", i));
+        }
+        // Let the workers pick up the jobs.
+        fiber::reschedule();
+        // Both workers are busy and the queue is full.
+        assert!(pool.try_execute(|| ()).is_err());
+        let metrics = pool.metrics();
+        assert_eq!(metrics.workers, 2);
+        assert_eq!(metrics.busy_workers, 2);
+        assert_eq!(metrics.queue_len, 2);
+        assert_eq!(metrics.queue_size, 2);
+        let res = pool.execute_timeout(|| (), Duration::from_millis(10));
+        assert!(res.is_err());
+
+        cond.broadcast();
+        fiber::reschedule();
+        cond.broadcast();
+        pool.execute(|| ()).unwrap();
+        pool.shutdown();
+        assert_eq!(done.borrow().len(), 4);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn no_workers() {
+        assert!(Builder::new(0).build().is_err());
+        assert!(Pool::new(0, 1).is_err());
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn shutdown_executes_queued_jobs() {
+        if !crate::ffi::has_fiber_channel() {
+            return;
+        }
+        let pool = Builder::new(1)
+            .name("test_pool")
+            .queue_size(10)
+            .build()
+            .unwrap();
+        let counter = Rc::new(Cell::new(0));
+        for _ in 0..10 {
+            let counter = counter.clone();
+            pool.execute(move || {
+                assert!(fiber::name().starts_with("test_pool/"));
+                fiber::reschedule();
+                counter.set(counter.get() + 1);
+            })
+            .unwrap();
+        }
+        assert!(counter.get() < 10);
+        drop(pool);
+        assert_eq!(counter.get(), 10);
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    cell::{Cell, UnsafeCell},
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use crate::fiber::Cond;
+
+////////////////////////////////////////////////////////////////////////////////
+// RwLock
+////////////////////////////////////////////////////////////////////////////////
+
+/// A reader-writer lock for fibers.
+///
+/// The blocking counterpart of
+/// [`r#async::RwLock`](crate::fiber::r#async::RwLock) with the same locking
+/// policy, except that the locking methods **yield** the current fiber until
+/// the lock can be acquired.
+pub struct RwLock<T: ?Sized> {
+    readers: Cell<usize>,
+    is_writing: Cell<bool>,
+    waiting_writers: Cell<usize>,
+    cond: Cond,
+    data: UnsafeCell<T>,
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Creates a new lock in an unlocked state ready for use.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tarantool::fiber::rwlock::RwLock;
+    ///
+    /// let lock = RwLock::new(0);
+    /// ```
+    pub fn new(t: T) -> RwLock<T>
+    where
+        T: Sized,
+    {
+        RwLock {
+            readers: Cell::new(0),
+            is_writing: Cell::new(false),
+            waiting_writers: Cell::new(0),
+            cond: Cond::new(),
+            data: UnsafeCell::new(t),
+        }
+    }
+
+    /// Locks this lock with shared read access, yielding the current fiber
+    /// until there are no writers holding or waiting for the lock.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::rc::Rc;
+    /// use tarantool::fiber::{start_proc, rwlock::RwLock};
+    ///
+    /// let lock = Rc::new(RwLock::new(1));
+    /// let c_lock = Rc::clone(&lock);
+    ///
+    /// let r = lock.read();
+    /// start_proc(move || {
+    ///     // Many readers can hold the lock at the same time.
+    ///     assert_eq!(*c_lock.read(), 1);
+    /// }).join();
+    /// assert_eq!(*r, 1);
+    /// ```
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_read() {
+                return guard;
+            }
+            self.cond.wait();
+        }
+    }
+
+    /// Locks this lock with exclusive write access, yielding the current
+    /// fiber until there are no other readers or writers holding the lock.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tarantool::fiber::rwlock::RwLock;
+    ///
+    /// let lock = RwLock::new(1);
+    /// *lock.write() += 1;
+    /// assert_eq!(*lock.read(), 2);
+    /// ```
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        if let Some(guard) = self.try_write() {
+            return guard;
+        }
+        self.waiting_writers.set(self.waiting_writers.get() + 1);
+        loop {
+            self.cond.wait();
+            if !self.is_writing.get() && self.readers.get() == 0 {
+                self.waiting_writers.set(self.waiting_writers.get() - 1);
+                return RwLockWriteGuard::new(self);
+            }
+        }
+    }
+
+    /// Attempts to acquire this lock with shared read access.
+    ///
+    /// If the lock could not be acquired at this time, then `None` is returned.
+    ///
+    /// This function does not yield.
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        if self.is_writing.get() || self.waiting_writers.get() > 0 {
+            return None;
+        }
+        self.readers.set(self.readers.get() + 1);
+        Some(RwLockReadGuard { lock: self })
+    }
+
+    /// Attempts to acquire this lock with exclusive write access.
+    ///
+    /// If the lock could not be acquired at this time, then `None` is returned.
+    ///
+    /// This function does not yield.
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        if self.is_writing.get() || self.readers.get() > 0 {
+            return None;
+        }
+        Some(RwLockWriteGuard::new(self))
+    }
+
+    /// Consumes this lock, returning the underlying data.
+    pub fn into_inner(self) -> T
+    where
+        T: Sized,
+    {
+        self.data.into_inner()
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// Since this call borrows the `RwLock` mutably, no actual locking needs to
+    /// take place -- the mutable borrow statically guarantees no locks exist.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    /// Creates a new lock in an unlocked state ready for use.
+    /// This is equivalent to [`RwLock::new`].
+    fn from(t: T) -> Self {
+        RwLock::new(t)
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    /// Creates a `RwLock<T>`, with the `Default` value for T.
+    fn default() -> RwLock<T> {
+        RwLock::new(Default::default())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        if self.is_writing.get() {
+            d.field("data", &format_args!("<locked>"));
+        } else {
+            d.field("data", &unsafe { &*self.data.get() });
+        }
+        d.field("readers", &self.readers.get());
+        d.finish_non_exhaustive()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// RwLockReadGuard
+////////////////////////////////////////////////////////////////////////////////
+
+/// A handle to a [`RwLock`] held with shared read access.
+pub struct RwLockReadGuard<'a, T: ?Sized + 'a> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T: ?Sized + 'a> Drop for RwLockReadGuard<'a, T> {
+    fn drop(&mut self) {
+        let readers = self.lock.readers.get() - 1;
+        self.lock.readers.set(readers);
+        if readers == 0 {
+            self.lock.cond.broadcast();
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// RwLockWriteGuard
+////////////////////////////////////////////////////////////////////////////////
+
+/// A handle to a [`RwLock`] held with exclusive write access.
+pub struct RwLockWriteGuard<'a, T: ?Sized + 'a> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T: ?Sized> RwLockWriteGuard<'a, T> {
+    fn new(lock: &'a RwLock<T>) -> Self {
+        lock.is_writing.set(true);
+        Self { lock }
+    }
+}
+
+impl<'a, T: ?Sized + 'a> Drop for RwLockWriteGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.is_writing.set(false);
+        self.lock.cond.broadcast();
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use std::rc::Rc;
+
+    #[crate::test(tarantool = "crate")]
+    fn many_readers() {
+        let lock = Rc::new(RwLock::new(0));
+        let r = lock.read();
+        let jhs: Vec<_> = (0..3)
+            .map(|_| {
+                let lock = lock.clone();
+                fiber::start(move || {
+                    let r = lock.read();
+                    fiber::reschedule();
+                    *r
+                })
+            })
+            .collect();
+        // All of the readers hold the lock at once.
+        assert_eq!(lock.readers.get(), 4);
+        assert!(lock.try_write().is_none());
+        drop(r);
+        for jh in jhs {
+            assert_eq!(jh.join(), 0);
+        }
+        *lock.try_write().unwrap() = 1;
+        assert_eq!(*lock.read(), 1);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn writer_is_preferred() {
+        let lock = Rc::new(RwLock::new(0));
+        let r = lock.read();
+
+        let c_lock = lock.clone();
+        let writer = fiber::start_proc(move || *c_lock.write() += 1);
+        // The writer waits, so new readers wait too.
+        assert!(lock.try_read().is_none());
+        let c_lock = lock.clone();
+        let reader = fiber::start(move || *c_lock.read());
+
+        drop(r);
+        writer.join();
+        assert_eq!(reader.join(), 1);
+        assert_eq!(Rc::try_unwrap(lock).unwrap().into_inner(), 1);
+    }
+}
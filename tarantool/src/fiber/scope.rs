@@ -0,0 +1,238 @@
+//! See [`scope`] for examples and docs.
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+use super::{FiberState, JoinHandle, UnitJoinHandle};
+use crate::ffi::tarantool as ffi;
+
+/// Creates a scope for spawning fibers which may borrow local variables.
+///
+/// The function `f` is called with a [`Scope`] which can be used to spawn
+/// fibers. When `f` returns (or panics), all of the fibers spawned in the
+/// scope which haven't been joined yet are
+/// [cancelled](super::JoinHandle::cancel) and then joined, so none of them
+/// outlive the scope. Dropping the join handle of such a fiber doesn't cancel
+/// it before the end of the scope.
+///
+/// # Example
+/// ```no_run
+/// use tarantool::fiber;
+/// use std::time::Duration;
+///
+/// let mut results = vec![];
+/// let total = fiber::scope(|s| {
+///     let results = &mut results;
+///     let worker = s.spawn(move || {
+///         results.push(1);
+///         results.len()
+///     });
+///     // The cancelled fiber must check for cancellation and return.
+///     s.spawn_proc(|| {
+///         while !fiber::is_cancelled() {
+///             fiber::sleep(Duration::from_secs(1));
+///         }
+///     });
+///     worker.join()
+/// });
+/// assert_eq!(total, 1);
+/// assert_eq!(results, [1]);
+/// ```
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        children: Default::default(),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+    // Joins the children even if `f` panics, because they may borrow
+    // something from the stack frames being unwound.
+    let _guard = CancelAndJoinOnDrop(&scope);
+    f(&scope)
+}
+
+struct CancelAndJoinOnDrop<'a, 'scope, 'env>(&'a Scope<'scope, 'env>);
+
+impl Drop for CancelAndJoinOnDrop<'_, '_, '_> {
+    fn drop(&mut self) {
+        self.0.cancel_and_join();
+    }
+}
+
+/// A scope to spawn fibers in, see [`scope`].
+pub struct Scope<'scope, 'env: 'scope> {
+    children: RefCell<Vec<(NonNull<ffi::Fiber>, Rc<FiberState>)>>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl std::fmt::Debug for Scope<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Scope").finish_non_exhaustive()
+    }
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Creates a new fiber within the scope and **yields** execution to it
+    /// immediately, see [`fiber::start`](super::start) for details.
+    ///
+    /// The fiber is cancelled and joined at the end of the scope unless the
+    /// returned handle is joined before that. Unlike the fibers started with
+    /// [`fiber::start`](super::start) dropping the handle doesn't cancel the
+    /// fiber.
+    pub fn spawn<F, T>(&'scope self, f: F) -> JoinHandle<'scope, T>
+    where
+        F: FnOnce() -> T,
+        F: 'scope,
+        T: 'scope,
+    {
+        let jh = super::start(f);
+        self.add_child(jh.inner, &jh.state);
+        jh
+    }
+
+    /// Creates a new proc fiber within the scope, see [`Scope::spawn`] &
+    /// [`fiber::start_proc`](super::start_proc) for details.
+    pub fn spawn_proc<F>(&'scope self, f: F) -> UnitJoinHandle<'scope>
+    where
+        F: FnOnce(),
+        F: 'scope,
+    {
+        let jh = super::start_proc(f);
+        self.add_child(jh.inner, &jh.state);
+        jh
+    }
+
+    fn add_child(&self, inner: Option<NonNull<ffi::Fiber>>, state: &Rc<FiberState>) {
+        let inner = inner.expect("a new fiber is not joined yet");
+        state.is_scoped.set(true);
+        self.children.borrow_mut().push((inner, state.clone()));
+    }
+
+    fn cancel_and_join(&self) {
+        // The children may spawn more fibers while they're being joined.
+        loop {
+            let children = std::mem::take(&mut *self.children.borrow_mut());
+            if children.is_empty() {
+                break;
+            }
+            for (inner, state) in &children {
+                unsafe { state.cancel(*inner) }
+            }
+            for (inner, state) in &children {
+                unsafe { state.join(*inner) }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    #[crate::test(tarantool = "crate")]
+    fn children_are_cancelled() {
+        let cancelled = Cell::new(0);
+        let mut finished = false;
+        let res = scope(|s| {
+            for _ in 0..3 {
+                s.spawn_proc(|| {
+                    while fiber::check_cancelled().is_ok() {
+                        fiber::sleep(Duration::from_secs(1));
+                    }
+                    cancelled.set(cancelled.get() + 1);
+                });
+            }
+            // Joined explicitly, so it isn't cancelled.
+            let jh = s.spawn(|| {
+                fiber::reschedule();
+                finished = true;
+                fiber::is_cancelled()
+            });
+            jh.join()
+        });
+        assert!(!res);
+        assert!(finished);
+        assert_eq!(cancelled.get(), 3);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn forgotten_handle_is_joined() {
+        let counter = Cell::new(0);
+        let counter = &counter;
+        scope(|s| {
+            let jh = s.spawn(move || {
+                // Spawns another fiber in the same scope.
+                s.spawn_proc(move || {
+                    fiber::reschedule();
+                    counter.set(counter.get() + 1);
+                });
+                counter.set(counter.get() + 1);
+            });
+            std::mem::forget(jh);
+        });
+        assert_eq!(counter.get(), 2);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn dropped_handle_is_joined() {
+        let finished = Cell::new(false);
+        scope(|s| {
+            let _ = s.spawn(|| {
+                fiber::reschedule();
+                finished.set(true);
+                // The result is stored after the handle is dropped.
+                vec![1, 2, 3]
+            });
+            assert!(!finished.get());
+        });
+        assert!(finished.get());
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn handle_of_joined_sibling() {
+        let a_id = Cell::new(0);
+        let seen = RefCell::new(None);
+        let (a_id, seen_ref) = (&a_id, &seen);
+        scope(|s| {
+            let a = s.spawn_proc(move || {
+                fiber::set_name("scoped_sibling");
+                a_id.set(fiber::id());
+            });
+            s.spawn_proc(move || {
+                while !fiber::is_cancelled() {
+                    fiber::sleep(Duration::from_millis(10));
+                }
+                // `a` is joined by the scope before this fiber.
+                assert!(a.is_finished());
+                *seen_ref.borrow_mut() = Some((a.id(), a.name()));
+            });
+        });
+        assert_eq!(
+            seen.into_inner(),
+            Some((a_id.get(), "scoped_sibling".to_string()))
+        );
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn children_are_joined_on_panic() {
+        let finished = Cell::new(false);
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            scope(|s| {
+                s.spawn_proc(|| {
+                    fiber::reschedule();
+                    finished.set(true);
+                });
+                panic!("scope panicked");
+            })
+        }));
+        assert!(res.is_err());
+        assert!(finished.get());
+    }
+}
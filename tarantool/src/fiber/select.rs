@@ -0,0 +1,548 @@
+//! Waiting on multiple [`Channel`]s at once.
+//!
+//! See [`Select`] & [`fiber::select!`](macro@crate::fiber::select) for details.
+use std::{
+    cell::Cell,
+    fmt,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::fiber::{Channel, Cond, TryRecvError, TrySendError};
+
+////////////////////////////////////////////////////////////////////////////////
+// select!
+////////////////////////////////////////////////////////////////////////////////
+
+/// Waits until one of several channel operations is ready and executes it.
+///
+/// The macro is also available as `tarantool::fiber::select!`.
+///
+/// Each arm is either `recv(channel) -> result => body` or
+/// `send(channel, message) -> result => body`. The result of a receive is a
+/// `Result<T, TryRecvError>`, the result of a send is a
+/// `Result<(), TrySendError<T>>`, see [`SelectedOperation::recv`] &
+/// [`SelectedOperation::send`] for details.
+/// The message expression of a send arm is only evaluated if that arm is
+/// selected.
+///
+/// The last arm can be one of:
+/// - `default => body`, which is executed if none of the operations is ready
+///   right away, so the macro never yields,
+/// - `default(timeout) => body`, which is executed if none of the operations
+///   becomes ready within the `timeout`.
+///
+/// Without a `default` arm the current fiber **yields** until one of the
+/// operations is ready. If several operations are ready at once, one of them
+/// is chosen so that none of the channels is starved.
+///
+/// See [`Select`] for the non macro version of this api.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tarantool::fiber::{self, Channel};
+///
+/// let numbers = Channel::<i32>::new(1);
+/// let words = Channel::<String>::new(1);
+/// let results = Channel::<i32>::new(1);
+///
+/// fiber::select! {
+///     recv(numbers) -> n => println!("got a number {:?}", n),
+///     recv(words) -> w => println!("got a word {:?}", w),
+///     send(results, 42) -> res => assert!(res.is_ok()),
+///     default(Duration::from_secs(1)) => println!("timed out"),
+/// }
+/// ```
+#[macro_export]
+macro_rules! fiber_select {
+    ////////////////////////////////////////////////////////////////////////////
+    // Parse the arms into a list of `[(op args) res => body]`.
+    ////////////////////////////////////////////////////////////////////////////
+    (@parse [$($arms:tt)*]) => {
+        $crate::fiber_select!(@start [$($arms)*] (block))
+    };
+    (@parse [$($arms:tt)*] default => $body:expr $(,)?) => {
+        $crate::fiber_select!(@start [$($arms)*] (try $body))
+    };
+    (@parse [$($arms:tt)*] default($timeout:expr) => $body:expr $(,)?) => {
+        $crate::fiber_select!(@start [$($arms)*] (timeout $timeout, $body))
+    };
+    (@parse [$($arms:tt)*] recv($ch:expr) -> $res:pat => $body:expr, $($rest:tt)*) => {
+        $crate::fiber_select!(@parse [$($arms)* [(recv $ch) $res => $body]] $($rest)*)
+    };
+    (@parse [$($arms:tt)*] recv($ch:expr) -> $res:pat => $body:block $($rest:tt)*) => {
+        $crate::fiber_select!(@parse [$($arms)* [(recv $ch) $res => $body]] $($rest)*)
+    };
+    (@parse [$($arms:tt)*] recv($ch:expr) -> $res:pat => $body:expr) => {
+        $crate::fiber_select!(@parse [$($arms)* [(recv $ch) $res => $body]])
+    };
+    (@parse [$($arms:tt)*] send($ch:expr, $msg:expr) -> $res:pat => $body:expr, $($rest:tt)*) => {
+        $crate::fiber_select!(@parse [$($arms)* [(send $ch, $msg) $res => $body]] $($rest)*)
+    };
+    (@parse [$($arms:tt)*] send($ch:expr, $msg:expr) -> $res:pat => $body:block $($rest:tt)*) => {
+        $crate::fiber_select!(@parse [$($arms)* [(send $ch, $msg) $res => $body]] $($rest)*)
+    };
+    (@parse [$($arms:tt)*] send($ch:expr, $msg:expr) -> $res:pat => $body:expr) => {
+        $crate::fiber_select!(@parse [$($arms)* [(send $ch, $msg) $res => $body]])
+    };
+    (@parse [$($arms:tt)*] $($rest:tt)*) => {
+        ::std::compile_error!(
+            "expected `recv(channel) -> res => body`, `send(channel, msg) -> res => body`, \
+            `default => body` or `default(timeout) => body`"
+        )
+    };
+
+    ////////////////////////////////////////////////////////////////////////////
+    // Register the operations one by one. Each channel & operation index is
+    // stored in a separate (hygienic) variable, which is then referred to from
+    // the dispatch code accumulated in the third argument.
+    ////////////////////////////////////////////////////////////////////////////
+    (@start [$($arms:tt)*] $mode:tt) => {{
+        let mut __select = $crate::fiber::Select::new();
+        $crate::fiber_select!(@emit [__select __oper __index] [$($arms)*] [] $mode)
+    }};
+    (
+        @emit [$sel:ident $oper:ident $index:ident]
+        [[(recv $ch:expr) $res:pat => $body:expr] $($arms:tt)*]
+        [$($dispatch:tt)*] $mode:tt
+    ) => {{
+        let __ch = &$ch;
+        let __i = $sel.recv(__ch);
+        $crate::fiber_select!(
+            @emit [$sel $oper $index] [$($arms)*]
+            [$($dispatch)* if $index == __i { let $res = $oper.recv(__ch); $body } else]
+            $mode
+        )
+    }};
+    (
+        @emit [$sel:ident $oper:ident $index:ident]
+        [[(send $ch:expr, $msg:expr) $res:pat => $body:expr] $($arms:tt)*]
+        [$($dispatch:tt)*] $mode:tt
+    ) => {{
+        let __ch = &$ch;
+        let __i = $sel.send(__ch);
+        $crate::fiber_select!(
+            @emit [$sel $oper $index] [$($arms)*]
+            [$($dispatch)* if $index == __i { let $res = $oper.send(__ch, $msg); $body } else]
+            $mode
+        )
+    }};
+    (@emit [$sel:ident $oper:ident $index:ident] [] [$($dispatch:tt)*] (block)) => {{
+        let $oper = $sel.select();
+        let $index = $oper.index();
+        $($dispatch)* { ::std::unreachable!() }
+    }};
+    (@emit [$sel:ident $oper:ident $index:ident] [] [$($dispatch:tt)*] (try $default:expr)) => {
+        match $sel.try_select() {
+            ::std::result::Result::Ok($oper) => {
+                let $index = $oper.index();
+                $($dispatch)* { ::std::unreachable!() }
+            }
+            ::std::result::Result::Err(_) => $default,
+        }
+    };
+    (
+        @emit [$sel:ident $oper:ident $index:ident] [] [$($dispatch:tt)*]
+        (timeout $timeout:expr, $default:expr)
+    ) => {
+        match $sel.select_timeout($timeout) {
+            ::std::result::Result::Ok($oper) => {
+                let $index = $oper.index();
+                $($dispatch)* { ::std::unreachable!() }
+            }
+            ::std::result::Result::Err(_) => $default,
+        }
+    };
+
+    ////////////////////////////////////////////////////////////////////////////
+    // Entry point.
+    ////////////////////////////////////////////////////////////////////////////
+    ($($tokens:tt)*) => {
+        $crate::fiber_select!(@parse [] $($tokens)*)
+    };
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Select
+////////////////////////////////////////////////////////////////////////////////
+
+/// Waits until one of several channel operations is ready.
+///
+/// The operations are added with [`Select::recv`] & [`Select::send`], which
+/// return the operation's index. Then one of the `select` methods waits until
+/// any of the operations is ready and returns a [`SelectedOperation`], which
+/// must be completed by passing the same channel to
+/// [`SelectedOperation::recv`] or [`SelectedOperation::send`] correspondingly.
+///
+/// The waiting fiber is woken up by the other fibers working with the
+/// channels, so no busy polling is done. Note that only the operations done
+/// via the [`Channel`] api are taken into account.
+///
+/// See also the [`fiber::select!`](macro@crate::fiber::select) macro, which is
+/// usually more convenient.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tarantool::fiber::{Channel, Select};
+///
+/// let rx1 = Channel::<i32>::new(1);
+/// let rx2 = Channel::<i32>::new(1);
+///
+/// let mut sel = Select::new();
+/// let i1 = sel.recv(&rx1);
+/// let i2 = sel.recv(&rx2);
+/// match sel.select_timeout(Duration::from_secs(1)) {
+///     Ok(oper) if oper.index() == i1 => println!("rx1: {:?}", oper.recv(&rx1)),
+///     Ok(oper) if oper.index() == i2 => println!("rx2: {:?}", oper.recv(&rx2)),
+///     Ok(_) => unreachable!(),
+///     Err(_) => println!("timed out"),
+/// }
+/// ```
+pub struct Select<'a> {
+    ops: Vec<(Operation, &'a dyn Watch)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Recv,
+    Send,
+}
+
+impl<'a> Select<'a> {
+    /// Creates an empty list of channel operations.
+    pub fn new() -> Self {
+        Self { ops: Vec::new() }
+    }
+
+    /// Adds a receive operation on the `channel` and returns its index.
+    pub fn recv<T>(&mut self, channel: &'a Channel<T>) -> usize {
+        self.ops.push((Operation::Recv, channel));
+        self.ops.len() - 1
+    }
+
+    /// Adds a send operation on the `channel` and returns its index.
+    pub fn send<T>(&mut self, channel: &'a Channel<T>) -> usize {
+        self.ops.push((Operation::Send, channel));
+        self.ops.len() - 1
+    }
+
+    /// Returns one of the operations which are ready right away.
+    ///
+    /// This function does not yield.
+    pub fn try_select(&mut self) -> Result<SelectedOperation, TrySelectError> {
+        self.ready_operation().ok_or(TrySelectError)
+    }
+
+    /// **Yields** the current fiber until one of the operations is ready and
+    /// returns it.
+    ///
+    /// If there are no operations, the fiber yields forever.
+    pub fn select(&mut self) -> SelectedOperation {
+        self.select_deadline(None)
+            .expect("there's no timeout, so an operation must be selected")
+    }
+
+    /// **Yields** the current fiber at most `timeout` until one of the
+    /// operations is ready and returns it.
+    pub fn select_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<SelectedOperation, SelectTimeoutError> {
+        self.select_deadline(Instant::now().checked_add(timeout))
+            .ok_or(SelectTimeoutError)
+    }
+
+    fn select_deadline(&mut self, deadline: Option<Instant>) -> Option<SelectedOperation> {
+        if let Some(oper) = self.ready_operation() {
+            return Some(oper);
+        }
+
+        let cond = Rc::new(Cond::new());
+        let _watchers = Watchers::new(&self.ops, cond.clone());
+        loop {
+            match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    cond.wait_timeout(deadline - now);
+                }
+                None => {
+                    cond.wait();
+                }
+            }
+            if let Some(oper) = self.ready_operation() {
+                return Some(oper);
+            }
+        }
+    }
+
+    fn ready_operation(&self) -> Option<SelectedOperation> {
+        thread_local! {
+            static NEXT_START: Cell<usize> = const { Cell::new(0) };
+        }
+
+        if self.ops.is_empty() {
+            return None;
+        }
+        // Start checking from a different operation each time, so that a
+        // channel which is always ready doesn't starve the others.
+        let start = NEXT_START.with(|next| {
+            let start = next.get();
+            next.set(start.wrapping_add(1));
+            start % self.ops.len()
+        });
+        (start..self.ops.len())
+            .chain(0..start)
+            .find(|&i| {
+                let (op, channel) = self.ops[i];
+                channel.is_ready(op)
+            })
+            .map(|index| {
+                let (op, channel) = self.ops[index];
+                SelectedOperation {
+                    index,
+                    op,
+                    channel: channel.id(),
+                }
+            })
+    }
+}
+
+impl Default for Select<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Select<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Select")
+            .field("operations", &self.ops.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Registers the cond in all of the channels for the duration of the wait.
+struct Watchers<'s, 'a> {
+    ops: &'s [(Operation, &'a dyn Watch)],
+    cond: Rc<Cond>,
+}
+
+impl<'s, 'a> Watchers<'s, 'a> {
+    fn new(ops: &'s [(Operation, &'a dyn Watch)], cond: Rc<Cond>) -> Self {
+        for (_, channel) in ops {
+            channel.add_watcher(&cond);
+        }
+        Self { ops, cond }
+    }
+}
+
+impl Drop for Watchers<'_, '_> {
+    fn drop(&mut self) {
+        for (_, channel) in self.ops {
+            channel.remove_watcher(&self.cond);
+        }
+    }
+}
+
+/// Type erased [`Channel`].
+trait Watch {
+    fn is_ready(&self, op: Operation) -> bool;
+    fn id(&self) -> *const ();
+    fn add_watcher(&self, cond: &Rc<Cond>);
+    fn remove_watcher(&self, cond: &Rc<Cond>);
+}
+
+impl<T> Watch for Channel<T> {
+    fn is_ready(&self, op: Operation) -> bool {
+        match op {
+            Operation::Recv => self.is_recv_ready(),
+            Operation::Send => self.is_send_ready(),
+        }
+    }
+
+    fn id(&self) -> *const () {
+        Channel::id(self)
+    }
+
+    fn add_watcher(&self, cond: &Rc<Cond>) {
+        Channel::add_watcher(self, cond)
+    }
+
+    fn remove_watcher(&self, cond: &Rc<Cond>) {
+        Channel::remove_watcher(self, cond)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// SelectedOperation
+////////////////////////////////////////////////////////////////////////////////
+
+/// An operation which is ready, returned from [`Select`].
+///
+/// The operation must be completed right away by calling
+/// [`SelectedOperation::recv`] or [`SelectedOperation::send`], because once
+/// the current fiber yields the operation may not be ready any more.
+#[must_use = "the selected operation must be completed with `recv` or `send`"]
+#[derive(Debug)]
+pub struct SelectedOperation {
+    index: usize,
+    op: Operation,
+    channel: *const (),
+}
+
+impl SelectedOperation {
+    /// Returns the index of the operation, as returned from [`Select::recv`]
+    /// or [`Select::send`].
+    #[inline(always)]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Completes the receive operation.
+    ///
+    /// Returns [`TryRecvError::Disconnected`] if the channel is closed and
+    /// [`TryRecvError::Empty`] if the message was taken by another fiber
+    /// after the operation was selected.
+    ///
+    /// This function does not yield.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the selected operation isn't a receive operation on the
+    /// `channel`.
+    pub fn recv<T>(self, channel: &Channel<T>) -> Result<T, TryRecvError> {
+        self.check(Operation::Recv, channel.id());
+        channel.try_recv()
+    }
+
+    /// Completes the send operation.
+    ///
+    /// Returns the message back in [`TrySendError::Disconnected`] if the
+    /// channel is closed and in [`TrySendError::Full`] if the channel was
+    /// filled by another fiber after the operation was selected.
+    ///
+    /// This function does not yield.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the selected operation isn't a send operation on the
+    /// `channel`.
+    pub fn send<T>(self, channel: &Channel<T>, msg: T) -> Result<(), TrySendError<T>>
+    where
+        T: 'static,
+    {
+        self.check(Operation::Send, channel.id());
+        channel.try_send(msg)
+    }
+
+    #[track_caller]
+    fn check(&self, op: Operation, channel: *const ()) {
+        assert!(
+            self.op == op && self.channel == channel,
+            "passed a wrong channel or operation to SelectedOperation::{}",
+            match op {
+                Operation::Recv => "recv",
+                Operation::Send => "send",
+            }
+        );
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Errors
+////////////////////////////////////////////////////////////////////////////////
+
+/// Returned from [`Select::try_select`] if none of the operations is ready.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TrySelectError;
+
+/// Returned from [`Select::select_timeout`] if none of the operations became
+/// ready within the timeout.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SelectTimeoutError;
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+
+    #[crate::test(tarantool = "crate")]
+    fn select_ready_channel() {
+        if !crate::ffi::has_fiber_channel() {
+            return;
+        }
+
+        let ch1 = Channel::<i32>::new(1);
+        let ch2 = Channel::<&str>::new(1);
+        let full = Channel::<i32>::new(1);
+        full.try_send(0).unwrap();
+
+        let mut sel = Select::new();
+        let i1 = sel.recv(&ch1);
+        let i2 = sel.recv(&ch2);
+        sel.send(&full);
+        assert_eq!(sel.try_select().unwrap_err(), TrySelectError);
+
+        ch2.try_send("hello").unwrap();
+        let oper = sel.select();
+        assert_eq!(oper.index(), i2);
+        assert_eq!(oper.recv(&ch2), Ok("hello"));
+
+        let res = sel.select_timeout(Duration::from_millis(10));
+        assert_eq!(res.unwrap_err(), SelectTimeoutError);
+
+        ch1.clone().close();
+        let oper = sel.try_select().unwrap();
+        assert_eq!(oper.index(), i1);
+        assert_eq!(oper.recv(&ch1), Err(TryRecvError::Disconnected));
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn select_waits_for_other_fibers() {
+        if !crate::ffi::has_fiber_channel() {
+            return;
+        }
+
+        // Unbuffered channels are only ready when the other side is blocked.
+        let rx = Channel::<i32>::new(0);
+        let tx = Channel::<i32>::new(0);
+
+        let c_rx = rx.clone();
+        let sender = fiber::start(move || c_rx.send(1));
+        let res = fiber_select! {
+            recv(rx) -> v => v,
+            send(tx, 2) -> _ => unreachable!(),
+        };
+        assert_eq!(res, Ok(1));
+        assert_eq!(sender.join(), Ok(()));
+
+        let c_tx = tx.clone();
+        let receiver = fiber::start(move || {
+            fiber::sleep(Duration::from_millis(10));
+            c_tx.recv()
+        });
+        let res = fiber_select! {
+            recv(rx) -> _ => unreachable!(),
+            send(tx, 2) -> res => {
+                assert_eq!(res, Ok(()));
+                "sent"
+            }
+            default(Duration::from_secs(10)) => "timed out",
+        };
+        assert_eq!(res, "sent");
+        assert_eq!(receiver.join(), Some(2));
+
+        let res = fiber_select! {
+            recv(rx) -> _ => false,
+            default => true,
+        };
+        assert!(res);
+    }
+}
@@ -0,0 +1,218 @@
+use std::{cell::Cell, fmt, time::Duration, time::Instant};
+
+use crate::fiber::Cond;
+
+////////////////////////////////////////////////////////////////////////////////
+// Semaphore
+////////////////////////////////////////////////////////////////////////////////
+
+/// A counting semaphore for fibers.
+///
+/// The blocking counterpart of
+/// [`r#async::Semaphore`](crate::fiber::r#async::Semaphore), except that
+/// [`Semaphore::acquire`] **yields** the current fiber until a permit is
+/// available.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::rc::Rc;
+/// use tarantool::fiber::{start_proc, semaphore::Semaphore};
+///
+/// let semaphore = Rc::new(Semaphore::new(2));
+/// let jhs: Vec<_> = (0..10)
+///     .map(|_| {
+///         let semaphore = semaphore.clone();
+///         start_proc(move || {
+///             // At most 2 fibers are here at the same time.
+///             let _permit = semaphore.acquire();
+///         })
+///     })
+///     .collect();
+/// for jh in jhs {
+///     jh.join();
+/// }
+/// ```
+pub struct Semaphore {
+    permits: Cell<usize>,
+    cond: Cond,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the given number of permits.
+    pub fn new(permits: usize) -> Self {
+        Self {
+            permits: Cell::new(permits),
+            cond: Cond::new(),
+        }
+    }
+
+    /// Acquires a permit, yielding the current fiber until one is available.
+    #[inline]
+    pub fn acquire(&self) -> SemaphorePermit<'_> {
+        self.acquire_many(1)
+    }
+
+    /// Acquires `n` permits at once, yielding the current fiber until enough
+    /// are available.
+    ///
+    /// Note that fibers waiting for fewer permits may acquire them first, so
+    /// a fiber waiting for many permits may wait for a long time.
+    pub fn acquire_many(&self, n: usize) -> SemaphorePermit<'_> {
+        loop {
+            if let Some(permit) = self.try_acquire_many(n) {
+                return permit;
+            }
+            self.cond.wait();
+        }
+    }
+
+    /// Acquires `n` permits at once, yielding the current fiber at most
+    /// `timeout` until enough are available.
+    ///
+    /// Returns `None` if the timeout expired.
+    pub fn acquire_many_timeout(&self, n: usize, timeout: Duration) -> Option<SemaphorePermit<'_>> {
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            if let Some(permit) = self.try_acquire_many(n) {
+                return Some(permit);
+            }
+            match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    self.cond.wait_timeout(deadline - now);
+                }
+                None => {
+                    self.cond.wait();
+                }
+            }
+        }
+    }
+
+    /// Attempts to acquire a permit without yielding.
+    #[inline]
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.try_acquire_many(1)
+    }
+
+    /// Attempts to acquire `n` permits without yielding.
+    pub fn try_acquire_many(&self, n: usize) -> Option<SemaphorePermit<'_>> {
+        let permits = self.permits.get();
+        if permits < n {
+            return None;
+        }
+        self.permits.set(permits - n);
+        Some(SemaphorePermit {
+            semaphore: self,
+            permits: n,
+        })
+    }
+
+    /// Adds `n` new permits to the semaphore.
+    pub fn add_permits(&self, n: usize) {
+        self.permits.set(self.permits.get() + n);
+        // See `r#async::Semaphore::add_permits`.
+        self.cond.broadcast();
+    }
+
+    /// Returns the number of permits which can be acquired without yielding.
+    #[inline]
+    pub fn available_permits(&self) -> usize {
+        self.permits.get()
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("permits", &self.permits.get())
+            .finish_non_exhaustive()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// SemaphorePermit
+////////////////////////////////////////////////////////////////////////////////
+
+/// Permits acquired from a [`Semaphore`], returned to it when dropped.
+#[must_use = "the permits are released immediately if the guard is dropped"]
+#[derive(Debug)]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+}
+
+impl SemaphorePermit<'_> {
+    /// Drops the guard without returning the permits to the semaphore.
+    #[inline]
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+
+    /// Returns the number of permits held by the guard.
+    #[inline(always)]
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.add_permits(self.permits);
+        }
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+    use crate::fiber;
+    use std::rc::Rc;
+
+    #[crate::test(tarantool = "crate")]
+    fn limits_concurrency() {
+        let semaphore = Rc::new(Semaphore::new(2));
+        let active = Rc::new(Cell::new(0));
+        let max_active = Rc::new(Cell::new(0));
+        let jhs: Vec<_> = (0..5)
+            .map(|_| {
+                let semaphore = semaphore.clone();
+                let active = active.clone();
+                let max_active = max_active.clone();
+                fiber::start_proc(move || {
+                    let _permit = semaphore.acquire();
+                    active.set(active.get() + 1);
+                    max_active.set(max_active.get().max(active.get()));
+                    fiber::sleep(Duration::from_millis(1));
+                    active.set(active.get() - 1);
+                })
+            })
+            .collect();
+        for jh in jhs {
+            jh.join();
+        }
+        assert_eq!(max_active.get(), 2);
+        assert_eq!(semaphore.available_permits(), 2);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn acquire_many() {
+        let semaphore = Semaphore::new(3);
+        let permit = semaphore.try_acquire_many(2).unwrap();
+        assert_eq!(permit.num_permits(), 2);
+        assert!(semaphore.try_acquire_many(2).is_none());
+        let res = semaphore.acquire_many_timeout(2, Duration::from_millis(10));
+        assert!(res.is_none());
+
+        permit.forget();
+        assert_eq!(semaphore.available_permits(), 1);
+        semaphore.add_permits(1);
+        let permit = semaphore.acquire_many(2);
+        drop(permit);
+        assert_eq!(semaphore.available_permits(), 2);
+    }
+}
@@ -8,9 +8,11 @@
 //! - [Indexes](https://www.tarantool.io/en/doc/latest/book/box/data_model/#indexes)
 //! - [Lua reference: Submodule box.index](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_index/)
 use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::marker::PhantomData;
 use std::mem::MaybeUninit;
-use std::ops::Range;
+use std::ops::{Bound, Range, RangeBounds};
 use std::ptr::null_mut;
 
 use num_derive::ToPrimitive;
@@ -20,13 +22,13 @@ use serde::{Deserialize, Serialize};
 use crate::error::{Error, TarantoolError, TarantoolErrorCode};
 use crate::ffi::tarantool as ffi;
 use crate::msgpack;
-use crate::space::{Space, SpaceId, SystemSpace};
-use crate::tuple::{Encode, ToTupleBuffer, Tuple, TupleBuffer};
+use crate::space::{FieldPath, Space, SpaceId, SystemSpace};
+use crate::stat::IndexStat;
+use crate::tuple::{DecodeOwned, Encode, ToTupleBuffer, Tuple, TupleBuffer};
 use crate::tuple::{KeyDef, KeyDefPart};
 use crate::tuple_from_box_api;
 use crate::unwrap_or;
 use crate::util::NumOrStr;
-use crate::util::Value;
 
 pub type IndexId = u32;
 
@@ -141,7 +143,8 @@ impl<'a> Builder<'a> {
         run_count_per_level(run_count_per_level: u32)
         run_size_ratio(run_size_ratio: f32)
         sequence(sequence: impl Into<SequenceOpt>)
-        func(func: String)
+        func(func: impl Into<String>)
+        hint(hint: bool)
     }
 
     /// Add a part to the index's parts list.
@@ -220,15 +223,28 @@ pub struct IndexOptions {
     pub parts: Option<Vec<Part>>,
     pub dimension: Option<u32>,
     pub distance: Option<RtreeIndexDistanceType>,
+    /// Bloom filter false positive rate. Only for vinyl.
     pub bloom_fpr: Option<f32>,
+    /// Size of a page in bytes used for disk reads and writes. Only for vinyl.
     pub page_size: Option<u32>,
+    /// Default maximum size of a range in bytes. Only for vinyl.
     pub range_size: Option<u32>,
+    /// Maximum number of runs per level of the LSM tree. Only for vinyl.
     pub run_count_per_level: Option<u32>,
+    /// Ratio between the sizes of adjacent levels of the LSM tree. Only for
+    /// vinyl.
     pub run_size_ratio: Option<f32>,
     pub sequence: Option<SequenceOpt>,
+    /// Name of the function for a functional index. The function must be
+    /// registered in `_func` with `is_deterministic = true` and
+    /// `is_sandboxed = true`, and return an array of keys (or an array of
+    /// arrays of keys if it's created with `opts = {is_multikey = true}`).
+    ///
+    /// For details see [creating a functional index](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/create_index/#creating-a-functional-index).
     pub func: Option<String>,
-    // Only for Tarantool >= 2.6
-    // pub hint: Option<bool>,
+    /// Use hints to speed up comparisons in a memtx `TREE` index.
+    /// Only for Tarantool >= 2.6.
+    pub hint: Option<bool>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -392,6 +408,9 @@ pub struct Part {
     pub is_nullable: Option<bool>,
     #[serde(default)]
     pub path: Option<String>,
+    /// Don't index tuples with `null` in this part. Only for Tarantool >= 2.8.
+    #[serde(default)]
+    pub exclude_null: Option<bool>,
 }
 
 macro_rules! define_setters {
@@ -415,6 +434,7 @@ impl Part {
             collation: None,
             is_nullable: None,
             path: None,
+            exclude_null: None,
         }
     }
 
@@ -422,7 +442,8 @@ impl Part {
         field_type(r#type: FieldType)
         collation(collation: String)
         is_nullable(is_nullable: bool)
-        path(path: String)
+        path(path: impl Into<String>)
+        exclude_null(exclude_null: bool)
     }
 
     #[inline(always)]
@@ -445,6 +466,20 @@ impl From<String> for Part {
     }
 }
 
+impl From<FieldPath> for Part {
+    #[inline(always)]
+    fn from(f: FieldPath) -> Self {
+        Self::field(f.into_string())
+    }
+}
+
+impl From<(FieldPath, FieldType)> for Part {
+    #[inline(always)]
+    fn from((f, t): (FieldPath, FieldType)) -> Self {
+        Self::field(f.into_string()).field_type(t)
+    }
+}
+
 impl From<u32> for Part {
     #[inline(always)]
     fn from(f: u32) -> Self {
@@ -531,6 +566,18 @@ impl Index {
         tuple.decode::<Metadata>()
     }
 
+    /// Return statistics of the index. Only vinyl indexes provide statistics,
+    /// for memtx indexes all values are zero.
+    ///
+    /// See [index_object:stat()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_index/stat/).
+    #[inline]
+    pub fn stat(&self) -> Result<IndexStat, Error> {
+        crate::util::lua_eval_decode(&format!(
+            "box.space[{}].index[{}]:stat()",
+            self.space_id, self.index_id
+        ))
+    }
+
     // Drops index.
     #[inline(always)]
     pub fn drop(&self) -> Result<(), Error> {
@@ -598,10 +645,192 @@ impl Index {
 
         Ok(IndexIterator {
             ptr,
+            index: self.clone(),
+            _key_data: key_buf,
+            pos_data: None,
+            bounds: Vec::new(),
+            last_accepted: None,
+            exhausted: false,
+        })
+    }
+
+    /// Iterate over the tuples with keys within `range`.
+    ///
+    /// The lower bound determines the iterator type: `GE` for an inclusive
+    /// bound, `GT` for an exclusive one and `GE` with an empty key for an
+    /// unbounded one. The iteration stops at the first tuple beyond the upper
+    /// bound. Keys can be partial, in which case only the given parts are
+    /// compared, e.g. `("a",)..=("b",)` for an index on two string fields
+    /// matches all tuples with the first field from `"a"` to `"b"` inclusive.
+    ///
+    /// Only `TREE` indexes support range iteration.
+    ///
+    /// ```no_run
+    /// use tarantool::space::Space;
+    ///
+    /// let index = Space::find("users").unwrap().primary_key();
+    /// for tuple in index.select_range((10,)..(20,)).unwrap() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn select_range<K, R>(&self, range: R) -> Result<IndexIterator, Error>
+    where
+        K: ToTupleBuffer,
+        R: RangeBounds<K>,
+    {
+        let mut iter = match range.start_bound() {
+            Bound::Included(key) => self.select(IteratorType::GE, key)?,
+            Bound::Excluded(key) => self.select(IteratorType::GT, key)?,
+            Bound::Unbounded => self.select(IteratorType::GE, &())?,
+        };
+        let (key, accept): (_, fn(Ordering) -> bool) = match range.end_bound() {
+            Bound::Included(key) => (key, Ordering::is_le),
+            Bound::Excluded(key) => (key, Ordering::is_lt),
+            Bound::Unbounded => return Ok(iter),
+        };
+        iter.push_bound(key, accept)?;
+        Ok(iter)
+    }
+
+    /// Allocate and initialize iterator for index, which starts right after
+    /// the position specified by `after`.
+    ///
+    /// Same as [`Index::select`], but the iteration is resumed from a
+    /// previously saved position (see [`IndexIterator::position`]) or
+    /// starts after a given tuple, which doesn't have to be present in the
+    /// index.
+    ///
+    /// # Panicking
+    /// Will panic if the current tarantool executable doesn't support
+    /// iterator positions (see [`crate::ffi::has_iterator_position`]).
+    pub fn select_after<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        after: &After,
+    ) -> Result<IndexIterator, Error>
+    where
+        K: ToTupleBuffer + ?Sized,
+    {
+        let pos = match after {
+            After::Position(pos) => pos.clone(),
+            After::Tuple(tuple) => self.tuple_position(tuple)?,
+        };
+        let key_buf = key.to_tuple_buffer()?;
+        let Range { start, end } = key_buf.as_ref().as_ptr_range();
+        let Range {
+            start: pos_start,
+            end: pos_end,
+        } = pos.as_bytes().as_ptr_range();
+
+        let ptr = unsafe {
+            ffi::box_index_iterator_after(
+                self.space_id,
+                self.index_id,
+                iterator_type.to_i32().unwrap(),
+                start as _,
+                end as _,
+                pos_start as _,
+                pos_end as _,
+            )
+        };
+
+        if ptr.is_null() {
+            return Err(TarantoolError::last().into());
+        }
+
+        Ok(IndexIterator {
+            ptr,
+            index: self.clone(),
             _key_data: key_buf,
+            pos_data: Some(pos),
+            bounds: Vec::new(),
+            last_accepted: None,
+            exhausted: false,
         })
     }
 
+    /// Select a single page of tuples according to `options` and return it
+    /// along with the position of the last tuple in the page.
+    ///
+    /// Analogous to `index:select(key, {iterator = ..., limit = ...,
+    /// offset = ..., after = ..., fetch_pos = true})` in lua. The returned
+    /// [`Page::position`] can be passed as [`SelectOptions::after`] to
+    /// fetch the next page. Unlike `offset`, the position remains valid if
+    /// tuples are inserted or deleted between the requests.
+    ///
+    /// # Panicking
+    /// Will panic if the current tarantool executable doesn't support
+    /// iterator positions (see [`crate::ffi::has_iterator_position`]).
+    pub fn select_page<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        options: &SelectOptions,
+    ) -> Result<Page, Error>
+    where
+        K: ToTupleBuffer + ?Sized,
+    {
+        let mut iter = match &options.after {
+            Some(after) => self.select_after(iterator_type, key, after)?,
+            None => self.select(iterator_type, key)?,
+        };
+        let limit = options.limit.unwrap_or(u32::MAX) as usize;
+        let mut tuples = Vec::new();
+        let mut skipped = 0;
+        while tuples.len() < limit {
+            let tuple = unwrap_or!(iter.next_tuple()?, break);
+            if skipped < options.offset {
+                skipped += 1;
+                continue;
+            }
+            tuples.push(tuple);
+        }
+        let position = if tuples.is_empty() {
+            None
+        } else {
+            Some(iter.position()?)
+        };
+        Ok(Page { tuples, position })
+    }
+
+    /// Return the position of `tuple` in the index, which can be used as a
+    /// starting point for [`Index::select_after`]. The tuple doesn't have to
+    /// be present in the index.
+    ///
+    /// # Panicking
+    /// Will panic if the current tarantool executable doesn't support
+    /// iterator positions (see [`crate::ffi::has_iterator_position`]).
+    pub fn tuple_position<T>(&self, tuple: &T) -> Result<Position, Error>
+    where
+        T: ToTupleBuffer + ?Sized,
+    {
+        let buf;
+        let data = unwrap_or!(tuple.tuple_data(), {
+            buf = tuple.to_tuple_buffer()?;
+            buf.as_ref()
+        });
+        let Range { start, end } = data.as_ptr_range();
+        let mut pos = std::ptr::null();
+        let mut pos_end = std::ptr::null();
+        unsafe {
+            let svp = ffi::box_region_used();
+            if ffi::box_index_tuple_position(
+                self.space_id,
+                self.index_id,
+                start as _,
+                end as _,
+                &mut pos,
+                &mut pos_end,
+            ) < 0
+            {
+                ffi::box_region_truncate(svp);
+                return Err(TarantoolError::last().into());
+            }
+            Ok(Position::from_region(svp, pos, pos_end))
+        }
+    }
+
     /// Delete a tuple identified by a key.
     ///
     /// Same as [space.delete()](../space/struct.Space.html#method.delete), but a key is searched in this index instead
@@ -924,13 +1153,14 @@ impl Index {
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Representation of a tuple holding index metadata in system `_index` space.
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Metadata<'a> {
     pub space_id: SpaceId,
     pub index_id: IndexId,
     pub name: Cow<'a, str>,
     pub r#type: IndexType,
-    pub opts: BTreeMap<Cow<'a, str>, Value<'a>>,
+    /// Index options, e.g. `unique`, `hint` or vinyl's `bloom_fpr`.
+    pub opts: BTreeMap<Cow<'a, str>, rmpv::Value>,
     pub parts: Vec<Part>,
 }
 impl Encode for Metadata<'_> {}
@@ -1000,7 +1230,112 @@ impl Metadata<'_> {
 /// Index iterator. Can be used with `for` statement.
 pub struct IndexIterator {
     ptr: *mut ffi::BoxIterator,
+    index: Index,
     _key_data: TupleBuffer,
+    /// Position the iteration started after, if any.
+    pos_data: Option<Position>,
+    /// The iteration stops at the first tuple not satisfying any of these.
+    bounds: Vec<KeyBound>,
+    /// The last tuple which satisfied the `bounds`, only tracked if there are
+    /// any, because the tarantool iterator is already past the tuple which
+    /// didn't.
+    last_accepted: Option<Tuple>,
+    /// Set once a tuple doesn't satisfy the `bounds`.
+    exhausted: bool,
+}
+
+struct KeyBound {
+    key_def: KeyDef,
+    key: TupleBuffer,
+    accept: fn(Ordering) -> bool,
+}
+
+impl IndexIterator {
+    /// Stop the iteration at the first tuple which key doesn't start with
+    /// `prefix`.
+    ///
+    /// Useful with iterator types like `GE` or `LE`, which unlike `EQ` don't
+    /// stop when the key no longer matches, e.g. when iterating starting from
+    /// a position (see [`Index::select_after`]) or from a key longer than the
+    /// prefix.
+    ///
+    /// ```no_run
+    /// use tarantool::index::IteratorType;
+    /// use tarantool::space::Space;
+    ///
+    /// let index = Space::find("events").unwrap().index("by_user_time").unwrap();
+    /// let user_events_since = index
+    ///     .select(IteratorType::GE, &("user_1", 1_700_000_000))
+    ///     .unwrap()
+    ///     .take_while_prefix(&("user_1",))
+    ///     .unwrap();
+    /// ```
+    #[inline]
+    pub fn take_while_prefix<K>(mut self, prefix: &K) -> Result<Self, Error>
+    where
+        K: ToTupleBuffer + ?Sized,
+    {
+        self.push_bound(prefix, Ordering::is_eq)?;
+        Ok(self)
+    }
+
+    /// Decode each tuple into `T`.
+    #[inline(always)]
+    pub fn decode<T>(self) -> TypedIndexIterator<T>
+    where
+        T: DecodeOwned,
+    {
+        TypedIndexIterator::new(self)
+    }
+
+    fn push_bound<K>(&mut self, key: &K, accept: fn(Ordering) -> bool) -> Result<(), Error>
+    where
+        K: ToTupleBuffer + ?Sized,
+    {
+        self.bounds.push(KeyBound {
+            key_def: self.index.meta()?.to_key_def(),
+            key: key.to_tuple_buffer()?,
+            accept,
+        });
+        Ok(())
+    }
+
+    /// Return the position of the last tuple returned by the iterator, which
+    /// can be passed to [`Index::select_after`] to resume the iteration.
+    ///
+    /// If no tuples were returned yet, the position is empty and the
+    /// iteration resumed from it will start from the beginning.
+    ///
+    /// # Panicking
+    /// Will panic if the current tarantool executable doesn't support
+    /// iterator positions (see [`crate::ffi::has_iterator_position`]).
+    pub fn position(&self) -> Result<Position, Error> {
+        if self.exhausted {
+            return match &self.last_accepted {
+                Some(tuple) => self.index.tuple_position(tuple),
+                None => Ok(self.pos_data.clone().unwrap_or_default()),
+            };
+        }
+        let mut pos = std::ptr::null();
+        let mut pos_end = std::ptr::null();
+        unsafe {
+            let svp = ffi::box_region_used();
+            if ffi::box_iterator_position(self.ptr, &mut pos, &mut pos_end) < 0 {
+                ffi::box_region_truncate(svp);
+                return Err(TarantoolError::last().into());
+            }
+            Ok(Position::from_region(svp, pos, pos_end))
+        }
+    }
+
+    #[inline]
+    fn next_tuple(&mut self) -> Result<Option<Tuple>, Error> {
+        let mut result_ptr = null_mut();
+        if unsafe { ffi::box_iterator_next(self.ptr, &mut result_ptr) } < 0 {
+            return Err(TarantoolError::last().into());
+        }
+        Ok(Tuple::try_from_ptr(result_ptr))
+    }
 }
 
 impl Iterator for IndexIterator {
@@ -1008,11 +1343,24 @@ impl Iterator for IndexIterator {
 
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
+        if self.exhausted {
+            return None;
+        }
         let mut result_ptr = null_mut();
         if unsafe { ffi::box_iterator_next(self.ptr, &mut result_ptr) } < 0 {
             return None;
         }
-        Tuple::try_from_ptr(result_ptr)
+        let tuple = Tuple::try_from_ptr(result_ptr)?;
+        for bound in &self.bounds {
+            if !(bound.accept)(bound.key_def.compare_with_key(&tuple, &bound.key)) {
+                self.exhausted = true;
+                return None;
+            }
+        }
+        if !self.bounds.is_empty() {
+            self.last_accepted = Some(tuple.clone());
+        }
+        Some(tuple)
     }
 }
 
@@ -1023,6 +1371,416 @@ impl Drop for IndexIterator {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// Position
+////////////////////////////////////////////////////////////////////////////////
+
+/// Opaque position of a tuple in an index.
+///
+/// Positions are returned by [`IndexIterator::position`],
+/// [`Index::select_page`] and [`RemoteIndex::select_page`] and can be used to
+/// resume the iteration right after the corresponding tuple. A position can be
+/// serialized, e.g. to be handed out to a client as a pagination cursor.
+///
+/// [`RemoteIndex::select_page`]: crate::net_box::RemoteIndex::select_page
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Position(#[serde(with = "serde_bytes")] Vec<u8>);
+
+impl Position {
+    /// Construct a position from the bytes previously obtained via
+    /// [`Position::as_bytes`] or [`Position::into_bytes`].
+    #[inline(always)]
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self(bytes.into())
+    }
+
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    #[inline(always)]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// Returns `true` if the position points to the beginning of the index.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Copy the position allocated on the box region and truncate the region
+    /// back to `svp`.
+    unsafe fn from_region(
+        svp: usize,
+        start: *const std::os::raw::c_char,
+        end: *const std::os::raw::c_char,
+    ) -> Self {
+        let res = if start.is_null() {
+            Self::default()
+        } else {
+            let len = end.offset_from(start) as usize;
+            Self(std::slice::from_raw_parts(start as *const u8, len).to_vec())
+        };
+        ffi::box_region_truncate(svp);
+        res
+    }
+}
+
+/// Starting point of a paginated select, see [`Index::select_after`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum After {
+    /// Start after the position returned from a previous select.
+    Position(Position),
+    /// Start after the given tuple. The tuple doesn't have to be present in
+    /// the index, only its key parts are used.
+    Tuple(TupleBuffer),
+}
+
+impl From<Position> for After {
+    #[inline(always)]
+    fn from(pos: Position) -> Self {
+        Self::Position(pos)
+    }
+}
+
+impl From<TupleBuffer> for After {
+    #[inline(always)]
+    fn from(tuple: TupleBuffer) -> Self {
+        Self::Tuple(tuple)
+    }
+}
+
+/// Options for [`Index::select_page`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelectOptions {
+    /// Maximum number of tuples in the page. Unlimited if `None`.
+    pub limit: Option<u32>,
+    /// Number of tuples to skip before the page.
+    pub offset: u32,
+    /// Start the page after this position or tuple.
+    pub after: Option<After>,
+}
+
+/// A page of tuples returned by [`Index::select_page`].
+#[derive(Debug)]
+pub struct Page {
+    pub tuples: Vec<Tuple>,
+    /// Position of the last tuple in the page or `None` if the page is
+    /// empty. Pass it as [`SelectOptions::after`] to fetch the next page.
+    pub position: Option<Position>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// IndexKey
+////////////////////////////////////////////////////////////////////////////////
+
+/// A key type with a statically known number of parts.
+///
+/// Used by [`TypedIndex`] to check the key against the index definition.
+pub trait IndexKey: ToTupleBuffer {
+    /// Number of key parts.
+    const PARTS: usize;
+}
+
+macro_rules! impl_index_key {
+    ($($n:literal: $($t:ident)+;)+) => {
+        $(
+            impl<$($t),+> IndexKey for ($($t,)+)
+            where
+                Self: ToTupleBuffer,
+            {
+                const PARTS: usize = $n;
+            }
+        )+
+    }
+}
+
+impl_index_key! {
+    1: A;
+    2: A B;
+    3: A B C;
+    4: A B C D;
+    5: A B C D E;
+    6: A B C D E F;
+    7: A B C D E F G;
+    8: A B C D E F G H;
+    9: A B C D E F G H I;
+    10: A B C D E F G H I J;
+    11: A B C D E F G H I J K;
+    12: A B C D E F G H I J K L;
+    13: A B C D E F G H I J K L M;
+    14: A B C D E F G H I J K L M N;
+    15: A B C D E F G H I J K L M N O;
+    16: A B C D E F G H I J K L M N O P;
+}
+
+impl<T, const N: usize> IndexKey for [T; N]
+where
+    Self: ToTupleBuffer,
+{
+    const PARTS: usize = N;
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// TypedIndex
+////////////////////////////////////////////////////////////////////////////////
+
+/// An [`Index`] bound to a row type `T` and a key type `K`.
+///
+/// Tuples are decoded into `T` and keys are encoded from `K`, so no manual
+/// encoding or decoding is needed at call sites. `K` may be a prefix of the
+/// index key, which can be used with [`TypedIndex::select`] & co., but
+/// [`TypedIndex::get`], [`TypedIndex::delete`] & [`TypedIndex::update`]
+/// require the full key.
+///
+/// ```no_run
+/// use tarantool::index::{IteratorType, TypedIndex};
+/// use tarantool::space::Space;
+///
+/// let space = Space::find("users").unwrap();
+/// let by_name: TypedIndex<(u64, String), (String,)> =
+///     TypedIndex::new(space.index("name").unwrap()).unwrap();
+/// let user: Option<(u64, String)> = by_name.get(&("Alice".into(),)).unwrap();
+/// for user in by_name.select(IteratorType::GE, &("B".into(),)).unwrap() {
+///     let (id, name) = user.unwrap();
+/// }
+/// ```
+pub struct TypedIndex<T, K> {
+    index: Index,
+    /// Number of parts in the index definition.
+    parts: usize,
+    marker: PhantomData<fn(&K) -> T>,
+}
+
+impl<T, K> Clone for TypedIndex<T, K> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index.clone(),
+            parts: self.parts,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, K> std::fmt::Debug for TypedIndex<T, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TypedIndex").field(&self.index).finish()
+    }
+}
+
+impl<T, K> TypedIndex<T, K>
+where
+    T: DecodeOwned,
+    K: IndexKey,
+{
+    /// Wrap `index`, checking that `K` doesn't have more parts than the
+    /// index definition.
+    ///
+    /// Returns an error with code [`KeyPartCount`] in case of a mismatch.
+    ///
+    /// [`KeyPartCount`]: TarantoolErrorCode::KeyPartCount
+    #[inline]
+    pub fn new(index: Index) -> Result<Self, Error> {
+        let meta = index.meta()?;
+        let parts = meta.parts.len();
+        if K::PARTS > parts {
+            return Err(crate::set_and_get_error!(
+                TarantoolErrorCode::KeyPartCount,
+                "Invalid key part count for index '{}' (expected <= {}, got {})",
+                meta.name,
+                parts,
+                K::PARTS,
+            )
+            .into());
+        }
+        Ok(Self {
+            index,
+            parts,
+            marker: PhantomData,
+        })
+    }
+
+    /// Wrap `index` without checking the key, `K` is assumed to be the full
+    /// key of the index.
+    #[inline(always)]
+    pub fn new_unchecked(index: Index) -> Self {
+        Self {
+            index,
+            parts: K::PARTS,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns `true` if `K` has all of the index parts, i.e. it can be used
+    /// for the exact match operations.
+    #[inline(always)]
+    pub fn is_full_key(&self) -> bool {
+        K::PARTS == self.parts
+    }
+
+    fn check_full_key(&self) -> Result<(), Error> {
+        if !self.is_full_key() {
+            return Err(crate::set_and_get_error!(
+                TarantoolErrorCode::ExactMatch,
+                "Invalid key part count in an exact match (expected {}, got {})",
+                self.parts,
+                K::PARTS,
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Return the underlying untyped index.
+    #[inline(always)]
+    pub fn as_index(&self) -> &Index {
+        &self.index
+    }
+
+    /// Return the underlying untyped index.
+    #[inline(always)]
+    pub fn into_index(self) -> Index {
+        self.index
+    }
+
+    /// Get a row from index by the key.
+    ///
+    /// Returns an error if `K` isn't the full key. See [`Index::get`].
+    #[inline]
+    pub fn get(&self, key: &K) -> Result<Option<T>, Error> {
+        self.check_full_key()?;
+        self.index.get(key)?.map(|t| t.decode()).transpose()
+    }
+
+    /// Iterate over the rows matched by `key` according to `iterator_type`.
+    ///
+    /// See [`Index::select`].
+    #[inline]
+    pub fn select(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+    ) -> Result<TypedIndexIterator<T>, Error> {
+        let iter = self.index.select(iterator_type, key)?;
+        Ok(TypedIndexIterator::new(iter))
+    }
+
+    /// Iterate over the rows with keys within `range`.
+    ///
+    /// See [`Index::select_range`].
+    #[inline]
+    pub fn select_range<R>(&self, range: R) -> Result<TypedIndexIterator<T>, Error>
+    where
+        R: RangeBounds<K>,
+    {
+        let iter = self.index.select_range(range)?;
+        Ok(TypedIndexIterator::new(iter))
+    }
+
+    /// Iterate over all rows in the index.
+    #[inline]
+    pub fn all(&self) -> Result<TypedIndexIterator<T>, Error> {
+        let iter = self.index.select(IteratorType::All, &())?;
+        Ok(TypedIndexIterator::new(iter))
+    }
+
+    /// Delete a row identified by the key.
+    ///
+    /// Returns the deleted row or an error if `K` isn't the full key. See
+    /// [`Index::delete`].
+    #[inline]
+    pub fn delete(&self, key: &K) -> Result<Option<T>, Error> {
+        self.check_full_key()?;
+        self.index.delete(key)?.map(|t| t.decode()).transpose()
+    }
+
+    /// Update a row identified by the key.
+    ///
+    /// Returns the updated row or an error if `K` isn't the full key. See
+    /// [`Index::update`].
+    #[inline]
+    pub fn update<Op>(&self, key: &K, ops: impl AsRef<[Op]>) -> Result<Option<T>, Error>
+    where
+        Op: ToTupleBuffer,
+    {
+        self.check_full_key()?;
+        self.index.update(key, ops)?.map(|t| t.decode()).transpose()
+    }
+
+    /// Return the first (minimal) row matched by the key.
+    ///
+    /// See [`Index::min`].
+    #[inline]
+    pub fn min(&self, key: &K) -> Result<Option<T>, Error> {
+        self.index.min(key)?.map(|t| t.decode()).transpose()
+    }
+
+    /// Return the last (maximal) row matched by the key.
+    ///
+    /// See [`Index::max`].
+    #[inline]
+    pub fn max(&self, key: &K) -> Result<Option<T>, Error> {
+        self.index.max(key)?.map(|t| t.decode()).transpose()
+    }
+
+    /// Count the number of rows matched by the key.
+    ///
+    /// See [`Index::count`].
+    #[inline(always)]
+    pub fn count(&self, iterator_type: IteratorType, key: &K) -> Result<usize, Error> {
+        self.index.count(iterator_type, key)
+    }
+
+    /// Return the number of rows in the index.
+    #[inline(always)]
+    pub fn len(&self) -> Result<usize, Error> {
+        self.index.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        self.index.is_empty()
+    }
+}
+
+/// Index iterator which decodes tuples into `T`. Returned by
+/// [`TypedIndex::select`].
+pub struct TypedIndexIterator<T> {
+    inner: IndexIterator,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedIndexIterator<T> {
+    #[inline(always)]
+    pub fn new(inner: IndexIterator) -> Self {
+        Self {
+            inner,
+            marker: PhantomData,
+        }
+    }
+
+    /// Return the underlying untyped iterator.
+    #[inline(always)]
+    pub fn into_inner(self) -> IndexIterator {
+        self.inner
+    }
+}
+
+impl<T> Iterator for TypedIndexIterator<T>
+where
+    T: DecodeOwned,
+{
+    type Item = Result<T, Error>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|t| t.decode())
+    }
+}
+
 #[cfg(feature = "internal_test")]
 mod tests {
     use super::*;
@@ -1050,7 +1808,7 @@ mod tests {
                 index_id: 0,
                 name: "pk".into(),
                 r#type: IndexType::Hash,
-                opts: BTreeMap::from([("unique".into(), Value::from(true)),]),
+                opts: BTreeMap::from([("unique".into(), rmpv::Value::from(true))]),
                 parts: vec![Part {
                     field: 0.into(),
                     r#type: Some(FieldType::Unsigned),
@@ -1081,7 +1839,7 @@ mod tests {
                 index_id: 1,
                 name: "i".into(),
                 r#type: IndexType::Tree,
-                opts: BTreeMap::from([("unique".into(), Value::from(false)),]),
+                opts: BTreeMap::from([("unique".into(), rmpv::Value::from(false))]),
                 parts: vec![
                     Part {
                         field: 1.into(),
@@ -1108,6 +1866,313 @@ mod tests {
         space.drop().unwrap();
     }
 
+    #[crate::test(tarantool = "crate")]
+    fn select_page() {
+        if !crate::ffi::has_iterator_position() {
+            return;
+        }
+        let space = Space::builder("test_select_page_space")
+            .field(("id", space::FieldType::Unsigned))
+            .field(("value", space::FieldType::Unsigned))
+            .create()
+            .unwrap();
+        let index = space.index_builder("pk").create().unwrap();
+        for i in 0..10 {
+            space.insert(&(i, i * 10)).unwrap();
+        }
+        let ids = |page: &Page| -> Vec<u32> {
+            page.tuples
+                .iter()
+                .map(|t| t.field(0).unwrap().unwrap())
+                .collect()
+        };
+
+        let mut options = SelectOptions {
+            limit: Some(4),
+            offset: 1,
+            ..Default::default()
+        };
+        let page = index.select_page(IteratorType::All, &(), &options).unwrap();
+        assert_eq!(ids(&page), [1, 2, 3, 4]);
+
+        // Position survives modifications of the index
+        space.delete(&(5,)).unwrap();
+        options.offset = 0;
+        options.after = page.position.map(After::from);
+        let page = index.select_page(IteratorType::All, &(), &options).unwrap();
+        assert_eq!(ids(&page), [6, 7, 8, 9]);
+
+        options.after = page.position.clone().map(After::from);
+        let page = index.select_page(IteratorType::All, &(), &options).unwrap();
+        assert!(page.tuples.is_empty());
+        assert_eq!(page.position, None);
+
+        // Start after a tuple which isn't present in the index
+        options.after = Some(After::Tuple((5, 0).to_tuple_buffer().unwrap()));
+        options.limit = Some(2);
+        let page = index.select_page(IteratorType::All, &(), &options).unwrap();
+        assert_eq!(ids(&page), [6, 7]);
+
+        // Iterator position can be used directly
+        let mut iter = index.select(IteratorType::LT, &(4,)).unwrap();
+        assert!(iter.position().unwrap().is_empty());
+        assert_eq!(iter.next().unwrap().field::<u32>(0).unwrap(), Some(3));
+        let pos = iter.position().unwrap();
+        assert_eq!(pos, index.tuple_position(&(3, 30)).unwrap());
+        let iter = index
+            .select_after(IteratorType::LT, &(4,), &After::Position(pos))
+            .unwrap();
+        let rest: Vec<u32> = iter.map(|t| t.field(0).unwrap().unwrap()).collect();
+        assert_eq!(rest, [2, 1, 0]);
+
+        space.drop().unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn select_range_and_prefix() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Row {
+            group: String,
+            id: u32,
+        }
+
+        let space = Space::builder("test_select_range_space")
+            .field(("group", space::FieldType::String))
+            .field(("id", space::FieldType::Unsigned))
+            .create()
+            .unwrap();
+        let index = space
+            .index_builder("pk")
+            .parts(["group", "id"])
+            .create()
+            .unwrap();
+        for group in ["a", "b", "c"] {
+            for id in 1..=3 {
+                space.insert(&(group, id)).unwrap();
+            }
+        }
+        let keys = |iter: IndexIterator| -> Vec<(String, u32)> {
+            iter.map(|t| t.decode().unwrap()).collect()
+        };
+        let k = |group: &str, id| (group.to_string(), id);
+
+        let res = keys(index.select_range(("a", 3)..("b", 2)).unwrap());
+        assert_eq!(res, [k("a", 3), k("b", 1)]);
+
+        let res = keys(index.select_range(("a", 3)..=("b", 2)).unwrap());
+        assert_eq!(res, [k("a", 3), k("b", 1), k("b", 2)]);
+
+        // Partial keys
+        let res = keys(index.select_range(("b",)..=("b",)).unwrap());
+        assert_eq!(res, [k("b", 1), k("b", 2), k("b", 3)]);
+
+        let res = keys(index.select_range(..("a", 3)).unwrap());
+        assert_eq!(res, [k("a", 1), k("a", 2)]);
+
+        let res = keys(
+            index
+                .select_range((Bound::Excluded(("c", 1)), Bound::Unbounded))
+                .unwrap(),
+        );
+        assert_eq!(res, [k("c", 2), k("c", 3)]);
+
+        let res = keys(
+            index
+                .select(IteratorType::GE, &("b", 2))
+                .unwrap()
+                .take_while_prefix(&("b",))
+                .unwrap(),
+        );
+        assert_eq!(res, [k("b", 2), k("b", 3)]);
+
+        let res = keys(
+            index
+                .select(IteratorType::LE, &("b", 2))
+                .unwrap()
+                .take_while_prefix(&("b",))
+                .unwrap(),
+        );
+        assert_eq!(res, [k("b", 2), k("b", 1)]);
+
+        let rows: Vec<Row> = index
+            .select_range(("c",)..)
+            .unwrap()
+            .decode::<Row>()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|r| r.group == "c"));
+
+        // Decode errors are reported per item
+        let mut iter = index
+            .select_range(("a",)..=("a",))
+            .unwrap()
+            .decode::<(u32, u32)>();
+        assert!(matches!(iter.next(), Some(Err(Error::Decode(_)))));
+
+        let typed: TypedIndex<Row, (String, u32)> = TypedIndex::new(index).unwrap();
+        let ids: Vec<_> = typed
+            .select_range((k("a", 2))..(k("b", 0)))
+            .unwrap()
+            .map(|r| r.unwrap().id)
+            .collect();
+        assert_eq!(ids, [2, 3]);
+
+        // The position of a bounded iterator points at the last tuple it
+        // returned, not at the one which was rejected by the bound.
+        if crate::ffi::has_iterator_position() {
+            let mut iter = index.select_range(("a", 2)..("b", 1)).unwrap();
+            let res: Vec<(String, u32)> = iter.by_ref().map(|t| t.decode().unwrap()).collect();
+            assert_eq!(res, [k("a", 2), k("a", 3)]);
+            let pos = iter.position().unwrap();
+            let res = keys(
+                index
+                    .select_after(IteratorType::GE, &(), &After::Position(pos))
+                    .unwrap(),
+            );
+            assert_eq!(res[0], k("b", 1));
+
+            // Nothing was returned, the iteration is resumed from the start.
+            let mut iter = index.select_range(("a", 2)..("a", 2)).unwrap();
+            assert!(iter.next().is_none());
+            assert!(iter.position().unwrap().is_empty());
+        }
+
+        space.drop().unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn functional_and_multikey_index() {
+        let space = Space::builder("test_functional_index_space")
+            .field(("id", space::FieldType::Unsigned))
+            .field(("name", space::FieldType::String))
+            .field(("tags", space::FieldType::Array))
+            .create()
+            .unwrap();
+        space.index_builder("pk").create().unwrap();
+
+        let lua = crate::lua_state();
+        lua.exec(
+            "box.schema.func.create('test_lower_name', {
+                body = 'function(t) return {t[2]:lower()} end',
+                is_deterministic = true,
+                is_sandboxed = true,
+                if_not_exists = true,
+            })",
+        )
+        .unwrap();
+
+        let by_lower = space
+            .index_builder("by_lower_name")
+            .unique(false)
+            .func("test_lower_name")
+            .part((1, FieldType::String))
+            .create()
+            .unwrap();
+        let by_tag = space
+            .index_builder("by_tag")
+            .unique(false)
+            .part((
+                space::FieldPath::field("tags").any().key("name"),
+                FieldType::String,
+            ))
+            .create()
+            .unwrap();
+        let by_tag_nonnull = space
+            .index_builder("by_tag_nonnull")
+            .unique(false)
+            // Field numbers of the parts are 1-based, 3 is `tags`.
+            .part(
+                Part::new(3, FieldType::String)
+                    .path("[1].name")
+                    .is_nullable(true)
+                    .exclude_null(true),
+            )
+            .create()
+            .unwrap();
+
+        fn tag(name: &str) -> BTreeMap<&str, &str> {
+            BTreeMap::from([("name", name)])
+        }
+        space.insert(&(1, "Foo", vec![tag("x"), tag("y")])).unwrap();
+        space.insert(&(2, "FOO", vec![tag("y")])).unwrap();
+        space
+            .insert(&(3, "bar", Vec::<BTreeMap<&str, &str>>::new()))
+            .unwrap();
+
+        let ids = |iter: IndexIterator| -> Vec<u32> {
+            iter.map(|t| t.field(0).unwrap().unwrap()).collect()
+        };
+        assert_eq!(
+            ids(by_lower.select(IteratorType::Eq, &("foo",)).unwrap()),
+            [1, 2]
+        );
+        assert_eq!(
+            ids(by_tag.select(IteratorType::Eq, &("y",)).unwrap()),
+            [1, 2]
+        );
+        assert_eq!(ids(by_tag.select(IteratorType::Eq, &("x",)).unwrap()), [1]);
+        assert_eq!(by_tag_nonnull.len().unwrap(), 2);
+
+        let meta = by_tag.meta().unwrap();
+        assert_eq!(meta.parts[0].path.as_deref(), Some("[*].name"));
+        let meta = by_tag_nonnull.meta().unwrap();
+        assert_eq!(meta.parts[0].exclude_null, Some(true));
+
+        space.drop().unwrap();
+        lua.exec("box.schema.func.drop('test_lower_name')").unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn vinyl_options_and_stat() {
+        let space = Space::builder("test_vinyl_stat_space")
+            .engine(space::SpaceEngineType::Vinyl)
+            .field(("id", space::FieldType::Unsigned))
+            .create()
+            .unwrap();
+        let index = space
+            .index_builder("pk")
+            .bloom_fpr(0.25)
+            .page_size(4096)
+            .range_size(1024 * 1024)
+            .run_count_per_level(3)
+            .run_size_ratio(3.5)
+            .create()
+            .unwrap();
+        let opts = index.meta().unwrap().opts;
+        assert_eq!(opts["run_count_per_level"], rmpv::Value::from(3));
+        assert_eq!(opts["page_size"], rmpv::Value::from(4096));
+        assert_eq!(opts["range_size"], rmpv::Value::from(1024 * 1024));
+        assert_eq!(opts["run_size_ratio"].as_f64(), Some(3.5));
+        assert_eq!(opts["bloom_fpr"].as_f64(), Some(0.25));
+
+        for i in 0..10 {
+            space.insert(&(i,)).unwrap();
+        }
+        index.get(&(1,)).unwrap().unwrap();
+
+        let stat = index.stat().unwrap();
+        assert_eq!(stat.rows, 10);
+        assert_eq!(stat.memory.rows, 10);
+        assert_eq!(stat.put.rows, 10);
+        assert!(stat.lookup >= 1);
+
+        let vinyl = crate::stat::vinyl().unwrap();
+        assert!(vinyl.tx.commit >= 10);
+        assert!(vinyl.memory.level0 > 0);
+
+        space.drop().unwrap();
+
+        let space = Space::builder("test_memtx_stat_space")
+            .field(("id", space::FieldType::Unsigned))
+            .create()
+            .unwrap();
+        let index = space.index_builder("pk").create().unwrap();
+        space.insert(&(1,)).unwrap();
+        assert_eq!(index.stat().unwrap(), Default::default());
+        space.drop().unwrap();
+    }
+
     #[crate::test(tarantool = "crate")]
     fn key_def_for_key() {
         let space = Space::builder("test_key_def_for_keys_space")
@@ -48,6 +48,7 @@
 pub mod cbus;
 pub mod clock;
 pub mod coio;
+pub mod config;
 pub mod datetime;
 pub mod decimal;
 #[doc(hidden)]
@@ -62,11 +63,13 @@ pub mod msgpack;
 pub mod net_box;
 pub mod network;
 pub mod proc;
+pub mod replication;
 pub mod schema;
 pub mod sequence;
 pub mod session;
 pub mod space;
 pub mod sql;
+pub mod stat;
 #[cfg(feature = "test")]
 pub mod test;
 pub mod transaction;
@@ -2,8 +2,9 @@ use std::rc::Rc;
 use std::vec::IntoIter;
 
 use crate::error::Error;
-use crate::index::IteratorType;
-use crate::tuple::{Encode, ToTupleBuffer, Tuple};
+use crate::index::{After, IteratorType, Page, SelectOptions};
+use crate::msgpack;
+use crate::tuple::{RawBytes, ToTupleBuffer, Tuple};
 
 use super::inner::ConnInner;
 use super::protocol;
@@ -66,6 +67,8 @@ impl RemoteIndex {
                     options.offset,
                     iterator_type,
                     key,
+                    None,
+                    false,
                 )
             },
             |buf, _| {
@@ -77,6 +80,80 @@ impl RemoteIndex {
         )
     }
 
+    /// The remote-call equivalent of the local call `Index::select_after(...)`
+    /// (see [details](../index/struct.Index.html#method.select_after)).
+    ///
+    /// Requires tarantool 2.11 on the remote side.
+    pub fn select_after<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        after: &After,
+        options: &Options,
+    ) -> Result<RemoteIndexIterator, Error>
+    where
+        K: ToTupleBuffer + ?Sized,
+    {
+        self.conn_inner.request(
+            |buf, sync| {
+                protocol::encode_select(
+                    buf,
+                    sync,
+                    self.space_id,
+                    self.index_id,
+                    options.limit.unwrap_or(u32::MAX),
+                    options.offset,
+                    iterator_type,
+                    key,
+                    Some(after),
+                    false,
+                )
+            },
+            |buf, _| {
+                protocol::decode_multiple_rows(buf, None).map(|result| RemoteIndexIterator {
+                    inner: result.into_iter(),
+                })
+            },
+            options,
+        )
+    }
+
+    /// The remote-call equivalent of the local call `Index::select_page(...)`
+    /// (see [details](../index/struct.Index.html#method.select_page)).
+    ///
+    /// The page is selected according to `page`, the `offset` & `limit`
+    /// fields of `options` are ignored. Requires tarantool 2.11 on the remote
+    /// side.
+    pub fn select_page<K>(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+        page: &SelectOptions,
+        options: &Options,
+    ) -> Result<Page, Error>
+    where
+        K: ToTupleBuffer + ?Sized,
+    {
+        self.conn_inner.request(
+            |buf, sync| {
+                protocol::encode_select(
+                    buf,
+                    sync,
+                    self.space_id,
+                    self.index_id,
+                    page.limit.unwrap_or(u32::MAX),
+                    page.offset,
+                    iterator_type,
+                    key,
+                    page.after.as_ref(),
+                    true,
+                )
+            },
+            |buf, _| protocol::decode_page(buf),
+            options,
+        )
+    }
+
     /// The remote-call equivalent of the local call `Space::update(...)`
     /// (see [details](../index/struct.Index.html#method.update)).
     pub fn update<K, Op>(
@@ -87,8 +164,11 @@ impl RemoteIndex {
     ) -> Result<Option<Tuple>, Error>
     where
         K: ToTupleBuffer + ?Sized,
-        Op: Encode,
+        Op: ToTupleBuffer,
     {
+        let mut ops_buf = Vec::with_capacity(4 + ops.len() * 4);
+        msgpack::write_array(&mut ops_buf, ops)?;
+        let ops = RawBytes::new(&ops_buf);
         self.conn_inner.request(
             |buf, sync| protocol::encode_update(buf, sync, self.space_id, self.index_id, key, ops),
             protocol::decode_single_row,
@@ -106,8 +186,11 @@ impl RemoteIndex {
     ) -> Result<Option<Tuple>, Error>
     where
         T: ToTupleBuffer + ?Sized,
-        Op: Encode,
+        Op: ToTupleBuffer,
     {
+        let mut ops_buf = Vec::with_capacity(4 + ops.len() * 4);
+        msgpack::write_array(&mut ops_buf, ops)?;
+        let ops = RawBytes::new(&ops_buf);
         self.conn_inner.request(
             |buf, sync| {
                 protocol::encode_upsert(buf, sync, self.space_id, self.index_id, value, ops)
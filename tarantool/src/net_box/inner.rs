@@ -9,6 +9,7 @@ use crate::coio::CoIOStream;
 use crate::error::Error;
 use crate::fiber::{is_cancelled, set_cancellable, sleep, time, Cond, Fiber};
 use crate::net_box::stream::ConnStream;
+use crate::space::Field;
 use crate::tuple::Decode;
 use crate::unwrap_or;
 
@@ -208,6 +209,14 @@ impl ConnInner {
         Ok(self.schema.lookup_index(name, space_id))
     }
 
+    pub fn lookup_space_format(
+        self: &Rc<Self>,
+        space_id: u32,
+    ) -> Result<Option<Result<Vec<Field>, String>>, Error> {
+        self.refresh_schema()?;
+        Ok(self.schema.lookup_space_format(space_id))
+    }
+
     pub fn close(self: &Rc<Self>) {
         let state = self.state.get();
         if matches!(state, ConnState::Connecting) || matches!(state, ConnState::Auth) {
@@ -9,7 +9,7 @@ use num_derive::FromPrimitive;
 use sha1::{Digest, Sha1};
 
 use crate::error::Error;
-use crate::index::IteratorType;
+use crate::index::{After, IteratorType, Page, Position};
 use crate::msgpack;
 use crate::tuple::{ToTupleBuffer, Tuple};
 
@@ -23,6 +23,7 @@ const LIMIT: u8 = 0x12;
 const OFFSET: u8 = 0x13;
 const ITERATOR: u8 = 0x14;
 const INDEX_BASE: u8 = 0x15;
+const FETCH_POSITION: u8 = 0x1f;
 
 const KEY: u8 = 0x20;
 const TUPLE: u8 = 0x21;
@@ -30,9 +31,12 @@ const FUNCTION_NAME: u8 = 0x22;
 const USER_NAME: u8 = 0x23;
 const EXPR: u8 = 0x27;
 const OPS: u8 = 0x28;
+const AFTER_POSITION: u8 = 0x2e;
+const AFTER_TUPLE: u8 = 0x2f;
 
 const DATA: u8 = 0x30;
 const ERROR: u8 = 0x31;
+const POSITION: u8 = 0x35;
 
 const SQL_TEXT: u8 = 0x40;
 const SQL_BIND: u8 = 0x41;
@@ -50,14 +54,18 @@ enum IProtoKey {
     Offset = OFFSET,
     Iterator = ITERATOR,
     IndexBase = INDEX_BASE,
+    FetchPosition = FETCH_POSITION,
     Key = KEY,
     Tuple = TUPLE,
     FunctionName = FUNCTION_NAME,
     UserName = USER_NAME,
     Expr = EXPR,
     Ops = OPS,
+    AfterPosition = AFTER_POSITION,
+    AfterTuple = AFTER_TUPLE,
     Data = DATA,
     Error = ERROR,
+    Position = POSITION,
     SqlText = SQL_TEXT,
     SqlBind = SQL_BIND,
 }
@@ -351,12 +359,15 @@ pub fn encode_select<K>(
     offset: u32,
     iterator_type: IteratorType,
     key: &K,
+    after: Option<&After>,
+    fetch_pos: bool,
 ) -> Result<(), Error>
 where
     K: ToTupleBuffer + ?Sized,
 {
     encode_header(stream, sync, IProtoType::Select)?;
-    rmp::encode::write_map_len(stream, 6)?;
+    let map_len = 6 + after.is_some() as u32 + fetch_pos as u32;
+    rmp::encode::write_map_len(stream, map_len)?;
     rmp::encode::write_pfix(stream, SPACE_ID)?;
     rmp::encode::write_u32(stream, space_id)?;
     rmp::encode::write_pfix(stream, INDEX_ID)?;
@@ -369,6 +380,22 @@ where
     rmp::encode::write_u32(stream, iterator_type as u32)?;
     rmp::encode::write_pfix(stream, KEY)?;
     key.write_tuple_data(stream)?;
+    match after {
+        Some(After::Position(pos)) => {
+            rmp::encode::write_pfix(stream, AFTER_POSITION)?;
+            rmp::encode::write_str_len(stream, pos.as_bytes().len() as u32)?;
+            stream.write_all(pos.as_bytes())?;
+        }
+        Some(After::Tuple(tuple)) => {
+            rmp::encode::write_pfix(stream, AFTER_TUPLE)?;
+            tuple.write_tuple_data(stream)?;
+        }
+        None => {}
+    }
+    if fetch_pos {
+        rmp::encode::write_pfix(stream, FETCH_POSITION)?;
+        rmp::encode::write_bool(stream, true)?;
+    }
     Ok(())
 }
 
@@ -588,6 +615,38 @@ pub fn decode_multiple_rows(
     Ok(vec![])
 }
 
+/// Decode the response to a select request with `fetch_pos` set.
+pub fn decode_page(buffer: &mut Cursor<Vec<u8>>) -> Result<Page, Error> {
+    let mut tuples = vec![];
+    let mut position = None;
+    let payload_len = rmp::decode::read_map_len(buffer)?;
+    for _ in 0..payload_len {
+        let key = rmp::decode::read_pfix(buffer)?;
+        match key {
+            DATA => {
+                let items_count = rmp::decode::read_array_len(buffer)? as usize;
+                tuples.reserve(items_count);
+                for _ in 0..items_count {
+                    tuples.push(decode_tuple(buffer)?);
+                }
+            }
+            POSITION => {
+                let len = rmp::decode::read_str_len(buffer)? as usize;
+                let mut pos = vec![0; len];
+                buffer.read_exact(&mut pos)?;
+                position = Some(Position::from_bytes(pos));
+            }
+            _ => {
+                msgpack::skip_value(buffer)?;
+            }
+        }
+    }
+    if tuples.is_empty() {
+        position = None;
+    }
+    Ok(Page { tuples, position })
+}
+
 pub fn decode_single_row(buffer: &mut Cursor<Vec<u8>>, _: &Header) -> Result<Option<Tuple>, Error> {
     let payload_len = rmp::decode::read_map_len(buffer)?;
     for _ in 0..payload_len {
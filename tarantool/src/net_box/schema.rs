@@ -8,7 +8,7 @@ use std::rc::Rc;
 use crate::error::Error;
 use crate::fiber::{Latch, LatchGuard};
 use crate::index::IteratorType;
-use crate::space::{SystemSpace, SYSTEM_ID_MAX};
+use crate::space::{Field, SystemSpace, SYSTEM_ID_MAX};
 use crate::tuple::Tuple;
 
 use super::inner::ConnInner;
@@ -19,6 +19,8 @@ pub struct ConnSchema {
     version: Cell<Option<u64>>,
     is_updating: Cell<bool>,
     space_ids: RefCell<HashMap<String, u32>>,
+    /// Space formats or the errors which happened when decoding them.
+    space_formats: RefCell<HashMap<u32, Result<Vec<Field>, String>>>,
     index_ids: RefCell<HashMap<(u32, String), u32>>,
     lock: Latch,
 }
@@ -37,6 +39,7 @@ impl ConnSchema {
             version: Cell::new(None),
             is_updating: Cell::new(false),
             space_ids: Default::default(),
+            space_formats: Default::default(),
             index_ids: Default::default(),
             lock: Latch::new(),
         });
@@ -78,18 +81,34 @@ impl ConnSchema {
     pub fn update(&self, conn_inner: &Rc<ConnInner>) -> Result<(), Error> {
         self.is_updating.set(true);
         let (spaces_data, actual_schema_version) = self.fetch_schema_spaces(conn_inner)?;
+        let indexes_data = self.fetch_schema_indexes(conn_inner)?;
+
+        // The cache is rebuilt from scratch, so that the dropped spaces &
+        // indexes are removed from it.
+        let mut space_ids = HashMap::with_capacity(spaces_data.len());
+        let mut space_formats = HashMap::with_capacity(spaces_data.len());
         for row in spaces_data {
             let (id, _, name) = row.decode::<(u32, u32, String)>()?;
-            self.space_ids.borrow_mut().insert(name, id);
+            space_ids.insert(name, id);
+            // Format is only used for validation, so the error is reported
+            // when the format is requested.
+            let format = match row.field::<Vec<Field>>(6) {
+                Ok(format) => Ok(format.unwrap_or_default()),
+                Err(e) => Err(e.to_string()),
+            };
+            space_formats.insert(id, format);
         }
 
-        for row in self.fetch_schema_indexes(conn_inner)? {
+        let mut index_ids = HashMap::with_capacity(indexes_data.len());
+        for row in indexes_data {
             let (space_id, index_id, name) = row.decode::<(u32, u32, String)>()?;
-            self.index_ids
-                .borrow_mut()
-                .insert((space_id, name), index_id);
+            index_ids.insert((space_id, name), index_id);
         }
 
+        *self.space_ids.borrow_mut() = space_ids;
+        *self.space_formats.borrow_mut() = space_formats;
+        *self.index_ids.borrow_mut() = index_ids;
+
         self.version.set(Some(actual_schema_version));
         self.is_updating.set(false);
         Ok(())
@@ -99,6 +118,10 @@ impl ConnSchema {
         self.space_ids.borrow().get(name).copied()
     }
 
+    pub fn lookup_space_format(&self, space_id: u32) -> Option<Result<Vec<Field>, String>> {
+        self.space_formats.borrow().get(&space_id).cloned()
+    }
+
     pub fn lookup_index(&self, name: &str, space_id: u32) -> Option<u32> {
         self.index_ids
             .borrow()
@@ -128,6 +151,8 @@ impl ConnSchema {
                     0,
                     IteratorType::GT,
                     &(SYSTEM_ID_MAX,),
+                    None,
+                    false,
                 )
             },
             |buf, header| Ok((decode_multiple_rows(buf, None)?, header.schema_version)),
@@ -148,6 +173,8 @@ impl ConnSchema {
                     0,
                     IteratorType::All,
                     &empty_array,
+                    None,
+                    false,
                 )
             },
             |buf, _| decode_multiple_rows(buf, None),
@@ -1,8 +1,9 @@
 use std::rc::Rc;
 
-use crate::error::Error;
+use crate::error::{Error, TarantoolErrorCode};
 use crate::index::IteratorType;
-use crate::tuple::{Encode, ToTupleBuffer, Tuple};
+use crate::space::Field;
+use crate::tuple::{ToTupleBuffer, Tuple};
 
 use super::index::{RemoteIndex, RemoteIndexIterator};
 use super::inner::ConnInner;
@@ -31,6 +32,33 @@ impl RemoteSpace {
             .map(|index_id| RemoteIndex::new(self.conn_inner.clone(), self.space_id, index_id)))
     }
 
+    /// Return the format of the remote space from the cached schema.
+    ///
+    /// The result can be used to validate
+    /// [`UpdateOps`](crate::space::UpdateOps) before sending them, see
+    /// [`UpdateOps::validate`](crate::space::UpdateOps::validate).
+    ///
+    /// Returns an error if the space is not in the cached schema (e.g. it was
+    /// dropped) or its format couldn't be decoded.
+    pub fn format(&self) -> Result<Vec<Field>, Error> {
+        match self.conn_inner.lookup_space_format(self.space_id)? {
+            Some(Ok(format)) => Ok(format),
+            Some(Err(e)) => Err(crate::set_and_get_error!(
+                TarantoolErrorCode::WrongSpaceFormat,
+                "Failed to decode format of space {}: {}",
+                self.space_id,
+                e,
+            )
+            .into()),
+            None => Err(crate::set_and_get_error!(
+                TarantoolErrorCode::NoSuchSpace,
+                "Space '{}' does not exist",
+                self.space_id,
+            )
+            .into()),
+        }
+    }
+
     /// Returns index with id = 0
     #[inline(always)]
     pub fn primary_key(&self) -> RemoteIndex {
@@ -96,7 +124,7 @@ impl RemoteSpace {
     ) -> Result<Option<Tuple>, Error>
     where
         K: ToTupleBuffer + ?Sized,
-        Op: Encode,
+        Op: ToTupleBuffer,
     {
         self.primary_key().update(key, ops, options)
     }
@@ -111,7 +139,7 @@ impl RemoteSpace {
     ) -> Result<Option<Tuple>, Error>
     where
         T: ToTupleBuffer + ?Sized,
-        Op: Encode,
+        Op: ToTupleBuffer,
     {
         self.primary_key().upsert(value, ops, options)
     }
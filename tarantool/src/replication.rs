@@ -0,0 +1,294 @@
+//! Box: replication
+//!
+//! Typed access to the replication status of the instance
+//! ([box.info.replication](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_info/replication/))
+//! and replication control functions (`box.ctl.*`).
+//!
+//! ```no_run
+//! use tarantool::replication;
+//!
+//! for replica in replication::replicas().unwrap() {
+//!     if let Some(upstream) = &replica.upstream {
+//!         if upstream.status != "follow" || upstream.lag > 1.0 {
+//!             println!("replica {} is unhealthy: {:?}", replica.id, upstream);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! See also:
+//! - [Lua reference: Submodule box.ctl](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_ctl/)
+//! - [`vclock`](crate::vclock)
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::vclock::{Lsn, Vclock};
+
+/// Replication status of a single replica from the instance's point of view,
+/// see [`replicas`].
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ReplicaInfo {
+    /// Replica id in the replica set.
+    pub id: u32,
+    pub uuid: String,
+    /// Log sequence number of the latest entry in the replica's WAL.
+    pub lsn: Lsn,
+    /// Replica's name, only for Tarantool >= 3.0.
+    pub name: Option<String>,
+    /// Status of the replication from this replica to the current instance.
+    /// `None` for the current instance and the replicas the instance doesn't
+    /// replicate from.
+    pub upstream: Option<Upstream>,
+    /// Status of the replication from the current instance to this replica.
+    /// `None` if the replica is not connected.
+    pub downstream: Option<Downstream>,
+}
+
+/// See [`ReplicaInfo::upstream`].
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Upstream {
+    /// Replication status, e.g. `"follow"`, `"connect"`, `"auth"`,
+    /// `"sync"`, `"stopped"`, `"disconnected"`.
+    pub status: String,
+    /// Seconds since the last event received from the replica.
+    pub idle: f64,
+    /// URI of the replica.
+    pub peer: String,
+    /// Replication lag in seconds.
+    pub lag: f64,
+    /// Error message if the replication is broken.
+    pub message: Option<String>,
+    /// System error message (e.g. errno) if the replication is broken.
+    pub system_message: Option<String>,
+}
+
+impl Upstream {
+    /// Returns `true` if the instance is following the replica.
+    #[inline(always)]
+    pub fn is_follow(&self) -> bool {
+        self.status == "follow"
+    }
+}
+
+/// See [`ReplicaInfo::downstream`].
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Downstream {
+    /// Replication status, e.g. `"follow"` or `"stopped"`.
+    pub status: String,
+    /// Seconds since the last event sent to the replica, only for Tarantool
+    /// >= 2.10.
+    pub idle: Option<f64>,
+    /// Vclock of the replica as it was last reported.
+    pub vclock: Option<Vclock>,
+    /// Replication lag in seconds, only for Tarantool >= 2.10.
+    pub lag: Option<f64>,
+    /// Error message if the replication is broken.
+    pub message: Option<String>,
+    /// System error message (e.g. errno) if the replication is broken.
+    pub system_message: Option<String>,
+}
+
+impl Downstream {
+    /// Returns `true` if the replica is following the instance.
+    #[inline(always)]
+    pub fn is_follow(&self) -> bool {
+        self.status == "follow"
+    }
+}
+
+/// Returns the replication status of every replica known to the instance,
+/// including the instance itself, ordered by replica id.
+///
+/// See [box.info.replication](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_info/replication/).
+#[inline]
+pub fn replicas() -> Result<Vec<ReplicaInfo>, Error> {
+    crate::util::lua_eval_decode(
+        "(function()
+            local res = setmetatable({}, require('msgpack').array_mt)
+            for _, replica in pairs(box.info.replication) do
+                table.insert(res, replica)
+            end
+            table.sort(res, function(a, b) return a.id < b.id end)
+            return res
+        end)()",
+    )
+}
+
+/// Returns the replication status of the replica with the given `id`.
+#[inline]
+pub fn replica(id: u32) -> Result<Option<ReplicaInfo>, Error> {
+    crate::util::lua_eval_decode_with("box.info.replication[...]", id)
+}
+
+/// Returns the id of the current instance in the replica set (`box.info.id`).
+#[inline]
+pub fn instance_id() -> Result<u32, Error> {
+    let res = crate::lua_state().eval("return box.info.id")?;
+    Ok(res)
+}
+
+/// Returns the uuid of the current instance (`box.info.uuid`).
+#[inline]
+pub fn instance_uuid() -> Result<String, Error> {
+    let res = crate::lua_state().eval("return box.info.uuid")?;
+    Ok(res)
+}
+
+/// Returns `true` if the instance is in read-only mode (`box.info.ro`).
+#[inline]
+pub fn is_ro() -> Result<bool, Error> {
+    let res = crate::lua_state().eval("return box.info.ro")?;
+    Ok(res)
+}
+
+/// Returns the status of the instance (`box.info.status`), e.g.
+/// `"running"`, `"loading"` or `"orphan"`.
+#[inline]
+pub fn status() -> Result<String, Error> {
+    let res = crate::lua_state().eval("return box.info.status")?;
+    Ok(res)
+}
+
+/// Make the current instance the leader of the replica set, see
+/// [box.ctl.promote()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_ctl/promote/).
+///
+/// Only for Tarantool >= 2.6.2.
+#[inline]
+pub fn promote() -> Result<(), Error> {
+    crate::lua_state().exec("box.ctl.promote()")?;
+    Ok(())
+}
+
+/// Revoke the leader role from the current instance, see
+/// [box.ctl.demote()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_ctl/demote/).
+///
+/// Only for Tarantool >= 2.8.
+#[inline]
+pub fn demote() -> Result<(), Error> {
+    crate::lua_state().exec("box.ctl.demote()")?;
+    Ok(())
+}
+
+/// Wait until the instance becomes read-only, see
+/// [box.ctl.wait_ro()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_ctl/wait_ro/).
+///
+/// If `timeout` is `None` waits indefinitely, otherwise returns an error when
+/// the timeout is exceeded.
+#[inline]
+pub fn wait_ro(timeout: Option<Duration>) -> Result<(), Error> {
+    crate::lua_state()
+        .exec_with("box.ctl.wait_ro(...)", timeout.map(|t| t.as_secs_f64()))
+        .map_err(tlua::LuaError::from)?;
+    Ok(())
+}
+
+/// Wait until the instance becomes read-write, see
+/// [box.ctl.wait_rw()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_ctl/wait_rw/).
+///
+/// If `timeout` is `None` waits indefinitely, otherwise returns an error when
+/// the timeout is exceeded.
+#[inline]
+pub fn wait_rw(timeout: Option<Duration>) -> Result<(), Error> {
+    crate::lua_state()
+        .exec_with("box.ctl.wait_rw(...)", timeout.map(|t| t.as_secs_f64()))
+        .map_err(tlua::LuaError::from)?;
+    Ok(())
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+
+    #[crate::test(tarantool = "crate")]
+    fn replication_info() {
+        let id = instance_id().unwrap();
+        let uuid = instance_uuid().unwrap();
+        assert_eq!(status().unwrap(), "running");
+        assert!(!is_ro().unwrap());
+
+        let replicas = replicas().unwrap();
+        let me = replicas.iter().find(|r| r.id == id).unwrap();
+        assert_eq!(me.uuid, uuid);
+        assert!(me.upstream.is_none());
+        assert_eq!(replica(id).unwrap().as_ref(), Some(me));
+        assert_eq!(replica(31).unwrap(), None);
+
+        wait_rw(Some(Duration::from_millis(100))).unwrap();
+        assert!(wait_ro(Some(Duration::from_millis(100))).is_err());
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn decode_replica_info() {
+        // The same as `box.info.replication[2]` of a replica set with another
+        // instance, which is connected in both directions.
+        let replica: ReplicaInfo = crate::util::lua_eval_decode(
+            "{
+                id = 2,
+                uuid = 'f0b6e2c2-4a5d-4c3e-9d8e-6d8b3f0b1c2d',
+                lsn = 7,
+                upstream = {
+                    status = 'follow',
+                    idle = 0.5,
+                    peer = 'replicator@localhost:3302',
+                    lag = 0.01,
+                },
+                downstream = {
+                    status = 'follow',
+                    idle = 0.25,
+                    vclock = {10, 7},
+                    lag = 0,
+                },
+            }",
+        )
+        .unwrap();
+        assert_eq!(replica.id, 2);
+        assert_eq!(replica.lsn, 7);
+        assert_eq!(replica.name, None);
+        let upstream = replica.upstream.unwrap();
+        assert!(upstream.is_follow());
+        assert_eq!(upstream.lag, 0.01);
+        assert_eq!(upstream.message, None);
+        let downstream = replica.downstream.unwrap();
+        assert!(downstream.is_follow());
+        assert_eq!(downstream.idle, Some(0.25));
+        assert_eq!(downstream.lag, Some(0.0));
+        let vclock = downstream.vclock.unwrap();
+        assert_eq!(vclock.get(1), 10);
+        assert_eq!(vclock.get(2), 7);
+
+        // Broken replication, older versions don't report downstream idle &
+        // lag.
+        let replica: ReplicaInfo = crate::util::lua_eval_decode(
+            "{
+                id = 3,
+                uuid = '0c9f1a4e-3b2d-4e5f-8a7b-1c2d3e4f5a6b',
+                lsn = 0,
+                name = 'r3',
+                upstream = {
+                    status = 'stopped',
+                    idle = 10,
+                    peer = 'localhost:3303',
+                    lag = 0,
+                    message = 'Duplicate key exists',
+                },
+                downstream = {
+                    status = 'stopped',
+                    message = 'unexpected EOF when reading from socket',
+                    system_message = 'Broken pipe',
+                },
+            }",
+        )
+        .unwrap();
+        assert_eq!(replica.name.as_deref(), Some("r3"));
+        let upstream = replica.upstream.unwrap();
+        assert!(!upstream.is_follow());
+        assert_eq!(upstream.message.as_deref(), Some("Duplicate key exists"));
+        let downstream = replica.downstream.unwrap();
+        assert!(!downstream.is_follow());
+        assert_eq!(downstream.idle, None);
+        assert_eq!(downstream.vclock, None);
+        assert_eq!(downstream.system_message.as_deref(), Some("Broken pipe"));
+    }
+}
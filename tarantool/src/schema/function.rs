@@ -0,0 +1,85 @@
+use serde::Serialize;
+use tlua::LuaError;
+
+use crate::error::{Error, TarantoolErrorCode};
+use crate::space::{Space, SystemSpace};
+
+crate::define_str_enum! {
+    /// Language of a stored function.
+    pub enum FuncLanguage {
+        Lua = "LUA",
+        C = "C",
+        SqlExpr = "SQL_EXPR",
+    }
+}
+
+/// Options for a new function, used by [`create_function`].
+/// (for details see [box.schema.func.create() options](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/func_create/)).
+///
+/// Options set to `None` are left to tarantool's defaults.
+#[derive(Clone, Debug, Default, Serialize, tlua::Push, PartialEq, Eq)]
+pub struct FuncOptions {
+    pub if_not_exists: Option<bool>,
+    pub setuid: Option<bool>,
+    pub language: Option<FuncLanguage>,
+    /// Function source code for persistent lua functions, e.g.
+    /// `"function(a, b) return a + b end"`.
+    pub body: Option<String>,
+    /// The function returns the same result for the same arguments. Required
+    /// for functions used in functional indexes and constraints.
+    pub is_deterministic: Option<bool>,
+    /// The function is executed in an isolated environment. Required for
+    /// functions used in functional indexes.
+    pub is_sandboxed: Option<bool>,
+    pub param_list: Option<Vec<String>>,
+    pub returns: Option<String>,
+    pub exports: Option<Vec<String>>,
+    pub opts: Option<FuncOpts>,
+}
+
+/// Additional function options, see [`FuncOptions::opts`].
+#[derive(Clone, Debug, Default, Serialize, tlua::Push, PartialEq, Eq)]
+pub struct FuncOpts {
+    /// The function returns multiple keys for a multikey functional index.
+    pub is_multikey: Option<bool>,
+}
+
+/// Create a stored function.
+/// (for details see [box.schema.func.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/func_create/)).
+///
+/// Returns the id of the new function.
+pub fn create_function(name: &str, opts: &FuncOptions) -> Result<u32, Error> {
+    let lua = crate::lua_state();
+    lua.exec_with(
+        "local name, opts = ...
+        box.schema.func.create(name, opts)",
+        (name, opts),
+    )
+    .map_err(LuaError::from)?;
+    // box.schema.func.create doesn't return anything, so look up the id.
+    function_id(name)?.ok_or(Error::MetaNotFound)
+}
+
+/// Drop a stored function.
+/// (for details see [box.schema.func.drop()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/func_drop/)).
+pub fn drop_function(name: &str) -> Result<(), Error> {
+    let lua = crate::lua_state();
+    lua.exec_with("box.schema.func.drop(...)", name)
+        .map_err(LuaError::from)?;
+    Ok(())
+}
+
+/// Find the id of a stored function by `name`.
+pub fn function_id(name: &str) -> Result<Option<u32>, Error> {
+    let sys_vfunc: Space = SystemSpace::VFunc.into();
+    let name_idx = sys_vfunc.index("name").ok_or_else(|| {
+        crate::set_and_get_error!(
+            TarantoolErrorCode::NoSuchIndexName,
+            "No index 'name' is defined in space '_vfunc'",
+        )
+    })?;
+    Ok(match name_idx.get(&(name,))? {
+        None => None,
+        Some(func) => func.field::<u32>(0)?,
+    })
+}
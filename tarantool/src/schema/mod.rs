@@ -1,3 +1,4 @@
+pub mod function;
 pub mod index;
 pub mod sequence;
 pub mod space;
@@ -1,6 +1,54 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use tlua::LuaError;
+
 use crate::error::Error;
 use crate::schema;
+use crate::sequence::{SeqOptions, Sequence};
 use crate::space::{Space, SystemSpace};
+use crate::tuple::Encode;
+
+/// Create a sequence.
+/// (for details see [box.schema.sequence.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/create/)).
+///
+/// - `name` - name of sequence, which should conform to the rules for object names.
+/// - `opts` - see SeqOptions struct.
+///
+/// Returns a new sequence.
+pub fn create_sequence(name: &str, opts: &SeqOptions) -> Result<Sequence, Error> {
+    let lua = crate::lua_state();
+    let seq_id: u32 = lua
+        .eval_with(
+            "local name, opts = ...
+            return box.schema.sequence.create(name, opts).id",
+            (name, opts),
+        )
+        .map_err(LuaError::from)?;
+    Ok(Sequence::new(seq_id))
+}
+
+/// Alter an existing sequence.
+/// (for details see [sequence_object:alter()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/alter/)).
+///
+/// - `seq_id` - ID of existing sequence.
+/// - `opts` - see SeqOptions struct. `id` and `if_not_exists` options are
+///   ignored.
+pub fn alter_sequence(seq_id: u32, opts: &SeqOptions) -> Result<(), Error> {
+    let opts = SeqOptions {
+        id: None,
+        if_not_exists: None,
+        ..opts.clone()
+    };
+    let lua = crate::lua_state();
+    lua.exec_with(
+        "local seq_id, opts = ...
+        box.schema.sequence.alter(seq_id, opts)",
+        (seq_id, &opts),
+    )
+    .map_err(LuaError::from)?;
+    Ok(())
+}
 
 /// Drop existing sequence.
 ///
@@ -8,11 +56,39 @@ use crate::space::{Space, SystemSpace};
 pub fn drop_sequence(seq_id: u32) -> Result<(), Error> {
     schema::revoke_object_privileges("sequence", seq_id)?;
 
-    let sys_sequence: Space = SystemSpace::Sequence.into();
-    sys_sequence.delete(&(seq_id,))?;
-
+    // Sequence data must be removed first, otherwise tarantool refuses to
+    // drop a sequence which has already been used.
     let sys_sequence_data: Space = SystemSpace::SequenceData.into();
     sys_sequence_data.delete(&(seq_id,))?;
 
+    let sys_sequence: Space = SystemSpace::Sequence.into();
+    sys_sequence.delete(&(seq_id,))?;
+
     Ok(())
 }
+
+/// SequenceMetadata is tuple, holding sequence metadata in system `_sequence` space.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SequenceMetadata<'a> {
+    pub id: u32,
+    pub owner: u32,
+    pub name: Cow<'a, str>,
+    pub step: i64,
+    pub min: i64,
+    pub max: i64,
+    pub start: i64,
+    pub cache: i64,
+    pub cycle: bool,
+}
+
+impl Encode for SequenceMetadata<'_> {}
+
+/// SequenceData is tuple, holding the last value generated by the sequence in
+/// system `_sequence_data` space.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceData {
+    pub id: u32,
+    pub value: i64,
+}
+
+impl Encode for SequenceData {}
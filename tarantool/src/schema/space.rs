@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::{cmp::max, collections::BTreeMap};
 
+use rmpv::Value as MpValue;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, TarantoolError, TarantoolErrorCode};
@@ -9,10 +10,10 @@ use crate::schema;
 use crate::schema::sequence as schema_seq;
 use crate::session;
 use crate::set_error;
+use crate::space::{Constraints, SpaceCreateOptions, SpaceEngineType};
 use crate::space::{Space, SystemSpace, SYSTEM_ID_MAX};
-use crate::space::{SpaceCreateOptions, SpaceEngineType};
 use crate::tuple::{Encode, Tuple};
-use crate::util::Value;
+use crate::util::NumOrStr;
 
 /// Create a space.
 /// (for details see [box.schema.space.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema/space_create/)).
@@ -21,7 +22,19 @@ use crate::util::Value;
 /// - `opts` - see SpaceCreateOptions struct.
 ///
 /// Returns a new space.
+#[inline(always)]
 pub fn create_space(name: &str, opts: &SpaceCreateOptions) -> Result<Space, Error> {
+    create_space_with_constraints(name, opts, &Constraints::default())
+}
+
+/// Create a space with constraints & foreign keys set via [`space::Builder`].
+///
+/// [`space::Builder`]: crate::space::Builder
+pub(crate) fn create_space_with_constraints(
+    name: &str,
+    opts: &SpaceCreateOptions,
+    constraints: &Constraints,
+) -> Result<Space, Error> {
     // Check if space already exists.
     if let Some(space) = Space::find(name) {
         return if opts.if_not_exists {
@@ -50,55 +63,151 @@ pub fn create_space(name: &str, opts: &SpaceCreateOptions) -> Result<Space, Erro
     // Resolve ID of new space or use ID, specified in options.
     let id = opts.id.map(Ok).unwrap_or_else(resolve_new_space_id)?;
 
-    let flags = opts
-        .is_local
-        .then(|| ("group_id".into(), Value::Num(1)))
-        .into_iter()
-        .chain(
-            opts.is_temporary
-                .then(|| ("temporary".into(), Value::Bool(true))),
-        )
-        .chain(opts.is_sync.then(|| ("is_sync".into(), Value::Bool(true))))
-        .collect();
-
-    let format = opts
-        .format
-        .iter()
-        .flat_map(|f| f.iter())
-        .map(|f| {
-            IntoIterator::into_iter([
-                ("name".into(), Value::Str(f.name.as_str().into())),
-                ("type".into(), Value::Str(f.field_type.as_str().into())),
-                ("is_nullable".into(), Value::Bool(f.is_nullable)),
-            ])
-            .collect()
-        })
-        .collect();
+    let mut flags = vec![];
+    if opts.is_local {
+        flags.push(("group_id".into(), 1.into()));
+    }
+    if opts.is_temporary {
+        flags.push(("temporary".into(), true.into()));
+    }
+    if opts.is_sync {
+        flags.push(("is_sync".into(), true.into()));
+    }
+    if !constraints.tuple.is_empty() {
+        flags.push((
+            "constraint".into(),
+            resolve_constraints(&constraints.tuple)?,
+        ));
+    }
+    if !constraints.tuple_foreign_key.is_empty() {
+        let mut fkeys = Vec::with_capacity(constraints.tuple_foreign_key.len());
+        for (fk_name, fk) in &constraints.tuple_foreign_key {
+            let field = fk
+                .field
+                .iter()
+                .map(|(local, foreign)| (num_or_str_to_mp(local), num_or_str_to_mp(foreign)))
+                .collect();
+            let fk = MpValue::Map(vec![
+                ("space".into(), resolve_space(&fk.space, name, id)?.into()),
+                ("field".into(), MpValue::Map(field)),
+            ]);
+            fkeys.push((fk_name.as_str().into(), fk));
+        }
+        flags.push(("foreign_key".into(), MpValue::Map(fkeys)));
+    }
+
+    let fields = opts.format.as_deref().unwrap_or_default();
+    let field_names = constraints
+        .field
+        .keys()
+        .chain(constraints.field_foreign_key.keys());
+    for field_name in field_names {
+        if !fields.iter().any(|f| &f.name == field_name) {
+            set_error!(
+                TarantoolErrorCode::NoSuchFieldNameInSpace,
+                "Field '{}' was not found in space '{}' format",
+                field_name,
+                name
+            );
+            return Err(TarantoolError::last().into());
+        }
+    }
+
+    let mut format = vec![];
+    for f in fields {
+        let mut field = vec![
+            ("name".into(), f.name.as_str().into()),
+            ("type".into(), f.field_type.as_str().into()),
+            ("is_nullable".into(), f.is_nullable.into()),
+        ];
+        if let Some(constraint) = constraints.field.get(&f.name) {
+            field.push(("constraint".into(), resolve_constraints(constraint)?));
+        }
+        if let Some(foreign_key) = constraints.field_foreign_key.get(&f.name) {
+            let mut fkeys = Vec::with_capacity(foreign_key.len());
+            for (fk_name, fk) in foreign_key {
+                let fk = MpValue::Map(vec![
+                    ("space".into(), resolve_space(&fk.space, name, id)?.into()),
+                    ("field".into(), num_or_str_to_mp(&fk.field)),
+                ]);
+                fkeys.push((fk_name.as_str().into(), fk));
+            }
+            field.push(("foreign_key".into(), MpValue::Map(fkeys)));
+        }
+        format.push(MpValue::Map(field));
+    }
 
     let sys_space: Space = SystemSpace::Space.into();
-    sys_space.insert(&SpaceMetadata {
+    sys_space.insert(&(
         id,
         user_id,
-        name: name.into(),
-        engine: opts.engine,
-        field_count: opts.field_count,
-        flags,
+        name,
+        opts.engine,
+        opts.field_count,
+        MpValue::Map(flags),
         format,
-    })?;
+    ))?;
 
     Ok(Space::find(name).unwrap())
 }
 
+/// Resolve the names of constraint functions into ids, which is how
+/// constraints are stored in `_space`.
+fn resolve_constraints(constraints: &BTreeMap<String, NumOrStr>) -> Result<MpValue, Error> {
+    let mut res = Vec::with_capacity(constraints.len());
+    for (name, func) in constraints {
+        let func_id = match func {
+            NumOrStr::Num(id) => *id,
+            NumOrStr::Str(func_name) => match schema::function::function_id(func_name)? {
+                Some(id) => id,
+                None => {
+                    set_error!(TarantoolErrorCode::NoSuchFunction, "{}", func_name);
+                    return Err(TarantoolError::last().into());
+                }
+            },
+        };
+        res.push((name.as_str().into(), func_id.into()));
+    }
+    Ok(MpValue::Map(res))
+}
+
+/// Resolve the name of a space referenced by a foreign key into an id. The
+/// space being created (`new_name`, `new_id`) can reference itself.
+fn resolve_space(space: &NumOrStr, new_name: &str, new_id: u32) -> Result<u32, Error> {
+    match space {
+        NumOrStr::Num(id) => Ok(*id),
+        NumOrStr::Str(name) if name == new_name => Ok(new_id),
+        NumOrStr::Str(name) => match Space::find(name) {
+            Some(space) => Ok(space.id()),
+            None => {
+                set_error!(TarantoolErrorCode::NoSuchSpace, "{}", name);
+                Err(TarantoolError::last().into())
+            }
+        },
+    }
+}
+
+#[inline]
+fn num_or_str_to_mp(v: &NumOrStr) -> MpValue {
+    match v {
+        NumOrStr::Num(n) => (*n).into(),
+        NumOrStr::Str(s) => s.as_str().into(),
+    }
+}
+
 /// SpaceMetadata is tuple, holding space metadata in system `_space` space.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SpaceMetadata<'a> {
     pub id: u32,
     pub user_id: u32,
     pub name: Cow<'a, str>,
     pub engine: SpaceEngineType,
     pub field_count: u32,
-    pub flags: BTreeMap<Cow<'a, str>, Value<'a>>,
-    pub format: Vec<BTreeMap<Cow<'a, str>, Value<'a>>>,
+    /// Space options, e.g. `temporary`, `is_sync` or `constraint`.
+    pub flags: BTreeMap<Cow<'a, str>, MpValue>,
+    /// Space format. Unlike [`Space::format`] this includes field constraints
+    /// & foreign keys.
+    pub format: Vec<BTreeMap<Cow<'a, str>, MpValue>>,
 }
 
 impl Encode for SpaceMetadata<'_> {}
@@ -1,14 +1,68 @@
 //! Box: sequences
+use serde::Serialize;
+
 use crate::error::{Error, TarantoolError};
 use crate::ffi::tarantool as ffi;
+use crate::schema::sequence::{SequenceData, SequenceMetadata};
 use crate::space::{Space, SystemSpace};
 
+/// Options for new or altered sequence, used by [`Sequence::create`] &
+/// [`Sequence::alter`].
+/// (for details see [box.schema.sequence.create() options](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/create/)).
+///
+/// Options set to `None` are left to tarantool's defaults.
+#[derive(Clone, Debug, Default, Serialize, tlua::Push, PartialEq, Eq)]
+pub struct SeqOptions {
+    pub start: Option<i64>,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub step: Option<i64>,
+    pub cycle: Option<bool>,
+    pub cache: Option<i64>,
+    pub id: Option<u32>,
+    pub if_not_exists: Option<bool>,
+}
+
 /// A sequence is a generator of ordered integer values.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Sequence {
     seq_id: u32,
 }
 
 impl Sequence {
+    #[inline(always)]
+    pub(crate) fn new(seq_id: u32) -> Self {
+        Self { seq_id }
+    }
+
+    /// Create a sequence.
+    /// (for details see [box.schema.sequence.create()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/create/)).
+    ///
+    /// - `name` - name of sequence, which should conform to the rules for object names.
+    /// - `opts` - see [`SeqOptions`] struct.
+    ///
+    /// ```no_run
+    /// use tarantool::sequence::{Sequence, SeqOptions};
+    ///
+    /// let mut seq = Sequence::create(
+    ///     "countdown",
+    ///     &SeqOptions {
+    ///         start: Some(10),
+    ///         min: Some(0),
+    ///         step: Some(-1),
+    ///         ..Default::default()
+    ///     },
+    /// )
+    /// .unwrap();
+    /// assert_eq!(seq.next().unwrap(), 10);
+    /// ```
+    ///
+    /// Returns a new sequence.
+    #[inline(always)]
+    pub fn create(name: &str, opts: &SeqOptions) -> Result<Self, Error> {
+        crate::schema::sequence::create_sequence(name, opts)
+    }
+
     /// Find sequence by name.
     pub fn find(name: &str) -> Result<Option<Self>, Error> {
         let space: Space = SystemSpace::Sequence.into();
@@ -16,20 +70,40 @@ impl Sequence {
 
         Ok(match name_idx.get(&(name,))? {
             None => None,
-            Some(row_tuple) => Some(Sequence {
-                seq_id: row_tuple.field(0)?.unwrap(),
-            }),
+            Some(row_tuple) => Some(Sequence::new(row_tuple.field(0)?.unwrap())),
         })
     }
 
+    /// Get sequence ID.
+    #[inline(always)]
+    pub fn id(&self) -> u32 {
+        self.seq_id
+    }
+
+    /// Change the options of the sequence.
+    /// (for details see [sequence_object:alter()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_schema_sequence/alter/)).
+    ///
+    /// Only the options set to `Some` are changed, `id` and `if_not_exists`
+    /// are ignored.
+    #[inline(always)]
+    pub fn alter(&self, opts: &SeqOptions) -> Result<(), Error> {
+        crate::schema::sequence::alter_sequence(self.seq_id, opts)
+    }
+
+    /// Drop the sequence.
+    #[inline(always)]
+    pub fn drop_sequence(&self) -> Result<(), Error> {
+        crate::schema::sequence::drop_sequence(self.seq_id)
+    }
+
     #[allow(clippy::should_implement_trait)]
     /// Generate the next value and return it.
     ///
     /// The generation algorithm is simple:
     /// - If this is the first time, then return the `start` value.
     /// - If the previous value plus the `increment` value is less than the `minimum` value or greater than the
-    /// `maximum` value, that is "overflow", so either raise an error (if `cycle = false`) or return the `maximum` value
-    /// (if `cycle = true` and `step < 0`) or return the `minimum` value (if `cycle = true` and `step > 0`).
+    ///   `maximum` value, that is "overflow", so either raise an error (if `cycle = false`) or return the `maximum` value
+    ///   (if `cycle = true` and `step < 0`) or return the `minimum` value (if `cycle = true` and `step > 0`).
     ///
     /// If there was no error, then save the returned result, it is now the "previous value".
     pub fn next(&mut self) -> Result<i64, Error> {
@@ -63,4 +137,112 @@ impl Sequence {
             Ok(())
         }
     }
+
+    /// Return the last value generated by the sequence.
+    ///
+    /// Returns `None` if [next](#method.next) was never called since the
+    /// sequence was created or [reset](#method.reset).
+    #[inline]
+    pub fn current(&self) -> Result<Option<i64>, Error> {
+        let sys_sequence_data: Space = SystemSpace::SequenceData.into();
+        let data = sys_sequence_data.get(&(self.seq_id,))?;
+        match data {
+            None => Ok(None),
+            Some(tuple) => Ok(Some(tuple.decode::<SequenceData>()?.value)),
+        }
+    }
+
+    /// Return sequence metadata from system `_sequence` space.
+    #[inline]
+    pub fn meta(&self) -> Result<SequenceMetadata<'static>, Error> {
+        let sys_sequence: Space = SystemSpace::Sequence.into();
+        let tuple = sys_sequence
+            .get(&(self.seq_id,))?
+            .ok_or(Error::MetaNotFound)?;
+        tuple.decode::<SequenceMetadata>()
+    }
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+
+    #[crate::test(tarantool = "crate")]
+    fn create_alter_drop() {
+        let seq = Sequence::create(
+            "test_seq_create_alter_drop",
+            &SeqOptions {
+                start: Some(10),
+                min: Some(5),
+                max: Some(12),
+                step: Some(2),
+                cycle: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            Sequence::find("test_seq_create_alter_drop").unwrap(),
+            Some(seq.clone())
+        );
+
+        let meta = seq.meta().unwrap();
+        assert_eq!(
+            meta,
+            SequenceMetadata {
+                id: seq.id(),
+                owner: meta.owner,
+                name: "test_seq_create_alter_drop".into(),
+                step: 2,
+                min: 5,
+                max: 12,
+                start: 10,
+                cache: 0,
+                cycle: true,
+            }
+        );
+
+        let mut seq = seq;
+        assert_eq!(seq.current().unwrap(), None);
+        assert_eq!(seq.next().unwrap(), 10);
+        assert_eq!(seq.next().unwrap(), 12);
+        assert_eq!(seq.current().unwrap(), Some(12));
+        assert_eq!(seq.next().unwrap(), 5);
+
+        seq.alter(&SeqOptions {
+            step: Some(3),
+            cycle: Some(false),
+            ..Default::default()
+        })
+        .unwrap();
+        let meta = seq.meta().unwrap();
+        assert_eq!((meta.step, meta.cycle), (3, false));
+        assert_eq!((meta.min, meta.max), (5, 12));
+        assert_eq!(seq.next().unwrap(), 8);
+
+        seq.reset().unwrap();
+        assert_eq!(seq.current().unwrap(), None);
+
+        seq.next().unwrap();
+        seq.drop_sequence().unwrap();
+        assert_eq!(Sequence::find("test_seq_create_alter_drop").unwrap(), None);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn create_if_not_exists() {
+        let seq = Sequence::create("test_seq_if_not_exists", &Default::default()).unwrap();
+        assert!(Sequence::create("test_seq_if_not_exists", &Default::default()).is_err());
+
+        let same = Sequence::create(
+            "test_seq_if_not_exists",
+            &SeqOptions {
+                if_not_exists: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(seq, same);
+
+        seq.drop_sequence().unwrap();
+    }
 }
@@ -7,9 +7,12 @@
 //! - [Lua reference: Submodule box.space](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/)
 //! - [C API reference: Module box](https://www.tarantool.io/en/doc/latest/dev_guide/reference_capi/box/)
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::marker::PhantomData;
 use std::ops::Range;
 use std::os::raw::c_char;
+use std::time::Duration;
 
 use num_derive::ToPrimitive;
 use serde::{Deserialize, Serialize};
@@ -17,11 +20,12 @@ use serde_json::{Map, Value};
 
 use crate::error::{Error, TarantoolError};
 use crate::ffi::tarantool as ffi;
-use crate::index::{Index, IndexIterator, IteratorType};
+use crate::index::{Index, IndexIterator, IndexKey, IteratorType, TypedIndex};
 use crate::schema::space::SpaceMetadata;
-use crate::tuple::{Encode, ToTupleBuffer, Tuple, TupleBuffer};
+use crate::tuple::{DecodeOwned, Encode, ToTupleBuffer, Tuple, TupleBuffer};
 use crate::tuple_from_box_api;
 use crate::unwrap_or;
+use crate::util::NumOrStr;
 
 /// End of the reserved range of system spaces.
 pub const SYSTEM_ID_MAX: SpaceId = 511;
@@ -148,6 +152,52 @@ impl Default for SpaceCreateOptions {
     }
 }
 
+/// Tuple foreign key definition, see [`Builder::foreign_key`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ForeignKey {
+    /// Name or id of the referenced space.
+    pub space: NumOrStr,
+    /// Pairs of local and foreign fields specified by names or 0-based
+    /// numbers.
+    pub field: Vec<(NumOrStr, NumOrStr)>,
+}
+
+impl ForeignKey {
+    #[inline]
+    pub fn new<L, F>(space: impl Into<NumOrStr>, field: impl IntoIterator<Item = (L, F)>) -> Self
+    where
+        L: Into<NumOrStr>,
+        F: Into<NumOrStr>,
+    {
+        Self {
+            space: space.into(),
+            field: field
+                .into_iter()
+                .map(|(l, f)| (l.into(), f.into()))
+                .collect(),
+        }
+    }
+}
+
+/// Field foreign key definition, see [`Builder::field_foreign_key`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FieldForeignKey {
+    /// Name or id of the referenced space.
+    pub space: NumOrStr,
+    /// Name or 0-based number of the referenced field.
+    pub field: NumOrStr,
+}
+
+impl FieldForeignKey {
+    #[inline(always)]
+    pub fn new(space: impl Into<NumOrStr>, field: impl Into<NumOrStr>) -> Self {
+        Self {
+            space: space.into(),
+            field: field.into(),
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Field
 ////////////////////////////////////////////////////////////////////////////////
@@ -160,6 +210,7 @@ pub struct Field {
     pub name: String, // TODO(gmoshkin): &str
     #[serde(alias = "type")]
     pub field_type: FieldType,
+    #[serde(default)]
     pub is_nullable: bool,
 }
 
@@ -309,6 +360,137 @@ impl IsNullable {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// SpaceFormat
+////////////////////////////////////////////////////////////////////////////////
+
+/// A type which describes the format of a space, whose tuples it represents.
+///
+/// This trait can be derived for structs with named fields. Each struct field
+/// becomes a field in the space format, the field's type and nullability are
+/// deduced from the rust type via [`KnownFieldType`]. Fields of type
+/// `Option<T>` are nullable.
+///
+/// The field names are the same as the ones used by serde, so
+/// `#[serde(rename = "name")]` & `#[serde(rename_all = "...")]` attributes are
+/// taken into account. Fields marked with `#[serde(skip)]` or
+/// `#[serde(skip_serializing)]` are not a part of the format. Serde attributes
+/// which make the encoded tuple differ from the format, like
+/// `#[serde(flatten)]` or `#[serde(skip_serializing_if = "...")]`, are
+/// rejected at compile time.
+///
+/// The following field attributes are supported:
+/// - `#[space_format(field_type = "any")]` - set the field type explicitly,
+///   this is required for types which don't implement [`KnownFieldType`].
+/// - `#[space_format(is_nullable = true)]` - set the field nullability
+///   explicitly.
+/// - `#[space_format(primary_key)]` - add the field to the parts of the
+///   primary key returned by [`SpaceFormat::primary_key`].
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use tarantool::space::{Space, SpaceFormat};
+///
+/// #[derive(Serialize, Deserialize, SpaceFormat)]
+/// struct User {
+///     #[space_format(primary_key)]
+///     id: u64,
+///     name: String,
+///     #[serde(rename = "nickname")]
+///     nick: Option<String>,
+/// }
+///
+/// let space = Space::builder("users")
+///     .format(User::format())
+///     .create()
+///     .unwrap();
+/// space
+///     .index_builder("pk")
+///     .parts(User::primary_key().unwrap())
+///     .create()
+///     .unwrap();
+/// ```
+pub trait SpaceFormat {
+    /// Returns the fields of the space format.
+    fn format() -> Vec<Field>;
+
+    /// Returns the parts of the primary key if any fields were marked with
+    /// `#[space_format(primary_key)]`.
+    #[inline(always)]
+    fn primary_key() -> Option<Vec<crate::index::Part>> {
+        None
+    }
+}
+
+pub use tarantool_proc::SpaceFormat;
+
+/// A rust type which has a corresponding [`FieldType`] in the space format.
+///
+/// This trait is used by `#[derive(SpaceFormat)]` to deduce field types.
+pub trait KnownFieldType {
+    const FIELD_TYPE: FieldType;
+    const IS_NULLABLE: bool = false;
+}
+
+macro_rules! impl_known_field_type {
+    ($($field_type:ident: $($t:ty),+;)+) => {
+        $($(
+            impl KnownFieldType for $t {
+                const FIELD_TYPE: FieldType = FieldType::$field_type;
+            }
+        )+)+
+    }
+}
+
+impl_known_field_type! {
+    Unsigned: u8, u16, u32, u64, usize;
+    Integer: i8, i16, i32, i64, isize;
+    Double: f32, f64;
+    Boolean: bool;
+    String: str, String, char;
+    Varbinary: serde_bytes::ByteBuf, serde_bytes::Bytes;
+    Decimal: crate::decimal::Decimal;
+    Uuid: crate::uuid::Uuid;
+    Datetime: crate::datetime::Datetime;
+    Any: crate::util::Value<'_>, serde_json::Value, rmpv::Value;
+    Map: serde_json::Map<String, serde_json::Value>;
+}
+
+impl<T: KnownFieldType> KnownFieldType for Option<T> {
+    const FIELD_TYPE: FieldType = T::FIELD_TYPE;
+    const IS_NULLABLE: bool = true;
+}
+
+impl<T: KnownFieldType + ?Sized> KnownFieldType for &T {
+    const FIELD_TYPE: FieldType = T::FIELD_TYPE;
+    const IS_NULLABLE: bool = T::IS_NULLABLE;
+}
+
+impl<T: KnownFieldType + ?Sized + ToOwned> KnownFieldType for std::borrow::Cow<'_, T> {
+    const FIELD_TYPE: FieldType = T::FIELD_TYPE;
+    const IS_NULLABLE: bool = T::IS_NULLABLE;
+}
+
+impl<T> KnownFieldType for Vec<T> {
+    const FIELD_TYPE: FieldType = FieldType::Array;
+}
+
+impl<T> KnownFieldType for [T] {
+    const FIELD_TYPE: FieldType = FieldType::Array;
+}
+
+impl<T, const N: usize> KnownFieldType for [T; N] {
+    const FIELD_TYPE: FieldType = FieldType::Array;
+}
+
+impl<K, V, S> KnownFieldType for HashMap<K, V, S> {
+    const FIELD_TYPE: FieldType = FieldType::Map;
+}
+
+impl<K, V> KnownFieldType for std::collections::BTreeMap<K, V> {
+    const FIELD_TYPE: FieldType = FieldType::Map;
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // ...
 ////////////////////////////////////////////////////////////////////////////////
@@ -632,6 +814,81 @@ impl Space {
         self.replace(value)
     }
 
+    /// Insert multiple tuples into a space.
+    ///
+    /// Tuples are inserted one by one, a failure to insert a tuple doesn't
+    /// stop the process. Instead the error is recorded in the returned
+    /// [`BulkReport`] along with the index of the failed tuple.
+    ///
+    /// If [`BulkOptions::chunk_size`] is set, every chunk of that many tuples
+    /// is inserted in a separate transaction, which is much faster than
+    /// committing each tuple separately. In this case the function must not be
+    /// called from within a transaction. If starting or committing a chunk
+    /// transaction fails, a [`BulkError`] is returned and the rest of the
+    /// tuples are not processed. The chunks committed before remain in the
+    /// space, see the [`BulkReport`] in the error.
+    ///
+    /// See also `tests/src/bench_bulk_insert.lua`.
+    #[inline]
+    pub fn insert_many<I>(&self, values: I, options: &BulkOptions) -> Result<BulkReport, BulkError>
+    where
+        I: IntoIterator,
+        I::Item: ToTupleBuffer,
+    {
+        self.write_many(values, options, |space, value| space.insert(value))
+    }
+
+    /// Insert or replace multiple tuples in a space.
+    ///
+    /// Same as [`Space::insert_many`] but uses [`Space::replace`] for every
+    /// tuple.
+    #[inline]
+    pub fn replace_many<I>(&self, values: I, options: &BulkOptions) -> Result<BulkReport, BulkError>
+    where
+        I: IntoIterator,
+        I::Item: ToTupleBuffer,
+    {
+        self.write_many(values, options, |space, value| space.replace(value))
+    }
+
+    fn write_many<I, F>(
+        &self,
+        values: I,
+        options: &BulkOptions,
+        f: F,
+    ) -> Result<BulkReport, BulkError>
+    where
+        I: IntoIterator,
+        F: Fn(&Self, &I::Item) -> Result<Tuple, Error>,
+    {
+        // Without a transaction every tuple is a chunk of its own.
+        let chunk_size = options.chunk_size.map_or(1, |n| n.max(1));
+        let mut report = BulkReport::default();
+        let mut values = values.into_iter().enumerate().peekable();
+        while values.peek().is_some() {
+            if options.chunk_size.is_some() && unsafe { ffi::box_txn_begin() } < 0 {
+                let error = TarantoolError::last().into();
+                return Err(BulkError { error, report });
+            }
+            let mut count = 0;
+            for (i, value) in values.by_ref().take(chunk_size) {
+                match f(self, &value) {
+                    Ok(_) => count += 1,
+                    Err(e) => report.errors.push((i, e)),
+                }
+            }
+            if options.chunk_size.is_some() && unsafe { ffi::box_txn_commit() } < 0 {
+                let error = TarantoolError::last().into();
+                return Err(BulkError { error, report });
+            }
+            report.count += count;
+            if options.yield_between_chunks && values.peek().is_some() {
+                crate::fiber::reschedule();
+            }
+        }
+        Ok(report)
+    }
+
     /// Deletes all tuples. The method is performed in background and doesn’t block consequent requests.
     #[inline(always)]
     pub fn truncate(&self) -> Result<(), Error> {
@@ -837,137 +1094,889 @@ impl Space {
         let tuple = sys_space.get(&(self.id,))?.ok_or(Error::MetaNotFound)?;
         tuple.decode::<SpaceMetadata>()
     }
+
+    /// Return space format from system `_space` space.
+    ///
+    /// The result can be used to validate [`UpdateOps`] before sending them,
+    /// see [`UpdateOps::validate`].
+    #[inline]
+    pub fn format(&self) -> Result<Vec<Field>, Error> {
+        let sys_space: Space = SystemSpace::Space.into();
+        let tuple = sys_space.get(&(self.id,))?.ok_or(Error::MetaNotFound)?;
+        Ok(tuple.field::<Vec<Field>>(6)?.unwrap_or_default())
+    }
+
+    /// Start building an upgrade of the space's tuples, see [`Upgrade`].
+    ///
+    /// - `name` - name of the upgrade, used to resume it after a restart.
+    #[inline(always)]
+    pub fn upgrade(&self, name: impl Into<String>) -> Upgrade<'_> {
+        Upgrade::new(self, name)
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
-// Builder
+// Bulk operations
 ////////////////////////////////////////////////////////////////////////////////
 
-#[allow(dead_code)]
-pub struct Builder<'a> {
-    name: &'a str,
-    opts: SpaceCreateOptions,
+/// Options for [`Space::insert_many`] and [`Space::replace_many`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BulkOptions {
+    /// Commit a transaction every `chunk_size` tuples. If `None`, every tuple
+    /// is committed separately.
+    pub chunk_size: Option<usize>,
+    /// Yield the current fiber between the chunks to let other fibers run.
+    /// Without `chunk_size` the fiber yields after every tuple.
+    pub yield_between_chunks: bool,
 }
 
-macro_rules! define_setters {
-    ($( $setter:ident ( $field:ident : $ty:ty ) )+) => {
-        $(
-            #[inline(always)]
-            pub fn $setter(mut self, $field: $ty) -> Self {
-                self.opts.$field = $field.into();
-                self
-            }
-        )+
+/// Result of [`Space::insert_many`] or [`Space::replace_many`].
+#[derive(Debug, Default)]
+pub struct BulkReport {
+    /// Number of tuples written successfully.
+    pub count: usize,
+    /// Errors for the tuples which failed to be written, along with the
+    /// indexes of the tuples in the input sequence.
+    pub errors: Vec<(usize, Error)>,
+}
+
+impl BulkReport {
+    /// Returns `true` if all of the tuples were written successfully.
+    #[inline(always)]
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
     }
 }
 
-impl<'a> Builder<'a> {
+/// Returned from [`Space::insert_many`] or [`Space::replace_many`] if a chunk
+/// transaction couldn't be started or committed.
+///
+/// The `report` describes the chunks committed before the failure and the
+/// per-tuple errors, including the ones from the failed chunk. Tuples of the
+/// failed chunk are not counted in [`BulkReport::count`].
+#[derive(Debug, thiserror::Error)]
+#[error("bulk write aborted after {} tuples: {error}", .report.count)]
+pub struct BulkError {
+    pub error: Error,
+    pub report: BulkReport,
+}
+
+impl From<BulkError> for Error {
     #[inline(always)]
-    pub fn new(name: &'a str) -> Self {
+    fn from(e: BulkError) -> Self {
+        e.error
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Upgrade
+////////////////////////////////////////////////////////////////////////////////
+
+/// Rewrites all of the tuples in a space with a user provided function, e.g.
+/// after a change of the data layout. Created with [`Space::upgrade`].
+///
+/// Tuples are processed in the primary key order in batches of
+/// [`Upgrade::batch_size`] tuples. Every batch is committed in a separate
+/// transaction and the current fiber yields between the batches, so
+/// upgrading a large space doesn't block the instance. Use [`Upgrade::delay`]
+/// to throttle the upgrade further.
+///
+/// The progress is stored in a dedicated [`Upgrade::PROGRESS_SPACE`] space
+/// (or the one set with [`Upgrade::progress_space`]) along with every batch, so if the instance is restarted in the middle of an
+/// upgrade, running the upgrade with the same name continues from the last
+/// committed batch. The record is removed once the upgrade is finished. For
+/// non-memtx spaces the progress is stored in a separate transaction right
+/// after the batch, so the last batch may be processed twice after a restart
+/// and the function should be idempotent.
+///
+/// The function must not change the primary key of a tuple, otherwise the
+/// batch is rolled back and an error is returned.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tarantool::space::Space;
+///
+/// let space = Space::find("users").unwrap();
+/// space
+///     .upgrade("add_email")
+///     .batch_size(500)
+///     .delay(Duration::from_millis(10))
+///     .on_progress(|p| println!("{}/{}", p.processed, p.total))
+///     .run(|tuple| {
+///         let (id, name): (u64, String) = tuple.decode()?;
+///         Ok(Some((id, name, "")))
+///     })
+///     .unwrap();
+/// ```
+pub struct Upgrade<'a> {
+    space: &'a Space,
+    name: String,
+    batch_size: usize,
+    delay: Duration,
+    on_progress: Option<ProgressCallback<'a>>,
+    progress_space: Option<Space>,
+}
+
+type ProgressCallback<'a> = Box<dyn FnMut(&UpgradeProgress) + 'a>;
+
+/// Progress of an [`Upgrade`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UpgradeProgress {
+    /// Number of tuples processed so far, including the ones processed before
+    /// a restart.
+    pub processed: usize,
+    /// Number of tuples which were actually rewritten.
+    pub updated: usize,
+    /// Number of tuples in the space at the start of the upgrade.
+    pub total: usize,
+}
+
+impl<'a> Upgrade<'a> {
+    const DEFAULT_BATCH_SIZE: usize = 1000;
+
+    /// Name of the memtx space holding the progress of the upgrades which
+    /// are in progress. The space is local to the instance, i.e. it isn't
+    /// replicated, and is created by the first [`Upgrade::run`] unless
+    /// another space is set with [`Upgrade::progress_space`].
+    ///
+    /// Format: `space_id`, `name`, number of processed tuples, number of
+    /// updated tuples and the primary key of the last processed tuple. The
+    /// primary key consists of the first two fields.
+    pub const PROGRESS_SPACE: &'static str = "tarantool_upgrade_progress";
+
+    #[inline(always)]
+    pub fn new(space: &'a Space, name: impl Into<String>) -> Self {
         Self {
-            name,
-            opts: Default::default(),
+            space,
+            name: name.into(),
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            delay: Duration::ZERO,
+            on_progress: None,
+            progress_space: None,
         }
     }
 
-    define_setters! {
-        if_not_exists(if_not_exists: bool)
-        engine(engine: SpaceEngineType)
-        id(id: SpaceId)
-        field_count(field_count: u32)
-        user(user: String)
-        is_local(is_local: bool)
-        is_temporary(is_temporary: bool)
-        is_sync(is_sync: bool)
+    /// Number of tuples processed in a single transaction. Default is 1000.
+    #[inline(always)]
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
     }
 
-    /// Add a field to the space's format.
-    ///
-    /// Use this method to set each field individually or use [`format`] to set
-    /// fields in bulk. The difference is purely syntactical.
-    ///
-    /// [`format`]: Self::format
+    /// Time to sleep between the batches to limit the load on the instance.
+    /// By default the fiber only yields.
     #[inline(always)]
-    pub fn field(mut self, field: impl Into<Field>) -> Self {
-        self.opts
-            .format
-            .get_or_insert_with(|| Vec::with_capacity(16))
-            .push(field.into());
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
         self
     }
 
-    /// Add fields to the space's format.
+    /// Set a callback which is called after every committed batch.
+    #[inline(always)]
+    pub fn on_progress(mut self, f: impl FnMut(&UpgradeProgress) + 'a) -> Self {
+        self.on_progress = Some(Box::new(f));
+        self
+    }
+
+    /// Store the progress in `space` instead of [`Upgrade::PROGRESS_SPACE`],
+    /// so that [`Upgrade::run`] doesn't create one. The space must have the
+    /// same format as [`Upgrade::PROGRESS_SPACE`].
+    #[inline(always)]
+    pub fn progress_space(mut self, space: Space) -> Self {
+        self.progress_space = Some(space);
+        self
+    }
+
+    /// Discard the saved progress of the upgrade, so that the next
+    /// [`Upgrade::run`] starts from the beginning.
+    #[inline]
+    pub fn reset(&self) -> Result<(), Error> {
+        let progress_space = match &self.progress_space {
+            Some(space) => Some(space.clone()),
+            None => Space::find(Self::PROGRESS_SPACE),
+        };
+        if let Some(progress_space) = progress_space {
+            progress_space.delete(&(self.space.id, &self.name))?;
+        }
+        Ok(())
+    }
+
+    /// Run the upgrade. For every tuple `f` should return the new tuple, or
+    /// `None` if the tuple should be left as is.
     ///
-    /// Use this method to set fields in bulk or use [`field`] to set
-    /// each field individually. The difference is purely syntactical.
+    /// If `f` returns an error, the current batch is rolled back and the error
+    /// is returned. The batches committed before are kept and the upgrade
+    /// can be resumed later.
     ///
-    /// ```no_run
-    /// use tarantool::space::{Space, FieldType as FT, IsNullable};
+    /// For memtx spaces `f` must not yield, because a yield aborts the batch
+    /// transaction and the commit fails.
     ///
-    /// let space = Space::builder("user_names")
-    ///     .format([
-    ///         ("id", FT::Unsigned),
-    ///         ("name", FT::String),
-    ///     ])
-    ///     .field(("nickname", FT::String, IsNullable::Nullable))
-    ///     .create();
-    /// ```
+    /// Unless [`Upgrade::progress_space`] is set, the first call creates the
+    /// [`Upgrade::PROGRESS_SPACE`] space, i.e. performs DDL, which fails on a
+    /// read-only instance.
     ///
-    /// [`field`]: Self::field
-    #[inline]
-    pub fn format(mut self, format: impl IntoIterator<Item = impl Into<Field>>) -> Self {
-        let iter = format.into_iter();
-        let (size, _) = iter.size_hint();
-        self.opts
-            .format
-            .get_or_insert_with(|| Vec::with_capacity(size))
-            .extend(iter.map(Into::into));
-        self
-    }
+    /// Must not be called from within a transaction.
+    pub fn run<F, T>(mut self, mut f: F) -> Result<UpgradeProgress, Error>
+    where
+        F: FnMut(&Tuple) -> Result<Option<T>, Error>,
+        T: ToTupleBuffer,
+    {
+        let progress_space = match self.progress_space.take() {
+            Some(space) => space,
+            None => Self::find_or_create_progress_space()?,
+        };
+        let sys_space: Space = SystemSpace::Space.into();
+        let record_key = (self.space.id, &self.name);
+        let engine = sys_space
+            .get(&(self.space.id,))?
+            .ok_or(Error::MetaNotFound)?
+            .field::<SpaceEngineType>(3)?;
+        let same_txn = engine == Some(SpaceEngineType::Memtx);
+        let pk = self.space.primary_key();
+        let key_def = pk.meta()?.to_key_def();
+
+        // Vinyl indexes don't support `len`, counting the tuples is slower, but
+        // works for any engine.
+        let total = if same_txn {
+            self.space.len()?
+        } else {
+            pk.count(IteratorType::All, &())?
+        };
+        let mut progress = UpgradeProgress {
+            total,
+            ..Default::default()
+        };
+        let mut last_key = None;
+        if let Some(record) = progress_space.get(&record_key)? {
+            let (_, _, processed, updated, key): UpgradeRecord = record.decode()?;
+            progress.processed = processed;
+            progress.updated = updated;
+            last_key = Some(TupleBuffer::try_from_vec(key.into_vec())?);
+        }
 
-    #[inline(always)]
-    pub fn create(self) -> crate::Result<Space> {
-        crate::schema::space::create_space(self.name, &self.opts)
+        loop {
+            if unsafe { ffi::box_txn_begin() } < 0 {
+                return Err(TarantoolError::last().into());
+            }
+            let mut updated = 0;
+            // The batch is read within the transaction, so that the writes
+            // made while a vinyl read yields conflict with the batch instead
+            // of being overwritten.
+            let mut process_batch = || -> Result<Option<(usize, TupleBuffer)>, Error> {
+                let batch: Vec<Tuple> = match &last_key {
+                    Some(key) => pk.select(IteratorType::GT, key)?,
+                    None => pk.select(IteratorType::All, &())?,
+                }
+                .take(self.batch_size)
+                .collect();
+                let last = match batch.last() {
+                    Some(last) => last,
+                    None => return Ok(None),
+                };
+                let key = TupleBuffer::from(pk.extract_key(last.clone()));
+                for tuple in &batch {
+                    if let Some(value) = f(tuple)? {
+                        let new = self.space.replace(&value)?;
+                        if key_def.compare(tuple, &new) != std::cmp::Ordering::Equal {
+                            return Err(crate::set_and_get_error!(
+                                crate::error::TarantoolErrorCode::CantUpdatePrimaryKey,
+                                "Attempt to modify a tuple field which is part of primary index in space '{}'",
+                                self.space.id,
+                            )
+                            .into());
+                        }
+                        updated += 1;
+                    }
+                }
+                if same_txn {
+                    let record: UpgradeRecord = (
+                        self.space.id,
+                        self.name.clone(),
+                        progress.processed + batch.len(),
+                        progress.updated + updated,
+                        serde_bytes::ByteBuf::from(key.as_ref()),
+                    );
+                    progress_space.replace(&record)?;
+                }
+                Ok(Some((batch.len(), key)))
+            };
+            let (batch_len, key) = match process_batch() {
+                Ok(Some(batch)) => batch,
+                Ok(None) => {
+                    unsafe { ffi::box_txn_rollback() };
+                    break;
+                }
+                Err(e) => {
+                    unsafe { ffi::box_txn_rollback() };
+                    return Err(e);
+                }
+            };
+            if unsafe { ffi::box_txn_commit() } < 0 {
+                return Err(TarantoolError::last().into());
+            }
+
+            progress.processed += batch_len;
+            progress.updated += updated;
+            if !same_txn {
+                let record: UpgradeRecord = (
+                    self.space.id,
+                    self.name.clone(),
+                    progress.processed,
+                    progress.updated,
+                    serde_bytes::ByteBuf::from(key.as_ref()),
+                );
+                progress_space.replace(&record)?;
+            }
+            if let Some(on_progress) = &mut self.on_progress {
+                on_progress(&progress);
+            }
+            if batch_len < self.batch_size {
+                break;
+            }
+            last_key = Some(key);
+            if self.delay.is_zero() {
+                crate::fiber::reschedule();
+            } else {
+                crate::fiber::sleep(self.delay);
+            }
+        }
+
+        progress_space.delete(&record_key)?;
+        Ok(progress)
     }
 
-    /// Destructure the builder struct into a tuple of name and space options.
-    #[inline(always)]
-    pub fn into_parts(self) -> (&'a str, SpaceCreateOptions) {
-        (self.name, self.opts)
+    /// Find or create the [`Upgrade::PROGRESS_SPACE`] space.
+    fn find_or_create_progress_space() -> Result<Space, Error> {
+        if let Some(space) = Space::find(Self::PROGRESS_SPACE) {
+            return Ok(space);
+        }
+        let space = Space::builder(Self::PROGRESS_SPACE)
+            .if_not_exists(true)
+            .is_local(true)
+            .field(Field::unsigned("space_id"))
+            .field(Field::string("name"))
+            .field(Field::unsigned("processed"))
+            .field(Field::unsigned("updated"))
+            .field(Field::varbinary("last_key"))
+            .create()?;
+        space
+            .index_builder("pk")
+            .if_not_exists(true)
+            .parts(["space_id", "name"])
+            .create()?;
+        Ok(space)
     }
 }
 
+/// Tuple of the [`Upgrade::PROGRESS_SPACE`] holding the progress of an
+/// [`Upgrade`].
+type UpgradeRecord = (SpaceId, String, usize, usize, serde_bytes::ByteBuf);
+
 ////////////////////////////////////////////////////////////////////////////////
-// UpdateOps
+// TypedSpace
 ////////////////////////////////////////////////////////////////////////////////
 
-/// A builder-style helper struct for [`Space::update`], [`Space::upsert`],
-/// [`Index::update`], [`Index::upsert`] methods.
+/// A [`Space`] bound to a row type `T` and a primary key type `K`.
 ///
-/// Start by calling the [`new`] function, then chain as many operations as
-/// needed ([`add`], [`assign`], [`insert`], etc.) after that you can either
-/// pass the resulting expression directly into one of the supported methods,
-/// or use the data directly after calling [`encode`] or [`into_inner`].
+/// Rows are encoded from and decoded into `T` and primary keys are encoded
+/// from `K`, so no manual encoding or decoding is needed at call sites. Use
+/// [`TypedSpace::primary_key`] or [`TypedSpace::index`] to get a
+/// [`TypedIndex`].
 ///
-/// # Examples
 /// ```no_run
-/// use tarantool::space::{Space, UpdateOps};
-/// let mut space = Space::find("employee").unwrap();
-/// space.update(
-///     &[1337],
-///     UpdateOps::new()
-///         .add("strikes", 1).unwrap()
-///         .assign("days-since-last-mistake", 0).unwrap(),
-/// )
-/// .unwrap();
-/// ```
+/// use serde::{Deserialize, Serialize};
+/// use tarantool::space::TypedSpace;
 ///
-/// [`new`]: UpdateOps::new
-/// [`add`]: UpdateOps::add
-/// [`assign`]: UpdateOps::assign
-/// [`insert`]: UpdateOps::insert
-/// [`encode`]: UpdateOps::encode
-/// [`into_inner`]: UpdateOps::into_inner
+/// #[derive(Serialize, Deserialize)]
+/// struct User {
+///     id: u64,
+///     name: String,
+/// }
+/// impl tarantool::tuple::Encode for User {}
+///
+/// let users: TypedSpace<User, (u64,)> = TypedSpace::find("users").unwrap().unwrap();
+/// let user = users.insert(&User { id: 1, name: "Alice".into() }).unwrap();
+/// let user: Option<User> = users.get(&(1,)).unwrap();
+/// ```
+pub struct TypedSpace<T, K> {
+    space: Space,
+    marker: PhantomData<fn(&T, &K) -> T>,
+}
+
+impl<T, K> Clone for TypedSpace<T, K> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            space: self.space.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, K> std::fmt::Debug for TypedSpace<T, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TypedSpace").field(&self.space).finish()
+    }
+}
+
+impl<T, K> TypedSpace<T, K>
+where
+    K: IndexKey,
+{
+    /// Wrap `space`, checking that `K` has all of the parts of the primary
+    /// key.
+    ///
+    /// Returns an error with code [`KeyPartCount`] in case of a mismatch.
+    ///
+    /// [`KeyPartCount`]: crate::error::TarantoolErrorCode::KeyPartCount
+    #[inline]
+    pub fn new(space: Space) -> Result<Self, Error> {
+        let pk = space.primary_key();
+        let meta = pk.meta()?;
+        if K::PARTS != meta.parts.len() {
+            return Err(crate::set_and_get_error!(
+                crate::error::TarantoolErrorCode::KeyPartCount,
+                "Invalid key part count for primary key of space {} (expected {}, got {})",
+                space.id(),
+                meta.parts.len(),
+                K::PARTS,
+            )
+            .into());
+        }
+        Ok(Self::new_unchecked(space))
+    }
+
+    /// Wrap `space` without checking the key, `K` is assumed to be the
+    /// primary key of the space.
+    #[inline(always)]
+    pub fn new_unchecked(space: Space) -> Self {
+        Self {
+            space,
+            marker: PhantomData,
+        }
+    }
+
+    /// Find space by name and check the key, see [`TypedSpace::new`].
+    ///
+    /// Returns `Ok(None)` if the space is not found. See [`Space::find`].
+    #[inline]
+    pub fn find(name: &str) -> Result<Option<Self>, Error> {
+        Space::find(name).map(Self::new).transpose()
+    }
+}
+
+impl<T, K> TypedSpace<T, K> {
+    /// Return the underlying untyped space.
+    #[inline(always)]
+    pub fn as_space(&self) -> &Space {
+        &self.space
+    }
+
+    /// Return the underlying untyped space.
+    #[inline(always)]
+    pub fn into_space(self) -> Space {
+        self.space
+    }
+}
+
+impl<T, K> TypedSpace<T, K>
+where
+    T: ToTupleBuffer + DecodeOwned,
+    K: IndexKey,
+{
+    /// Return the primary index.
+    ///
+    /// Returns an error if `K` has more parts than the index definition, see
+    /// [`TypedIndex::new`].
+    #[inline(always)]
+    pub fn primary_key(&self) -> Result<TypedIndex<T, K>, Error> {
+        TypedIndex::new(self.space.primary_key())
+    }
+
+    /// Find index by name and bind it to key type `I`.
+    ///
+    /// Returns `Ok(None)` if the index is not found or an error if `I` has
+    /// more parts than the index definition, see [`TypedIndex::new`].
+    #[inline]
+    pub fn index<I: IndexKey>(&self, name: &str) -> Result<Option<TypedIndex<T, I>>, Error> {
+        self.space.index(name).map(TypedIndex::new).transpose()
+    }
+
+    /// Insert a row into the space.
+    ///
+    /// Returns the inserted row. See [`Space::insert`].
+    #[inline]
+    pub fn insert(&self, value: &T) -> Result<T, Error> {
+        self.space.insert(value)?.decode()
+    }
+
+    /// Insert a row into the space, replacing the existing one with the same
+    /// primary key.
+    ///
+    /// Returns the new row. See [`Space::replace`].
+    #[inline]
+    pub fn replace(&self, value: &T) -> Result<T, Error> {
+        self.space.replace(value)?.decode()
+    }
+
+    /// Alias for [`TypedSpace::replace`].
+    #[inline(always)]
+    pub fn put(&self, value: &T) -> Result<T, Error> {
+        self.replace(value)
+    }
+
+    /// Insert multiple rows into the space.
+    ///
+    /// See [`Space::insert_many`].
+    #[inline(always)]
+    pub fn insert_many<I>(&self, values: I, options: &BulkOptions) -> Result<BulkReport, BulkError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.space.insert_many(values, options)
+    }
+
+    /// Insert or replace multiple rows in the space.
+    ///
+    /// See [`Space::replace_many`].
+    #[inline(always)]
+    pub fn replace_many<I>(&self, values: I, options: &BulkOptions) -> Result<BulkReport, BulkError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.space.replace_many(values, options)
+    }
+
+    /// Search for a row by the primary key.
+    ///
+    /// See [`Space::get`].
+    #[inline]
+    pub fn get(&self, key: &K) -> Result<Option<T>, Error> {
+        self.space.get(key)?.map(|t| t.decode()).transpose()
+    }
+
+    /// Iterate over the rows matched by `key` in the primary index.
+    ///
+    /// See [`Space::select`].
+    #[inline]
+    pub fn select(
+        &self,
+        iterator_type: IteratorType,
+        key: &K,
+    ) -> Result<crate::index::TypedIndexIterator<T>, Error> {
+        let iter = self.space.select(iterator_type, key)?;
+        Ok(crate::index::TypedIndexIterator::new(iter))
+    }
+
+    /// Delete a row identified by the primary key.
+    ///
+    /// Returns the deleted row. See [`Space::delete`].
+    #[inline]
+    pub fn delete(&self, key: &K) -> Result<Option<T>, Error> {
+        self.space.delete(key)?.map(|t| t.decode()).transpose()
+    }
+
+    /// Update a row identified by the primary key.
+    ///
+    /// Returns the updated row. See [`Space::update`].
+    #[inline]
+    pub fn update<Op>(&self, key: &K, ops: impl AsRef<[Op]>) -> Result<Option<T>, Error>
+    where
+        Op: ToTupleBuffer,
+    {
+        self.space.update(key, ops)?.map(|t| t.decode()).transpose()
+    }
+
+    /// Update or insert a row.
+    ///
+    /// See [`Space::upsert`].
+    #[inline(always)]
+    pub fn upsert<Op>(&self, value: &T, ops: impl AsRef<[Op]>) -> Result<(), Error>
+    where
+        Op: ToTupleBuffer,
+    {
+        self.space.upsert(value, ops)
+    }
+
+    /// Return the number of rows in the space.
+    #[inline(always)]
+    pub fn len(&self) -> Result<usize, Error> {
+        self.space.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        self.space.is_empty()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Builder
+////////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+pub struct Builder<'a> {
+    name: &'a str,
+    opts: SpaceCreateOptions,
+    constraints: Constraints,
+}
+
+/// Constraints & foreign keys of a space, which are set with the [`Builder`]
+/// methods, because [`SpaceCreateOptions`] & [`Field`] don't have them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Constraints {
+    /// Constraint name -> constraint function name or id.
+    pub tuple: BTreeMap<String, NumOrStr>,
+    /// Foreign key name -> foreign key definition.
+    pub tuple_foreign_key: BTreeMap<String, ForeignKey>,
+    /// Field name -> constraint name -> constraint function name or id.
+    pub field: BTreeMap<String, BTreeMap<String, NumOrStr>>,
+    /// Field name -> foreign key name -> foreign key definition.
+    pub field_foreign_key: BTreeMap<String, BTreeMap<String, FieldForeignKey>>,
+}
+
+impl Constraints {
+    /// Returns `true` if no constraints or foreign keys are set.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tuple.is_empty()
+            && self.tuple_foreign_key.is_empty()
+            && self.field.is_empty()
+            && self.field_foreign_key.is_empty()
+    }
+}
+
+macro_rules! define_setters {
+    ($( $setter:ident ( $field:ident : $ty:ty ) )+) => {
+        $(
+            #[inline(always)]
+            pub fn $setter(mut self, $field: $ty) -> Self {
+                self.opts.$field = $field.into();
+                self
+            }
+        )+
+    }
+}
+
+impl<'a> Builder<'a> {
+    #[inline(always)]
+    pub fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            opts: Default::default(),
+            constraints: Default::default(),
+        }
+    }
+
+    define_setters! {
+        if_not_exists(if_not_exists: bool)
+        engine(engine: SpaceEngineType)
+        id(id: SpaceId)
+        field_count(field_count: u32)
+        user(user: String)
+        is_local(is_local: bool)
+        is_temporary(is_temporary: bool)
+        is_sync(is_sync: bool)
+    }
+
+    /// Add a tuple constraint named `name`. `func` is the name or id of a
+    /// function registered in `_func`, which is called with the tuple and the
+    /// constraint name and must return `true` for valid tuples. See
+    /// [`Builder::field_constraint`] for details.
+    ///
+    /// Only for Tarantool >= 2.10.
+    #[inline]
+    pub fn constraint(mut self, name: impl Into<String>, func: impl Into<NumOrStr>) -> Self {
+        self.constraints.tuple.insert(name.into(), func.into());
+        self
+    }
+
+    /// Add a constraint named `name` to the field named `field`. `func` is the
+    /// name or id of a function registered in `_func`, which is called with
+    /// the field value and the constraint name and must return `true` for
+    /// valid values. The function must be a deterministic lua function with a
+    /// body, see [`schema::function::create_function`].
+    ///
+    /// ```no_run
+    /// use tarantool::schema::function::{create_function, FuncOptions};
+    /// use tarantool::space::{Field, Space};
+    ///
+    /// create_function("check_positive", &FuncOptions {
+    ///     body: Some("function(value) return value > 0 end".into()),
+    ///     is_deterministic: Some(true),
+    ///     ..Default::default()
+    /// }).unwrap();
+    ///
+    /// let space = Space::builder("accounts")
+    ///     .field(Field::unsigned("id"))
+    ///     .field(Field::integer("balance"))
+    ///     .field_constraint("balance", "positive", "check_positive")
+    ///     .create();
+    /// ```
+    ///
+    /// Only for Tarantool >= 2.10.
+    ///
+    /// [`schema::function::create_function`]: crate::schema::function::create_function
+    #[inline]
+    pub fn field_constraint(
+        mut self,
+        field: impl Into<String>,
+        name: impl Into<String>,
+        func: impl Into<NumOrStr>,
+    ) -> Self {
+        self.constraints
+            .field
+            .entry(field.into())
+            .or_default()
+            .insert(name.into(), func.into());
+        self
+    }
+
+    /// Add a tuple foreign key named `name`, which requires a tuple with the
+    /// same values of the specified fields to be present in another space.
+    ///
+    /// ```no_run
+    /// use tarantool::space::{FieldType as FT, ForeignKey, Space};
+    ///
+    /// let space = Space::builder("orders")
+    ///     .format([("id", FT::Unsigned), ("customer_id", FT::Unsigned), ("region", FT::String)])
+    ///     .foreign_key(
+    ///         "customer",
+    ///         ForeignKey::new("customers", [("customer_id", "id"), ("region", "region")]),
+    ///     )
+    ///     .create();
+    /// ```
+    ///
+    /// Only for Tarantool >= 2.10.
+    #[inline]
+    pub fn foreign_key(mut self, name: impl Into<String>, foreign_key: ForeignKey) -> Self {
+        self.constraints
+            .tuple_foreign_key
+            .insert(name.into(), foreign_key);
+        self
+    }
+
+    /// Add a foreign key named `name` to the field named `field`, which
+    /// requires the field value to be present in the field of another space.
+    ///
+    /// Only for Tarantool >= 2.10.
+    #[inline]
+    pub fn field_foreign_key(
+        mut self,
+        field: impl Into<String>,
+        name: impl Into<String>,
+        foreign_key: FieldForeignKey,
+    ) -> Self {
+        self.constraints
+            .field_foreign_key
+            .entry(field.into())
+            .or_default()
+            .insert(name.into(), foreign_key);
+        self
+    }
+
+    /// Add a field to the space's format.
+    ///
+    /// Use this method to set each field individually or use [`format`] to set
+    /// fields in bulk. The difference is purely syntactical.
+    ///
+    /// [`format`]: Self::format
+    #[inline(always)]
+    pub fn field(mut self, field: impl Into<Field>) -> Self {
+        self.opts
+            .format
+            .get_or_insert_with(|| Vec::with_capacity(16))
+            .push(field.into());
+        self
+    }
+
+    /// Add fields to the space's format.
+    ///
+    /// Use this method to set fields in bulk or use [`field`] to set
+    /// each field individually. The difference is purely syntactical.
+    ///
+    /// ```no_run
+    /// use tarantool::space::{Space, FieldType as FT, IsNullable};
+    ///
+    /// let space = Space::builder("user_names")
+    ///     .format([
+    ///         ("id", FT::Unsigned),
+    ///         ("name", FT::String),
+    ///     ])
+    ///     .field(("nickname", FT::String, IsNullable::Nullable))
+    ///     .create();
+    /// ```
+    ///
+    /// [`field`]: Self::field
+    #[inline]
+    pub fn format(mut self, format: impl IntoIterator<Item = impl Into<Field>>) -> Self {
+        let iter = format.into_iter();
+        let (size, _) = iter.size_hint();
+        self.opts
+            .format
+            .get_or_insert_with(|| Vec::with_capacity(size))
+            .extend(iter.map(Into::into));
+        self
+    }
+
+    #[inline(always)]
+    pub fn create(self) -> crate::Result<Space> {
+        crate::schema::space::create_space_with_constraints(
+            self.name,
+            &self.opts,
+            &self.constraints,
+        )
+    }
+
+    /// Destructure the builder struct into a tuple of name and space options.
+    ///
+    /// Constraints & foreign keys aren't a part of [`SpaceCreateOptions`], so
+    /// they are dropped. Use [`Builder::into_parts_with_constraints`] to keep
+    /// them.
+    #[inline(always)]
+    pub fn into_parts(self) -> (&'a str, SpaceCreateOptions) {
+        (self.name, self.opts)
+    }
+
+    /// Destructure the builder struct into a tuple of name, space options and
+    /// constraints & foreign keys.
+    #[inline(always)]
+    pub fn into_parts_with_constraints(self) -> (&'a str, SpaceCreateOptions, Constraints) {
+        (self.name, self.opts, self.constraints)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// UpdateOps
+////////////////////////////////////////////////////////////////////////////////
+
+/// A builder-style helper struct for [`Space::update`], [`Space::upsert`],
+/// [`Index::update`], [`Index::upsert`] methods.
+///
+/// Start by calling the [`new`] function, then chain as many operations as
+/// needed ([`add`], [`assign`], [`insert`], etc.) after that you can either
+/// pass the resulting expression directly into one of the supported methods,
+/// or use the data directly after calling [`encode`] or [`into_inner`].
+///
+/// # Examples
+/// ```no_run
+/// use tarantool::space::{Space, UpdateOps};
+/// let mut space = Space::find("employee").unwrap();
+/// space.update(
+///     &[1337],
+///     UpdateOps::new()
+///         .add("strikes", 1).unwrap()
+///         .assign("days-since-last-mistake", 0).unwrap(),
+/// )
+/// .unwrap();
+/// ```
+///
+/// [`new`]: UpdateOps::new
+/// [`add`]: UpdateOps::add
+/// [`assign`]: UpdateOps::assign
+/// [`insert`]: UpdateOps::insert
+/// [`encode`]: UpdateOps::encode
+/// [`into_inner`]: UpdateOps::into_inner
 pub struct UpdateOps {
     ops: Vec<TupleBuffer>,
 }
@@ -1113,6 +2122,167 @@ impl UpdateOps {
         }
         Ok(())
     }
+
+    /// Check the operations against the space `format` before sending them.
+    ///
+    /// The following is checked for each operation:
+    /// - fields specified by name or by a path (see [`FieldPath`]) exist in
+    ///   the format;
+    /// - paths only go into fields of types `map`, `array` or `any`;
+    /// - arithmetic, bitwise and splice operations are applied to fields of
+    ///   compatible types;
+    /// - `nil` is not assigned to non-nullable fields.
+    ///
+    /// Fields specified by number which are not described in the format are
+    /// not checked, neither are negative field numbers, which are counted
+    /// from the end of the tuple. A string is looked up as a field name
+    /// before it's parsed as a path, the same way tarantool does it.
+    ///
+    /// Use [`Space::format`] or [`RemoteSpace::format`] to get the format.
+    ///
+    /// [`RemoteSpace::format`]: crate::net_box::RemoteSpace::format
+    pub fn validate(&self, format: &[Field]) -> crate::Result<()> {
+        for op in &self.ops {
+            validate_update_op(op.as_ref(), format)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_update_op(data: &[u8], format: &[Field]) -> crate::Result<()> {
+    use crate::error::TarantoolErrorCode;
+    use rmpv::Value as MpValue;
+
+    let args: Vec<MpValue> = rmp_serde::from_slice(data)?;
+    if args.len() < 2 {
+        return Err(update_error(&"?", &"invalid operation format").into());
+    }
+    let op_code = args[0].as_str().unwrap_or_default();
+
+    // Resolve the top level field and check if there's a nested path.
+    let (field_desc, field_no, has_path) = match &args[1] {
+        MpValue::Integer(i) => {
+            // Negative numbers are counted from the end of the tuple, which
+            // may be longer than the format, so they can't be checked.
+            let field_no = i.as_u64().and_then(|i| usize::try_from(i).ok());
+            (i.to_string(), field_no, false)
+        }
+        MpValue::String(path) => {
+            let path = path.as_str().unwrap_or_default();
+            // Tarantool looks up the exact field name before parsing the path.
+            if let Some(field_no) = format.iter().position(|f| f.name == path) {
+                return check_update_op(
+                    &args,
+                    op_code,
+                    &format!("'{}'", path),
+                    &format[field_no],
+                    false,
+                );
+            }
+            let mut components = FieldPath::parse(path)
+                .map_err(|e| update_error(&format_args!("'{}'", path), &e))?;
+            let field_no = match components.remove(0) {
+                PathComponent::Key(name) => {
+                    let field_no = format.iter().position(|f| f.name == name);
+                    if field_no.is_none() {
+                        return Err(crate::set_and_get_error!(
+                            TarantoolErrorCode::NoSuchFieldNameInSpace,
+                            "Field '{}' was not found in the space format",
+                            name,
+                        )
+                        .into());
+                    }
+                    field_no
+                }
+                PathComponent::Index(n) => (n as usize).checked_sub(1),
+                PathComponent::Any => None,
+            };
+            if components.contains(&PathComponent::Any) {
+                let msg = "'[*]' is only allowed in multikey index parts";
+                return Err(update_error(&format_args!("'{}'", path), &msg).into());
+            }
+            (format!("'{}'", path), field_no, !components.is_empty())
+        }
+        _ => return Err(update_error(&"?", &"field must be a number or a string").into()),
+    };
+
+    let field = match field_no.and_then(|i| format.get(i)) {
+        Some(field) => field,
+        None => return Ok(()),
+    };
+    check_update_op(&args, op_code, &field_desc, field, has_path)
+}
+
+fn update_error(field: &dyn std::fmt::Display, msg: &dyn std::fmt::Display) -> TarantoolError {
+    crate::set_and_get_error!(
+        crate::error::TarantoolErrorCode::UpdateField,
+        "Field {} UPDATE error: {}",
+        field,
+        msg
+    )
+}
+
+fn check_update_op(
+    args: &[rmpv::Value],
+    op_code: &str,
+    field_desc: &str,
+    field: &Field,
+    has_path: bool,
+) -> crate::Result<()> {
+    use rmpv::Value as MpValue;
+
+    if has_path {
+        if !matches!(
+            field.field_type,
+            FieldType::Map | FieldType::Array | FieldType::Any
+        ) {
+            let msg = format!("can't use a path into field of type '{}'", field.field_type);
+            return Err(update_error(&field_desc, &msg).into());
+        }
+        return Ok(());
+    }
+
+    let compatible = match op_code {
+        "+" | "-" => matches!(
+            field.field_type,
+            FieldType::Unsigned
+                | FieldType::Integer
+                | FieldType::Number
+                | FieldType::Double
+                | FieldType::Decimal
+                | FieldType::Scalar
+                | FieldType::Any
+        ),
+        "&" | "|" | "^" => matches!(
+            field.field_type,
+            FieldType::Unsigned
+                | FieldType::Integer
+                | FieldType::Number
+                | FieldType::Scalar
+                | FieldType::Any
+        ),
+        ":" => matches!(
+            field.field_type,
+            FieldType::String | FieldType::Scalar | FieldType::Any
+        ),
+        "=" | "!" => {
+            if !field.is_nullable && args.get(2).map_or(false, MpValue::is_nil) {
+                let msg = "can't assign nil to a non-nullable field";
+                return Err(update_error(&field_desc, &msg).into());
+            }
+            true
+        }
+        _ => true,
+    };
+    if !compatible {
+        let msg = format!(
+            "operation '{}' is not applicable to field of type '{}'",
+            op_code, field.field_type
+        );
+        return Err(update_error(&field_desc, &msg).into());
+    }
+
+    Ok(())
 }
 
 impl Default for UpdateOps {
@@ -1146,6 +2316,236 @@ impl IntoIterator for UpdateOps {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////
+// FieldPath
+////////////////////////////////////////////////////////////////////////////////
+
+/// A builder for JSON paths to nested fields of a tuple, which can be used as
+/// the field argument in [`UpdateOps`] operations and in index parts (see
+/// [`index::Part`]).
+///
+/// The path starts with a top level field specified either by name
+/// ([`FieldPath::field`]) or by number ([`FieldPath::index`]). Nested map keys
+/// and array elements are added with [`FieldPath::key`] and
+/// [`FieldPath::at`].
+///
+/// **NOTE**: as in the rest of tarantool's JSON paths, array indexes in paths
+/// are 1-based, unlike the numeric field arguments of [`UpdateOps`] methods.
+///
+/// ```no_run
+/// use tarantool::space::{FieldPath, UpdateOps};
+///
+/// let path = FieldPath::index(3).key("address").key("city");
+/// assert_eq!(path.as_str(), "[3].address.city");
+///
+/// let path = FieldPath::field("emails").at(1);
+/// assert_eq!(path.as_str(), "emails[1]");
+///
+/// let path = FieldPath::field("user").key("first name");
+/// assert_eq!(path.as_str(), r#"user["first name"]"#);
+///
+/// let mut ops = UpdateOps::new();
+/// ops.assign(FieldPath::field("address").key("city"), "Moscow").unwrap();
+///
+/// // Multikey index part: index every element of the `tags` array.
+/// let path = FieldPath::field("tags").any().key("name");
+/// assert_eq!(path.as_str(), "tags[*].name");
+/// ```
+///
+/// [`index::Part`]: crate::index::Part
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FieldPath(String);
+
+impl FieldPath {
+    /// Start a path with a top level field specified by `name`.
+    #[inline]
+    pub fn field(name: &str) -> Self {
+        let mut path = String::with_capacity(name.len() + 8);
+        if is_path_identifier(name) {
+            path.push_str(name);
+        } else {
+            push_quoted_key(&mut path, name);
+        }
+        Self(path)
+    }
+
+    /// Start a path with a top level field specified by 1-based `index`.
+    #[inline]
+    pub fn index(index: u32) -> Self {
+        let mut path = String::with_capacity(16);
+        push_index(&mut path, index);
+        Self(path)
+    }
+
+    /// Add a map `key` to the path.
+    #[inline]
+    pub fn key(mut self, key: &str) -> Self {
+        if is_path_identifier(key) {
+            self.0.push('.');
+            self.0.push_str(key);
+        } else {
+            push_quoted_key(&mut self.0, key);
+        }
+        self
+    }
+
+    /// Add a 1-based array `index` to the path.
+    #[inline]
+    pub fn at(mut self, index: u32) -> Self {
+        push_index(&mut self.0, index);
+        self
+    }
+
+    /// Add `[*]` to the path, which matches any element of an array.
+    ///
+    /// Such paths can only be used in parts of multikey indexes, see
+    /// [multikey indexes](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_space/create_index/#creating-a-multikey-index-using-an-array-path).
+    #[inline]
+    pub fn any(mut self) -> Self {
+        self.0.push_str("[*]");
+        self
+    }
+
+    #[inline(always)]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[inline(always)]
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Split a path into components. The first component is always the top
+    /// level field.
+    pub(crate) fn parse(path: &str) -> Result<Vec<PathComponent>, String> {
+        let mut res = Vec::new();
+        let mut rest = path;
+        let mut first = true;
+        while !rest.is_empty() {
+            let c = rest.as_bytes()[0];
+            if c == b'[' {
+                let end = rest
+                    .find(']')
+                    .ok_or_else(|| "unterminated '['".to_string())?;
+                let inner = &rest[1..end];
+                let quote = inner.as_bytes().first().copied();
+                if quote == Some(b'"') || quote == Some(b'\'') {
+                    // Quoted keys may contain ']', so look for the closing
+                    // quote instead.
+                    let body = &rest[2..];
+                    let close = body
+                        .find(quote.unwrap() as char)
+                        .ok_or_else(|| "unterminated string".to_string())?;
+                    let key = &body[..close];
+                    let after = &body[close + 1..];
+                    if !after.starts_with(']') {
+                        return Err("expected ']' after string".into());
+                    }
+                    res.push(PathComponent::Key(key.into()));
+                    rest = &after[1..];
+                } else if inner.trim() == "*" {
+                    res.push(PathComponent::Any);
+                    rest = &rest[end + 1..];
+                } else {
+                    let index = inner
+                        .trim()
+                        .parse::<u32>()
+                        .map_err(|_| format!("invalid array index '{}'", inner))?;
+                    if index == 0 {
+                        return Err("array indexes are 1-based".into());
+                    }
+                    res.push(PathComponent::Index(index));
+                    rest = &rest[end + 1..];
+                }
+            } else {
+                if c == b'.' {
+                    if first {
+                        return Err("path can't start with '.'".into());
+                    }
+                    rest = &rest[1..];
+                } else if !first {
+                    return Err(format!("unexpected character '{}'", c as char));
+                }
+                let end = rest.find(['.', '[']).unwrap_or(rest.len());
+                let key = &rest[..end];
+                if key.is_empty() {
+                    return Err("empty key".into());
+                }
+                res.push(PathComponent::Key(key.into()));
+                rest = &rest[end..];
+            }
+            first = false;
+        }
+        if res.is_empty() {
+            return Err("empty path".into());
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PathComponent {
+    Key(String),
+    Index(u32),
+    /// `[*]`, any element of an array.
+    Any,
+}
+
+#[inline]
+fn is_path_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[inline]
+fn push_quoted_key(path: &mut String, key: &str) {
+    let quote = if key.contains('"') { '\'' } else { '"' };
+    path.push('[');
+    path.push(quote);
+    path.push_str(key);
+    path.push(quote);
+    path.push(']');
+}
+
+#[inline]
+fn push_index(path: &mut String, index: u32) {
+    use std::fmt::Write;
+    write!(path, "[{}]", index).expect("writing to a string can't fail");
+}
+
+impl std::fmt::Display for FieldPath {
+    #[inline(always)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for FieldPath {
+    #[inline(always)]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<FieldPath> for String {
+    #[inline(always)]
+    fn from(path: FieldPath) -> Self {
+        path.0
+    }
+}
+
+impl Serialize for FieldPath {
+    #[inline(always)]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // macros
 ////////////////////////////////////////////////////////////////////////////////
@@ -1240,4 +2640,707 @@ mod test {
         assert_eq!(t, (42, "foo".to_owned(), "bar".to_owned()));
         space.drop().unwrap();
     }
+
+    #[crate::test(tarantool = "crate")]
+    fn derive_space_format() {
+        #[derive(Serialize, Deserialize, SpaceFormat, Debug, PartialEq)]
+        #[space_format(tarantool = "crate")]
+        struct Row {
+            #[space_format(primary_key)]
+            id: u32,
+            #[space_format(primary_key)]
+            #[serde(rename = "kind")]
+            r#type: String,
+            score: Option<f64>,
+            #[space_format(field_type = "any")]
+            payload: (i32, bool),
+            tags: Vec<String>,
+        }
+        impl Encode for Row {}
+
+        assert_eq!(
+            Row::format(),
+            vec![
+                Field::unsigned("id"),
+                Field::string("kind"),
+                Field::double("score").is_nullable(true),
+                Field::any("payload"),
+                Field::array("tags"),
+            ]
+        );
+        assert_eq!(
+            Row::primary_key(),
+            Some(vec![
+                crate::index::Part::from("id"),
+                crate::index::Part::from("kind"),
+            ])
+        );
+
+        #[derive(Serialize, SpaceFormat)]
+        #[space_format(tarantool = "crate")]
+        #[serde(rename_all = "camelCase")]
+        struct Renamed {
+            user_id: u32,
+            #[serde(rename = "Name")]
+            full_name: String,
+            #[serde(skip)]
+            #[allow(dead_code)]
+            cache: Vec<u8>,
+        }
+        assert_eq!(
+            Renamed::format(),
+            vec![Field::unsigned("userId"), Field::string("Name")]
+        );
+
+        let space_name = crate::temp_space_name!();
+        let space = Space::builder(&space_name)
+            .format(Row::format())
+            .create()
+            .unwrap();
+        space
+            .index_builder("pk")
+            .parts(Row::primary_key().unwrap())
+            .create()
+            .unwrap();
+        let row = Row {
+            id: 1,
+            r#type: "foo".into(),
+            score: None,
+            payload: (-1, true),
+            tags: vec!["bar".into()],
+        };
+        space.insert(&row).unwrap();
+        let t = space.get(&(1, "foo")).unwrap().unwrap();
+        assert_eq!(t.decode::<Row>().unwrap(), row);
+        space.drop().unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn field_path() {
+        let path = FieldPath::index(3).key("address").key("city");
+        assert_eq!(path.as_str(), "[3].address.city");
+        let path = FieldPath::field("emails").at(2).key("домен");
+        assert_eq!(path.as_str(), "emails[2].домен");
+        let path = FieldPath::field("first name").key("x.y").key(r#"say "hi""#);
+        assert_eq!(path.as_str(), r#"["first name"]["x.y"]['say "hi"']"#);
+
+        assert_eq!(
+            FieldPath::parse(path.as_str()).unwrap(),
+            [
+                PathComponent::Key("first name".into()),
+                PathComponent::Key("x.y".into()),
+                PathComponent::Key(r#"say "hi""#.into()),
+            ]
+        );
+        assert_eq!(
+            FieldPath::parse("[3].address[1]").unwrap(),
+            [
+                PathComponent::Index(3),
+                PathComponent::Key("address".into()),
+                PathComponent::Index(1),
+            ]
+        );
+        assert!(FieldPath::parse("").is_err());
+        assert!(FieldPath::parse(".a").is_err());
+        assert!(FieldPath::parse("a..b").is_err());
+        assert!(FieldPath::parse("a[0]").is_err());
+        assert!(FieldPath::parse("a[1").is_err());
+        assert!(FieldPath::parse("a[x]").is_err());
+
+        let path = FieldPath::field("tags").any().key("name");
+        assert_eq!(path.as_str(), "tags[*].name");
+        assert_eq!(
+            FieldPath::parse(path.as_str()).unwrap(),
+            [
+                PathComponent::Key("tags".into()),
+                PathComponent::Any,
+                PathComponent::Key("name".into()),
+            ]
+        );
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn update_ops_validate_and_paths() {
+        let space_name = crate::temp_space_name!();
+        let space = Space::builder(&space_name)
+            .field(("id", FieldType::Unsigned))
+            .field(("name", FieldType::String))
+            .field(("info", FieldType::Map))
+            .field(("score", FieldType::Integer, IsNullable::Nullable))
+            .create()
+            .unwrap();
+        space.index_builder("pk").create().unwrap();
+        let format = space.format().unwrap();
+        assert_eq!(
+            format,
+            [
+                Field::unsigned("id"),
+                Field::string("name"),
+                Field::map("info"),
+                Field::integer("score").is_nullable(true),
+            ]
+        );
+
+        #[derive(Serialize)]
+        struct Address<'a> {
+            city: &'a str,
+            zip: u32,
+        }
+        #[derive(Serialize)]
+        struct Info<'a> {
+            address: Address<'a>,
+            tags: [&'a str; 2],
+        }
+        let info = Info {
+            address: Address {
+                city: "Moscow",
+                zip: 101000,
+            },
+            tags: ["a", "b"],
+        };
+        space.insert(&(1, "foo", info, 10)).unwrap();
+
+        let mut ops = UpdateOps::new();
+        ops.assign(FieldPath::field("info").key("address").key("city"), "Kazan")
+            .unwrap()
+            .add(FieldPath::index(3).key("address").key("zip"), 1)
+            .unwrap()
+            .assign(FieldPath::field("info").key("tags").at(2), "c")
+            .unwrap()
+            .splice("name", 0, 1, "b")
+            .unwrap()
+            .sub(3, 1)
+            .unwrap()
+            .assign("score", ())
+            .unwrap();
+        ops.validate(&format).unwrap();
+        let t = space.update(&(1,), &ops).unwrap().unwrap();
+        let (_, name, info, score): (u32, String, rmpv::Value, Option<i64>) = t.decode().unwrap();
+        assert_eq!(name, "boo");
+        assert_eq!(score, None);
+        let info = rmpv::ext::from_value::<serde_json::Value>(info).unwrap();
+        assert_eq!(
+            info,
+            serde_json::json!({
+                "address": { "city": "Kazan", "zip": 101001 },
+                "tags": ["a", "c"],
+            })
+        );
+
+        let validate = |f: &dyn Fn(&mut UpdateOps) -> crate::Result<&mut UpdateOps>| {
+            let mut ops = UpdateOps::new();
+            f(&mut ops).unwrap();
+            ops.validate(&format)
+        };
+        // No such field.
+        assert!(validate(&|ops| ops.assign("nope", 1)).is_err());
+        assert!(validate(&|ops| ops.assign(FieldPath::field("nope").key("x"), 1)).is_err());
+        // Path into a scalar field.
+        assert!(validate(&|ops| ops.assign(FieldPath::field("name").key("x"), 1)).is_err());
+        assert!(validate(&|ops| ops.assign(FieldPath::index(1).at(1), 1)).is_err());
+        // Incompatible operations.
+        assert!(validate(&|ops| ops.add("name", 1)).is_err());
+        assert!(validate(&|ops| ops.or(1, 1)).is_err());
+        assert!(validate(&|ops| ops.splice("id", 0, 1, "x")).is_err());
+        // Multikey paths.
+        assert!(validate(&|ops| ops.assign(FieldPath::field("info").any(), 1)).is_err());
+        // Nil in a non-nullable field.
+        assert!(validate(&|ops| ops.assign("name", ())).is_err());
+        // Fields outside of format are not checked.
+        assert!(validate(&|ops| ops.add(10, 1)).is_ok());
+        assert!(validate(&|ops| ops.assign(FieldPath::index(10).key("x"), 1)).is_ok());
+        // Negative numbers are counted from the end of the tuple, which may be
+        // longer than the format.
+        assert!(validate(&|ops| ops.splice(-1, 0, 1, "x")).is_ok());
+
+        // Exact field names are looked up before the paths.
+        let format = vec![Field::string("a.b"), Field::map("a")];
+        let mut ops = UpdateOps::new();
+        ops.add("a.b", 1).unwrap();
+        assert!(ops.validate(&format).is_err());
+        let mut ops = UpdateOps::new();
+        ops.assign("a.c", 1).unwrap();
+        assert!(ops.validate(&format).is_ok());
+
+        space.drop().unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn typed_space_and_index() {
+        use crate::index::TypedIndex;
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+        struct Row {
+            id: u32,
+            name: String,
+            score: i64,
+        }
+        impl Encode for Row {}
+
+        let space_name = crate::temp_space_name!();
+        let space = Space::builder(&space_name)
+            .field(("id", FieldType::Unsigned))
+            .field(("name", FieldType::String))
+            .field(("score", FieldType::Integer))
+            .create()
+            .unwrap();
+        space.index_builder("pk").create().unwrap();
+        space
+            .index_builder("by_name_score")
+            .unique(false)
+            .parts(["name", "score"])
+            .create()
+            .unwrap();
+
+        let rows: TypedSpace<Row, (u32,)> = TypedSpace::new(space.clone()).unwrap();
+        let row = |id, name: &str, score| Row {
+            id,
+            name: name.into(),
+            score,
+        };
+        assert_eq!(rows.insert(&row(1, "a", 10)).unwrap(), row(1, "a", 10));
+        rows.insert(&row(2, "b", 20)).unwrap();
+        rows.insert(&row(3, "a", 30)).unwrap();
+        assert_eq!(rows.replace(&row(3, "a", 5)).unwrap(), row(3, "a", 5));
+        assert_eq!(rows.len().unwrap(), 3);
+
+        assert_eq!(rows.get(&(2,)).unwrap(), Some(row(2, "b", 20)));
+        assert_eq!(rows.get(&(4,)).unwrap(), None);
+
+        let pk = rows.primary_key().unwrap();
+        assert_eq!(pk.get(&(1,)).unwrap(), Some(row(1, "a", 10)));
+        let all: Vec<_> = pk.all().unwrap().collect::<Result<_, _>>().unwrap();
+        assert_eq!(all, [row(1, "a", 10), row(2, "b", 20), row(3, "a", 5)]);
+
+        // Key arity is checked against the index definition.
+        assert!(TypedSpace::<Row, (u32, String)>::new(space.clone()).is_err());
+        assert!(TypedSpace::<Row, (u32,)>::find(&space_name)
+            .unwrap()
+            .is_some());
+        assert!(TypedSpace::<Row, (u32,)>::find("no_such_space")
+            .unwrap()
+            .is_none());
+        assert!(TypedIndex::<Row, (u32, String)>::new(space.primary_key()).is_err());
+        assert!(rows.index::<(String, i64, u32)>("by_name_score").is_err());
+        assert!(rows
+            .index::<(String, i64)>("no_such_index")
+            .unwrap()
+            .is_none());
+
+        let by_name = rows
+            .index::<(String, i64)>("by_name_score")
+            .unwrap()
+            .unwrap();
+        let found: Vec<_> = by_name
+            .select(IteratorType::GE, &("a".into(), 0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(found, [row(3, "a", 5), row(1, "a", 10), row(2, "b", 20)]);
+
+        // Prefix keys can be used for selects, but not for exact matches.
+        let by_name = rows.index::<(String,)>("by_name_score").unwrap().unwrap();
+        assert!(!by_name.is_full_key());
+        let found: Vec<_> = by_name
+            .select(IteratorType::Eq, &("a".into(),))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(found, [row(3, "a", 5), row(1, "a", 10)]);
+        assert_eq!(by_name.count(IteratorType::Eq, &("b".into(),)).unwrap(), 1);
+        assert!(by_name.delete(&("a".into(),)).is_err());
+        assert_eq!(rows.len().unwrap(), 3);
+
+        assert_eq!(
+            pk.update(&(2,), [("=", "score", 25)]).unwrap(),
+            Some(row(2, "b", 25))
+        );
+        assert_eq!(pk.delete(&(2,)).unwrap(), Some(row(2, "b", 25)));
+        assert_eq!(rows.delete(&(2,)).unwrap(), None);
+
+        space.drop().unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn insert_many() {
+        let space_name = crate::temp_space_name!();
+        let space = Space::builder(&space_name)
+            .field(("id", FieldType::Unsigned))
+            .field(("value", FieldType::String))
+            .create()
+            .unwrap();
+        space.index_builder("pk").create().unwrap();
+        space.insert(&(3, "x")).unwrap();
+
+        // Without chunks every tuple is committed separately.
+        let rows = (1..=5).map(|i| (i, format!("v{}", i)));
+        let report = space.insert_many(rows, &BulkOptions::default()).unwrap();
+        assert_eq!(report.count, 4);
+        assert!(!report.is_ok());
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, 2);
+        assert!(matches!(report.errors[0].1, Error::Tarantool(_)));
+        assert_eq!(space.len().unwrap(), 5);
+
+        // Failed tuples don't abort the chunk transaction.
+        let options = BulkOptions {
+            chunk_size: Some(3),
+            yield_between_chunks: true,
+        };
+        let rows = (4..=10).map(|i| (i, format!("v{}", i)));
+        let report = space.insert_many(rows, &options).unwrap();
+        assert_eq!(report.count, 5);
+        let failed: Vec<_> = report.errors.iter().map(|(i, _)| *i).collect();
+        assert_eq!(failed, [0, 1]);
+        assert_eq!(space.len().unwrap(), 10);
+
+        let rows = (9..=12).map(|i| (i, "new"));
+        let report = space.replace_many(rows, &options).unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.count, 4);
+        assert_eq!(space.len().unwrap(), 12);
+        let t = space.get(&(9,)).unwrap().unwrap();
+        assert_eq!(t.field::<String>(1).unwrap().unwrap(), "new");
+
+        // Chunked transactions can't be nested.
+        crate::transaction::transaction(|| -> Result<(), Error> {
+            let e = space.insert_many([(100, "a")], &options).unwrap_err();
+            assert_eq!(e.report.count, 0);
+            assert!(space
+                .insert_many([(100, "a")], &BulkOptions::default())
+                .is_ok());
+            Ok(())
+        })
+        .unwrap();
+
+        // The report of the committed chunks is returned along with the error.
+        // A yield aborts the memtx transaction of the second chunk.
+        let rows = (201..=206).map(|i| {
+            if i == 205 {
+                crate::fiber::reschedule();
+            }
+            (i, "x")
+        });
+        let e = space.insert_many(rows, &options).unwrap_err();
+        assert_eq!(e.report.count, 3);
+        assert!(matches!(Error::from(e), Error::Tarantool(_)));
+        assert!(space.get(&(203,)).unwrap().is_some());
+        assert!(space.get(&(204,)).unwrap().is_none());
+
+        space.drop().unwrap();
+
+        // Without chunks the fiber yields after every tuple. The space is
+        // temporary, so that the inserts don't yield waiting for the WAL.
+        let space = Space::builder(&space_name)
+            .is_temporary(true)
+            .create()
+            .unwrap();
+        space.index_builder("pk").create().unwrap();
+        let options = BulkOptions {
+            chunk_size: None,
+            yield_between_chunks: true,
+        };
+        let csw = crate::fiber::csw();
+        let report = space
+            .insert_many([(1, "a"), (2, "b"), (3, "c")], &options)
+            .unwrap();
+        assert_eq!(report.count, 3);
+        assert_eq!(crate::fiber::csw(), csw + 2);
+
+        space.drop().unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn upgrade() {
+        let space_name = crate::temp_space_name!();
+        let space = Space::builder(&space_name).create().unwrap();
+        space.index_builder("pk").create().unwrap();
+        for i in 0..10 {
+            space.insert(&(i, i * 10)).unwrap();
+        }
+
+        // The error aborts the upgrade, but the committed batches are kept.
+        let mut reports = vec![];
+        let res = space
+            .upgrade("double")
+            .batch_size(3)
+            .on_progress(|p| reports.push(*p))
+            .run(|t| {
+                let (id, value): (u32, u32) = t.decode()?;
+                if id == 7 {
+                    return Err(std::io::Error::new(std::io::ErrorKind::Other, "stop").into());
+                }
+                Ok((id % 2 == 0).then(|| (id, value * 2)))
+            });
+        assert!(res.is_err());
+        assert_eq!(reports.len(), 2);
+        assert_eq!(
+            reports[1],
+            UpgradeProgress {
+                processed: 6,
+                updated: 3,
+                total: 10
+            }
+        );
+        let t = space.get(&(4,)).unwrap().unwrap();
+        assert_eq!(t.decode::<(u32, u32)>().unwrap(), (4, 80));
+        let t = space.get(&(6,)).unwrap().unwrap();
+        assert_eq!(t.decode::<(u32, u32)>().unwrap(), (6, 60));
+        let progress_space = Space::find(Upgrade::PROGRESS_SPACE).unwrap();
+        let record = progress_space.get(&(space.id(), "double")).unwrap();
+        assert!(record.is_some());
+        let is_local: bool = crate::lua_state()
+            .eval_with("return box.space[...].is_local", Upgrade::PROGRESS_SPACE)
+            .unwrap();
+        assert!(is_local);
+
+        // The upgrade resumes after the last committed batch.
+        let mut seen = vec![];
+        let progress = space
+            .upgrade("double")
+            .batch_size(3)
+            .run(|t| {
+                let (id, value): (u32, u32) = t.decode()?;
+                seen.push(id);
+                Ok((id % 2 == 0).then(|| (id, value * 2)))
+            })
+            .unwrap();
+        assert_eq!(seen, [6, 7, 8, 9]);
+        assert_eq!(progress.processed, 10);
+        assert_eq!(progress.updated, 5);
+        let t = space.get(&(6,)).unwrap().unwrap();
+        assert_eq!(t.decode::<(u32, u32)>().unwrap(), (6, 120));
+        let record = progress_space.get(&(space.id(), "double")).unwrap();
+        assert!(record.is_none());
+
+        // Changing the primary key is not allowed.
+        let res = space.upgrade("pk").run(|t| {
+            let (id, value): (u32, u32) = t.decode()?;
+            Ok(Some((id + 100, value)))
+        });
+        assert!(res.is_err());
+        assert_eq!(space.len().unwrap(), 10);
+        assert!(space.get(&(100,)).unwrap().is_none());
+        space.upgrade("pk").reset().unwrap();
+
+        // The fiber sleeps between the batches, but not after the last one.
+        let delay = Duration::from_millis(10);
+        let start = std::time::Instant::now();
+        let progress = space
+            .upgrade("noop")
+            .batch_size(3)
+            .delay(delay)
+            .run(|_| Ok(None::<()>))
+            .unwrap();
+        assert_eq!(progress.processed, 10);
+        assert!(start.elapsed() >= delay * 3);
+
+        space.drop().unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn upgrade_vinyl() {
+        let space_name = crate::temp_space_name!();
+        let space = Space::builder(&space_name)
+            .engine(SpaceEngineType::Vinyl)
+            .create()
+            .unwrap();
+        space.index_builder("pk").create().unwrap();
+        for i in 0..10 {
+            space.insert(&(i, i * 10)).unwrap();
+        }
+
+        let mut reports = vec![];
+        let progress = space
+            .upgrade("double")
+            .batch_size(4)
+            .on_progress(|p| reports.push(*p))
+            .run(|t| {
+                let (id, value): (u32, u32) = t.decode()?;
+                Ok((id % 2 == 0).then(|| (id, value * 2)))
+            })
+            .unwrap();
+        assert_eq!(
+            progress,
+            UpgradeProgress {
+                processed: 10,
+                updated: 5,
+                total: 10
+            }
+        );
+        assert_eq!(reports.len(), 3);
+        for i in 0..10 {
+            let t = space.get(&(i,)).unwrap().unwrap();
+            let expected = if i % 2 == 0 { i * 20 } else { i * 10 };
+            assert_eq!(t.decode::<(u32, u32)>().unwrap(), (i, expected));
+        }
+        let progress_space = Space::find(Upgrade::PROGRESS_SPACE).unwrap();
+        let record = progress_space.get(&(space.id(), "double")).unwrap();
+        assert!(record.is_none());
+
+        // The progress can be stored in a space provided by the caller.
+        let custom_name = crate::temp_space_name!();
+        let custom = Space::builder(&custom_name)
+            .field(Field::unsigned("space_id"))
+            .field(Field::string("name"))
+            .field(Field::unsigned("processed"))
+            .field(Field::unsigned("updated"))
+            .field(Field::varbinary("last_key"))
+            .create()
+            .unwrap();
+        custom
+            .index_builder("pk")
+            .parts(["space_id", "name"])
+            .create()
+            .unwrap();
+        let mut records = vec![];
+        let progress = space
+            .upgrade("noop")
+            .batch_size(4)
+            .progress_space(custom.clone())
+            .on_progress(|_| records.push(custom.len().unwrap()))
+            .run(|_| Ok(None::<()>))
+            .unwrap();
+        assert_eq!(progress.processed, 10);
+        assert_eq!(records, [1, 1, 1]);
+        assert_eq!(custom.len().unwrap(), 0);
+
+        custom.drop().unwrap();
+        space.drop().unwrap();
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn constraints_and_foreign_keys() {
+        use crate::schema::function::{create_function, drop_function, FuncOptions};
+
+        // Constraints & foreign keys are supported since tarantool 2.10.
+        let has_constraints: bool = crate::lua_state()
+            .eval(
+                "local major, minor = _TARANTOOL:match('^(%d+)%.(%d+)')
+                return tonumber(major) * 100 + tonumber(minor) >= 210",
+            )
+            .unwrap();
+        if !has_constraints {
+            return;
+        }
+
+        // Clean up after a previous failed run.
+        let parent_name = "test_constraints_parent";
+        let child_name = "test_constraints_child";
+        for name in [child_name, parent_name] {
+            if let Some(space) = Space::find(name) {
+                space.drop().unwrap();
+            }
+        }
+        for name in ["test_check_positive", "test_check_tuple"] {
+            if crate::schema::function::function_id(name)
+                .unwrap()
+                .is_some()
+            {
+                drop_function(name).unwrap();
+            }
+        }
+
+        let func_opts = |body: &str| FuncOptions {
+            body: Some(body.into()),
+            is_deterministic: Some(true),
+            ..Default::default()
+        };
+        create_function(
+            "test_check_positive",
+            &func_opts("function(value) return value > 0 end"),
+        )
+        .unwrap();
+        create_function(
+            "test_check_tuple",
+            &func_opts("function(t) return t[2] ~= t[3] end"),
+        )
+        .unwrap();
+
+        let parent = Space::builder(parent_name)
+            .field(Field::unsigned("id"))
+            .field(Field::string("region"))
+            .create()
+            .unwrap();
+        parent
+            .index_builder("pk")
+            .parts(["id", "region"])
+            .create()
+            .unwrap();
+        parent.insert(&(1, "eu")).unwrap();
+
+        let child = Space::builder(child_name)
+            .field(Field::unsigned("id"))
+            .field(Field::unsigned("parent_id"))
+            .field(Field::string("region"))
+            .field_constraint("parent_id", "positive", "test_check_positive")
+            .field_foreign_key(
+                "parent_id",
+                "parent_id",
+                FieldForeignKey::new(parent.id(), "id"),
+            )
+            .constraint("check", "test_check_tuple")
+            .foreign_key(
+                "parent",
+                ForeignKey::new(parent_name, [("parent_id", "id"), ("region", "region")]),
+            )
+            .create()
+            .unwrap();
+        child.index_builder("pk").create().unwrap();
+
+        child.insert(&(1, 1, "eu")).unwrap();
+        // Field constraint.
+        assert!(child.insert(&(2, 0, "eu")).is_err());
+        // Field foreign key.
+        assert!(child.insert(&(3, 2, "eu")).is_err());
+        // Tuple foreign key.
+        assert!(child.insert(&(4, 1, "us")).is_err());
+        assert_eq!(child.len().unwrap(), 1);
+
+        let func_id = |name| crate::schema::function::function_id(name).unwrap().unwrap();
+        let meta = child.meta().unwrap();
+        assert_eq!(
+            meta.format[1]["constraint"],
+            rmpv::Value::Map(vec![(
+                "positive".into(),
+                func_id("test_check_positive").into()
+            )])
+        );
+        assert_eq!(
+            meta.format[1]["foreign_key"],
+            rmpv::Value::Map(vec![(
+                "parent_id".into(),
+                rmpv::Value::Map(vec![
+                    ("space".into(), parent.id().into()),
+                    ("field".into(), "id".into()),
+                ]),
+            )])
+        );
+        assert_eq!(
+            meta.flags["constraint"],
+            rmpv::Value::Map(vec![("check".into(), func_id("test_check_tuple").into())])
+        );
+        assert_eq!(child.format().unwrap()[1], Field::unsigned("parent_id"));
+
+        assert!(Space::builder("test_constraints_bad_field")
+            .field(Field::unsigned("id"))
+            .field_constraint("no_such_field", "positive", "test_check_positive")
+            .create()
+            .is_err());
+
+        // Constraints aren't a part of `SpaceCreateOptions`.
+        let (name, _, constraints) = Space::builder("test_constraints_parts")
+            .constraint("check", "test_check_tuple")
+            .into_parts_with_constraints();
+        assert_eq!(name, "test_constraints_parts");
+        assert_eq!(constraints.tuple.len(), 1);
+        assert!(constraints.field.is_empty());
+        let (name, _) = Space::builder("test_constraints_parts").into_parts();
+        assert_eq!(name, "test_constraints_parts");
+
+        child.drop().unwrap();
+        parent.drop().unwrap();
+        drop_function("test_check_positive").unwrap();
+        drop_function("test_check_tuple").unwrap();
+    }
 }
@@ -0,0 +1,463 @@
+//! Box: statistics
+//!
+//! Use [`metrics`] to fetch all of the instance wide statistics at once, e.g.
+//! for exporting them to a monitoring system.
+//!
+//! Storage engine statistics are returned by [`vinyl`] for vinyl and by
+//! [`slab`] for memtx, which has no per-index statistics.
+//!
+//! See also:
+//! - [Lua reference: Submodule box.stat](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_stat/)
+//! - [Lua reference: Submodule box.info](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_info/)
+//! - [Lua reference: Submodule box.slab](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_slab/)
+//! - [Index statistics](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_index/stat/),
+//!   see [`Index::stat`]
+//!
+//! [`Index::stat`]: crate::index::Index::stat
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::vclock::{Lsn, Vclock};
+
+/// Number of rows and bytes processed by an operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RowsBytes {
+    pub rows: u64,
+    pub bytes: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Metrics
+////////////////////////////////////////////////////////////////////////////////
+
+const INFO: &str = "(function()
+    local info = box.info
+    return {
+        version = info.version, id = info.id, uuid = info.uuid,
+        pid = info.pid, ro = info.ro, status = info.status,
+        uptime = info.uptime, lsn = info.lsn, signature = info.signature,
+        vclock = info.vclock,
+        cluster_uuid = (info.replicaset or info.cluster or {}).uuid,
+        election = info.election,
+    }
+end)()";
+
+const REQUESTS: &str = "box.stat()";
+
+const NET: &str = "box.stat.net()";
+
+const SLAB: &str = "(function()
+    local slab = box.slab.info()
+    for _, k in ipairs({'items_used_ratio', 'quota_used_ratio', 'arena_used_ratio'}) do
+        slab[k] = tonumber((tostring(slab[k]):gsub('%%', '')))
+    end
+    return slab
+end)()";
+
+const RUNTIME: &str = "box.runtime.info()";
+
+/// Returns all of the instance wide statistics fetched at once.
+///
+/// This is cheaper than calling [`info`], [`requests`], [`net`], [`slab`] and
+/// [`runtime`] one by one.
+#[inline]
+pub fn metrics() -> Result<Metrics, Error> {
+    crate::util::lua_eval_decode(&format!(
+        "{{info = {}, requests = {}, net = {}, slab = {}, runtime = {}}}",
+        INFO, REQUESTS, NET, SLAB, RUNTIME
+    ))
+}
+
+/// Returns general information about the instance, see
+/// [box.info](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_info/).
+///
+/// See also [`replication`](crate::replication) for the replication status.
+#[inline]
+pub fn info() -> Result<Info, Error> {
+    crate::util::lua_eval_decode(INFO)
+}
+
+/// Returns request statistics, see
+/// [box.stat()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_stat/stat/).
+#[inline]
+pub fn requests() -> Result<RequestStat, Error> {
+    crate::util::lua_eval_decode(REQUESTS)
+}
+
+/// Returns network statistics, see
+/// [box.stat.net()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_stat/net/).
+#[inline]
+pub fn net() -> Result<NetStat, Error> {
+    crate::util::lua_eval_decode(NET)
+}
+
+/// Returns memtx memory usage statistics, see
+/// [box.slab.info()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_slab/slab_info/).
+#[inline]
+pub fn slab() -> Result<SlabInfo, Error> {
+    crate::util::lua_eval_decode(SLAB)
+}
+
+/// Returns Lua and runtime memory usage statistics, see
+/// [box.runtime.info()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_info/runtime/).
+#[inline]
+pub fn runtime() -> Result<RuntimeInfo, Error> {
+    crate::util::lua_eval_decode(RUNTIME)
+}
+
+/// All of the instance wide statistics, see [`metrics`].
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Metrics {
+    pub info: Info,
+    pub requests: RequestStat,
+    pub net: NetStat,
+    pub slab: SlabInfo,
+    pub runtime: RuntimeInfo,
+}
+
+/// General information about the instance, see [`info`].
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Info {
+    /// Tarantool version.
+    pub version: String,
+    /// Replica id, `None` if the instance is not registered in the replica
+    /// set yet.
+    pub id: Option<u32>,
+    pub uuid: String,
+    pub cluster_uuid: String,
+    pub pid: u32,
+    pub ro: bool,
+    /// Instance status, e.g. `"running"`, `"loading"` or `"orphan"`.
+    pub status: String,
+    /// Seconds since the instance started.
+    pub uptime: u64,
+    /// Log sequence number of the last entry in the local WAL, `-1` if the
+    /// instance is not registered in the replica set, e.g. an anonymous
+    /// replica.
+    pub lsn: i64,
+    /// Sum of all of the vclock components.
+    pub signature: Lsn,
+    pub vclock: Vclock,
+    /// Leader election state, only for Tarantool >= 2.6.
+    pub election: Option<ElectionInfo>,
+}
+
+/// Leader election state, see [`Info::election`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ElectionInfo {
+    /// Election state of the instance: `"follower"`, `"candidate"` or
+    /// `"leader"`.
+    pub state: String,
+    pub term: u64,
+    /// Id of the instance the current instance voted for in the current term,
+    /// `0` if it didn't vote.
+    pub vote: u32,
+    /// Id of the current leader, `0` if it is unknown.
+    pub leader: u32,
+}
+
+/// Counter of events along with the number of events per second averaged
+/// over the last 5 seconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Rate {
+    pub total: u64,
+    pub rps: u64,
+    /// Number of events in progress, e.g. active connections. Only for some
+    /// of the [`NetStat`] counters.
+    pub current: u64,
+}
+
+/// Number of requests of each type, see [`requests`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "UPPERCASE")]
+pub struct RequestStat {
+    pub select: Rate,
+    pub insert: Rate,
+    pub replace: Rate,
+    pub update: Rate,
+    pub upsert: Rate,
+    pub delete: Rate,
+    pub call: Rate,
+    pub eval: Rate,
+    pub auth: Rate,
+    pub prepare: Rate,
+    pub execute: Rate,
+    pub begin: Rate,
+    pub commit: Rate,
+    pub rollback: Rate,
+    /// Number of failed requests.
+    pub error: Rate,
+}
+
+/// Network statistics, see [`net`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "UPPERCASE")]
+pub struct NetStat {
+    /// Bytes sent.
+    pub sent: Rate,
+    /// Bytes received.
+    pub received: Rate,
+    pub connections: Rate,
+    pub requests: Rate,
+    /// Requests being processed, only for Tarantool >= 2.10.
+    pub requests_in_progress: Rate,
+}
+
+/// Memtx memory usage in bytes, see [`slab`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SlabInfo {
+    /// Memory allocated for tuples and indexes.
+    pub items_size: u64,
+    /// Memory used by tuples and indexes.
+    pub items_used: u64,
+    /// `items_used / items_size` in percent.
+    pub items_used_ratio: f64,
+    /// The `memtx_memory` limit.
+    pub quota_size: u64,
+    /// Memory used by slab allocator.
+    pub quota_used: u64,
+    /// `quota_used / quota_size` in percent.
+    pub quota_used_ratio: f64,
+    /// Memory allocated for slabs.
+    pub arena_size: u64,
+    /// Memory used by slabs.
+    pub arena_used: u64,
+    /// `arena_used / arena_size` in percent.
+    pub arena_used_ratio: f64,
+}
+
+/// Runtime memory usage in bytes, see [`runtime`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RuntimeInfo {
+    /// Memory used by the Lua runtime.
+    pub lua: u64,
+    /// Memory used by the runtime arena.
+    pub used: u64,
+    /// Maximum memory the runtime arena can allocate.
+    pub maxalloc: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Vinyl
+////////////////////////////////////////////////////////////////////////////////
+
+/// Returns the statistics of the vinyl storage engine.
+///
+/// See [box.stat.vinyl()](https://www.tarantool.io/en/doc/latest/reference/reference_lua/box_stat/vinyl/).
+#[inline]
+pub fn vinyl() -> Result<VinylStat, Error> {
+    crate::util::lua_eval_decode("box.stat.vinyl()")
+}
+
+/// Statistics of the vinyl storage engine, see [`vinyl`].
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct VinylStat {
+    pub tx: VinylTxStat,
+    pub memory: VinylMemoryStat,
+    pub disk: VinylDiskStat,
+    pub regulator: VinylRegulatorStat,
+    pub scheduler: VinylSchedulerStat,
+}
+
+/// Transaction statistics, see [`VinylStat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct VinylTxStat {
+    /// Number of transactions aborted due to a conflict.
+    pub conflict: u64,
+    /// Number of committed transactions.
+    pub commit: u64,
+    /// Number of rolled back transactions.
+    pub rollback: u64,
+    /// Number of statements in active transactions.
+    pub statements: u64,
+    /// Number of active transactions.
+    pub transactions: u64,
+    /// Number of gap locks held by active transactions.
+    pub gap_locks: u64,
+    /// Number of open read views.
+    pub read_views: u64,
+}
+
+/// Memory usage in bytes, see [`VinylStat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct VinylMemoryStat {
+    pub tuple_cache: u64,
+    pub tx: u64,
+    pub level0: u64,
+    pub page_index: u64,
+    pub bloom_filter: u64,
+}
+
+/// Disk usage in bytes, see [`VinylStat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct VinylDiskStat {
+    pub data: u64,
+    pub index: u64,
+    pub data_compacted: u64,
+}
+
+/// Write rate regulator statistics, see [`VinylStat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct VinylRegulatorStat {
+    /// Estimated average dump rate in bytes per second.
+    pub dump_bandwidth: f64,
+    /// Memory level at which a dump is triggered.
+    pub dump_watermark: u64,
+    /// Average rate at which recent writes occurred in bytes per second.
+    pub write_rate: f64,
+    /// Write rate limit in bytes per second.
+    pub rate_limit: f64,
+    /// Number of fibers blocked waiting for the memory to be freed.
+    pub blocked_writers: u64,
+}
+
+/// Dump & compaction scheduler statistics, see [`VinylStat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct VinylSchedulerStat {
+    pub tasks_inprogress: u64,
+    pub tasks_completed: u64,
+    pub tasks_failed: u64,
+    pub dump_count: u64,
+    /// Total time spent on dumps in seconds.
+    pub dump_time: f64,
+    pub dump_input: u64,
+    pub dump_output: u64,
+    /// Total time spent on compaction in seconds.
+    pub compaction_time: f64,
+    pub compaction_input: u64,
+    pub compaction_output: u64,
+    /// Size of data waiting for compaction in bytes.
+    pub compaction_queue: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Index
+////////////////////////////////////////////////////////////////////////////////
+
+/// Index statistics returned by [`Index::stat`].
+///
+/// Only vinyl indexes provide the statistics, for memtx indexes all the values
+/// are zero.
+///
+/// [`Index::stat`]: crate::index::Index::stat
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct IndexStat {
+    /// Total number of rows in memory and on disk.
+    pub rows: u64,
+    /// Total size of rows in memory and on disk.
+    pub bytes: u64,
+    /// Number of lookups.
+    pub lookup: u64,
+    /// Rows returned by lookups.
+    pub get: RowsBytes,
+    /// Rows written.
+    pub put: RowsBytes,
+    /// Rows skipped by read iterators.
+    pub skip: RowsBytes,
+    pub memory: IndexMemoryStat,
+    pub disk: IndexDiskStat,
+    pub cache: IndexCacheStat,
+    pub range_count: u64,
+    pub run_count: u64,
+    /// Average number of runs per range.
+    pub run_avg: u64,
+    /// Number of runs per range, e.g. `"[1]:10 [2]:3"`.
+    pub run_histogram: String,
+}
+
+/// In-memory level statistics, see [`IndexStat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct IndexMemoryStat {
+    pub rows: u64,
+    pub bytes: u64,
+    pub index_size: u64,
+}
+
+/// On-disk statistics, see [`IndexStat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct IndexDiskStat {
+    pub rows: u64,
+    pub bytes: u64,
+    pub bytes_compressed: u64,
+    /// Size of page index in bytes.
+    pub index_size: u64,
+    /// Size of bloom filters in bytes.
+    pub bloom_size: u64,
+    pub pages: u64,
+    pub dump: IndexTaskStat,
+    pub compaction: IndexTaskStat,
+}
+
+/// Dump or compaction statistics, see [`IndexDiskStat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct IndexTaskStat {
+    pub count: u64,
+    /// Total time spent in seconds.
+    pub time: f64,
+    pub input: RowsBytes,
+    pub output: RowsBytes,
+    /// Data waiting to be processed. Only for compaction.
+    pub queue: RowsBytes,
+}
+
+/// Tuple cache statistics, see [`IndexStat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct IndexCacheStat {
+    pub rows: u64,
+    pub bytes: u64,
+    pub index_size: u64,
+    pub lookup: u64,
+    pub get: RowsBytes,
+    pub put: RowsBytes,
+    pub invalidate: RowsBytes,
+    pub evict: RowsBytes,
+}
+
+#[cfg(feature = "internal_test")]
+mod tests {
+    use super::*;
+
+    #[crate::test(tarantool = "crate")]
+    fn fetch_metrics() {
+        let m = metrics().unwrap();
+        assert_eq!(m.info.status, "running");
+        assert_eq!(m.info.id, Some(crate::replication::instance_id().unwrap()));
+        assert_eq!(m.info.uuid, crate::replication::instance_uuid().unwrap());
+        assert_eq!(m.info.signature, m.info.vclock.sum());
+        assert!(m.slab.quota_size > 0);
+        assert!(m.slab.quota_used_ratio > 0.0);
+        assert!(m.runtime.lua > 0);
+
+        let space_name = crate::temp_space_name!();
+        let space = crate::space::Space::builder(&space_name).create().unwrap();
+        space.index_builder("pk").create().unwrap();
+        let before = requests().unwrap();
+        space.insert(&(1,)).unwrap();
+        let after = requests().unwrap();
+        assert_eq!(after.insert.total, before.insert.total + 1);
+        space.drop().unwrap();
+
+        assert_eq!(info().unwrap().uuid, m.info.uuid);
+        assert!(net().unwrap().received.total >= m.net.received.total);
+        assert_eq!(slab().unwrap().quota_size, m.slab.quota_size);
+        assert!(runtime().unwrap().lua > 0);
+    }
+}
@@ -59,6 +59,35 @@ where
     Ok(rmp_serde::to_vec(val)?)
 }
 
+/// Evaluate a lua expression and decode its value via msgpack.
+///
+/// Unlike reading the value with [`tlua::LuaRead`] this allows using
+/// `#[serde(default)]` for the fields which are missing in some versions of
+/// tarantool.
+pub(crate) fn lua_eval_decode<T>(expr: &str) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let code = format!("return require('msgpack').encode({})", expr);
+    let data: tlua::AnyLuaString = crate::lua_state().eval(&code)?;
+    Ok(rmp_serde::from_slice(data.as_bytes())?)
+}
+
+/// Same as [`lua_eval_decode`], but `args` are passed to the lua expression
+/// as `...`.
+pub(crate) fn lua_eval_decode_with<T, A>(expr: &str, args: A) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+    A: tlua::PushInto<tlua::LuaState>,
+    A::Err: Into<tlua::Void>,
+{
+    let code = format!("return require('msgpack').encode({})", expr);
+    let data: tlua::AnyLuaString = crate::lua_state()
+        .eval_with(&code, args)
+        .map_err(tlua::LuaError::from)?;
+    Ok(rmp_serde::from_slice(data.as_bytes())?)
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, tlua::Push, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum NumOrStr {
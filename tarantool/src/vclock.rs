@@ -23,11 +23,15 @@
 ///
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
 use std::num::NonZeroI32;
+use std::time::{Duration, Instant};
 
+use serde::de::{Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use tlua::{Push, PushInto, PushOne, PushOneInto, Void};
 
+use crate::error::{Error, TarantoolErrorCode};
 use crate::lua_state;
 use crate::tlua::{AsLua, LuaRead, ReadResult};
 
@@ -74,7 +78,7 @@ pub type Lsn = u64;
 /// assert!(vc1.partial_cmp(&vc2).is_none());
 /// ```
 ///
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
 pub struct Vclock(HashMap<usize, Lsn>);
 
 impl Vclock {
@@ -124,7 +128,6 @@ impl Vclock {
     /// ```
     ///
     pub fn ignore_zero(mut self) -> Self {
-        println!("{self:?}");
         self.0.remove(&0);
         self
     }
@@ -158,6 +161,122 @@ impl Vclock {
     pub fn get(&self, index: usize) -> Lsn {
         self.0.get(&index).copied().unwrap_or(0)
     }
+
+    /// Sets an [`Lsn`] at `index`. Setting it to zero removes the component.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use tarantool::vclock::Vclock;
+    /// let mut vc = Vclock::from([0, 10]);
+    /// vc.set(2, 20);
+    /// vc.set(1, 0);
+    /// assert_eq!(vc, Vclock::from([0, 0, 20]));
+    /// ```
+    pub fn set(&mut self, index: usize, lsn: Lsn) {
+        if lsn == 0 {
+            self.0.remove(&index);
+        } else {
+            self.0.insert(index, lsn);
+        }
+    }
+
+    /// Updates every component to the maximum of its value in `self` and
+    /// `other`. The result is the least vclock which is `>=` both of them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use tarantool::vclock::Vclock;
+    /// let mut vc = Vclock::from([0, 10, 1]);
+    /// vc.merge(&Vclock::from([0, 2, 20, 3]));
+    /// assert_eq!(vc, Vclock::from([0, 10, 20, 3]));
+    /// ```
+    pub fn merge(&mut self, other: &Self) {
+        for (&index, &lsn) in &other.0 {
+            let entry = self.0.entry(index).or_insert(0);
+            *entry = (*entry).max(lsn);
+        }
+    }
+
+    /// Returns the sum of all components, also known as the vclock
+    /// signature. Signature grows with every change, so it can be used to
+    /// roughly estimate the distance between two vclocks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use tarantool::vclock::Vclock;
+    /// assert_eq!(Vclock::from([1, 10, 100]).sum(), 111);
+    /// ```
+    pub fn sum(&self) -> Lsn {
+        self.0.values().sum()
+    }
+
+    /// Returns the difference `self[i] - other[i]` for every component
+    /// which differs in `self` and `other`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use tarantool::vclock::Vclock;
+    /// # use std::collections::HashMap;
+    /// let diff = Vclock::from([0, 10, 5]).diff(&Vclock::from([0, 7, 5, 2]));
+    /// assert_eq!(diff, HashMap::from([(1, 3), (3, -2)]));
+    /// ```
+    pub fn diff(&self, other: &Self) -> HashMap<usize, i64> {
+        self.0
+            .keys()
+            .chain(other.0.keys())
+            .filter_map(|&i| {
+                let d = self.get(i) as i64 - other.get(i) as i64;
+                (d != 0).then(|| (i, d))
+            })
+            .collect()
+    }
+}
+
+/// Blocks the current fiber until the local vclock (`box.info.vclock`)
+/// catches up with `target`, i.e. becomes `>=` than `target`. The zero
+/// component is ignored in the comparison. Returns the reached vclock.
+///
+/// Tarantool has no trigger which fires when the vclock changes, so the
+/// vclock is polled with an exponential backoff starting at 1ms and capped at
+/// 100ms, i.e. the function may return up to 100ms after the vclock is
+/// reached.
+///
+/// Returns an error if the `timeout` is exceeded.
+pub fn wait_vclock(target: &Vclock, timeout: Duration) -> Result<Vclock, Error> {
+    const MIN_POLL_INTERVAL: Duration = Duration::from_millis(1);
+    const MAX_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    let target = target.clone().ignore_zero();
+    let deadline = Instant::now().checked_add(timeout);
+    let mut poll_interval = MIN_POLL_INTERVAL;
+    loop {
+        let current = Vclock::try_current()?;
+        if matches!(
+            current.clone().ignore_zero().partial_cmp(&target),
+            Some(Ordering::Greater | Ordering::Equal)
+        ) {
+            return Ok(current);
+        }
+        let now = Instant::now();
+        if matches!(deadline, Some(deadline) if now >= deadline) {
+            return Err(crate::set_and_get_error!(
+                TarantoolErrorCode::Timeout,
+                "timed out waiting for vclock {:?}",
+                target.0,
+            )
+            .into());
+        }
+        let sleep_for = match deadline {
+            Some(deadline) => poll_interval.min(deadline - now),
+            None => poll_interval,
+        };
+        crate::fiber::sleep(sleep_for);
+        poll_interval = (poll_interval * 2).min(MAX_POLL_INTERVAL);
+    }
 }
 
 impl<const N: usize> From<[Lsn; N]> for Vclock {
@@ -202,6 +321,54 @@ impl PartialOrd for Vclock {
     }
 }
 
+/// Vclock is deserialized either from a map `{id: lsn}` or from an array of
+/// lsns. The latter is how Lua encodes a vclock without the zero component,
+/// so the first element of the array corresponds to the replica id `1`.
+impl<'de> Deserialize<'de> for Vclock {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct VclockVisitor;
+
+        impl<'de> Visitor<'de> for VclockVisitor {
+            type Value = Vclock;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map {id: lsn} or an array of lsns")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Vclock, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut res = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some((index, lsn)) = map.next_entry::<usize, Lsn>()? {
+                    res.insert(index, lsn);
+                }
+                Ok(Vclock(res))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Vclock, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut res = HashMap::with_capacity(seq.size_hint().unwrap_or(0));
+                let mut index = 1;
+                while let Some(lsn) = seq.next_element::<Lsn>()? {
+                    if lsn != 0 {
+                        res.insert(index, lsn);
+                    }
+                    index += 1;
+                }
+                Ok(Vclock(res))
+            }
+        }
+
+        deserializer.deserialize_any(VclockVisitor)
+    }
+}
+
 impl<L> LuaRead<L> for Vclock
 where
     L: AsLua,
@@ -291,6 +458,52 @@ mod tests {
         assert!(Vclock::from([1, 10, 100]) > Vclock::from([1, 9, 88]));
     }
 
+    #[crate::test(tarantool = "crate")]
+    fn test_vclock_arithmetic() {
+        let mut vc = Vclock::from([5, 10, 0, 1]);
+        vc.merge(&Vclock::from([1, 20, 3]));
+        assert_eq!(vc, Vclock::from([5, 20, 3, 1]));
+        assert_eq!(vc.sum(), 29);
+
+        vc.set(0, 0);
+        vc.set(4, 4);
+        assert_eq!(vc, Vclock::from([0, 20, 3, 1, 4]));
+
+        let other = Vclock::from([7, 10, 3, 2]);
+        assert_eq!(
+            vc.diff(&other),
+            HashMap::from([(0, -7), (1, 10), (3, -1), (4, 4)])
+        );
+        assert_eq!(vc.diff(&vc), HashMap::new());
+        assert_eq!(Vclock::default().sum(), 0);
+    }
+
+    #[crate::test(tarantool = "crate")]
+    fn test_wait_vclock() {
+        let space_name = crate::temp_space_name!();
+        let space = crate::space::Space::builder(&space_name).create().unwrap();
+        space.index_builder("pk").create().unwrap();
+
+        let current = Vclock::current();
+        assert_eq!(
+            wait_vclock(&current, Duration::ZERO).unwrap().ignore_zero(),
+            current.clone().ignore_zero()
+        );
+
+        let mut target = current.ignore_zero();
+        for lsn in target.0.values_mut() {
+            *lsn += 1;
+        }
+        let err = wait_vclock(&target, Duration::from_millis(10)).unwrap_err();
+        assert!(matches!(err, Error::Tarantool(_)), "{}", err);
+
+        let jh = crate::fiber::start(move || wait_vclock(&target, Duration::from_secs(10)));
+        space.insert(&(1,)).unwrap();
+        assert!(jh.join().is_ok());
+
+        space.drop().unwrap();
+    }
+
     #[crate::test(tarantool = "crate")]
     fn test_vclock_luaread() {
         let l = lua_state();
@@ -353,6 +566,11 @@ mod tests {
         // Serialize
         assert_eq!(rmp_serde::to_vec(&vc).unwrap(), mp);
 
+        // Lua encodes vclocks without the zero component as arrays
+        let mp = b"\x93\x01\x00\x03"; // {1, 0, 3}
+        let vc: Vclock = rmp_serde::from_slice(mp).unwrap();
+        assert_eq!(vc, Vclock::from([0, 1, 0, 3]));
+
         let invalid_mp = b"\x81\x00\xa0"; // {[0] = ""}
         let err: Result<Vclock, _> = rmp_serde::from_read_ref(invalid_mp);
         assert_eq!(
@@ -7,7 +7,6 @@ use tarantool::sequence::Sequence;
 use tarantool::space::UpdateOps;
 use tarantool::space::{self, Field, Space, SpaceCreateOptions, SpaceEngineType, SystemSpace};
 use tarantool::tuple::Tuple;
-use tarantool::util::Value;
 use tarantool::{update, upsert};
 
 use crate::common::{QueryOperation, S1Record, S2Key, S2Record};
@@ -878,34 +877,14 @@ pub fn space_create_is_sync() {
 
 pub fn space_meta() {
     fn assert_field(
-        field: &BTreeMap<Cow<'_, str>, Value>,
+        field: &BTreeMap<Cow<'_, str>, rmpv::Value>,
         name: &str,
         r#type: &str,
         is_nullable: bool,
     ) {
-        assert!(matches!(field.get("is_nullable").unwrap(), Value::Bool(_)));
-        match field.get("is_nullable").unwrap() {
-            Value::Bool(nullable) => {
-                assert_eq!(*nullable, is_nullable);
-            }
-            _ => unreachable!(),
-        }
-
-        assert!(matches!(field.get("name").unwrap(), Value::Str(_)));
-        match field.get("name").unwrap() {
-            Value::Str(n) => {
-                assert_eq!(n.to_string(), name.to_string());
-            }
-            _ => unreachable!(),
-        }
-
-        assert!(matches!(field.get("type").unwrap(), Value::Str(_)));
-        match field.get("type").unwrap() {
-            Value::Str(t) => {
-                assert_eq!(t.to_string(), r#type.to_string());
-            }
-            _ => unreachable!(),
-        }
+        assert_eq!(field["is_nullable"], rmpv::Value::from(is_nullable));
+        assert_eq!(field["name"], rmpv::Value::from(name));
+        assert_eq!(field["type"], rmpv::Value::from(r#type));
     }
 
     let opts = SpaceCreateOptions {
@@ -928,7 +907,7 @@ pub fn space_meta() {
 
     assert_eq!(meta.name, "new_space_9");
     assert_eq!(meta.engine, SpaceEngineType::Memtx);
-    assert!(matches!(meta.flags.get("group_id").unwrap(), Value::Num(1)));
+    assert_eq!(meta.flags["group_id"], rmpv::Value::from(1));
 
     assert_field(meta.format.get(0).unwrap(), "f1", "unsigned", false);
     assert_field(meta.format.get(1).unwrap(), "f2", "boolean", false);
@@ -945,14 +924,8 @@ pub fn space_meta() {
     let meta = space.meta().expect("meta should exists");
 
     assert_eq!(meta.name, "new_space_10");
-    assert!(matches!(
-        meta.flags.get("temporary").unwrap(),
-        Value::Bool(true)
-    ));
-    assert!(matches!(
-        meta.flags.get("is_sync").unwrap(),
-        Value::Bool(true)
-    ));
+    assert_eq!(meta.flags["temporary"], rmpv::Value::from(true));
+    assert_eq!(meta.flags["is_sync"], rmpv::Value::from(true));
 }
 
 pub fn drop_space(name: &str) {
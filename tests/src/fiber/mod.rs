@@ -462,7 +462,10 @@ pub fn r#yield() {
 pub fn yield_canceled() {
     let mut fiber = Fiber::new("test_fiber", &mut |_| {
         fiber::sleep(Duration::from_millis(10));
-        assert!(fiber::r#yield().is_err());
+        assert!(matches!(
+            fiber::r#yield(),
+            Err(tarantool::error::Error::FiberCancelled)
+        ));
         0
     });
     fiber.set_joinable(true);
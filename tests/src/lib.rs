@@ -487,6 +487,7 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 net_box::ping_timeout,
                 net_box::ping_concurrent,
                 net_box::call,
+                net_box::call_fiber_local,
                 net_box::call_async,
                 net_box::call_async_error,
                 net_box::call_async_disconnected,
@@ -500,10 +501,12 @@ fn run_tests(cfg: TestConfig) -> Result<bool, io::Error> {
                 net_box::is_connected,
                 net_box::schema_sync,
                 net_box::select,
+                net_box::select_page,
                 net_box::get,
                 net_box::insert,
                 net_box::replace,
                 net_box::update,
+                net_box::update_ops,
                 net_box::upsert,
                 net_box::delete,
                 net_box::cancel_recv,
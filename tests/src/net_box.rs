@@ -5,13 +5,13 @@ use std::time::Duration;
 
 use tarantool::error::Error;
 use tarantool::fiber::{reschedule, sleep, start_proc, Cond, Fiber};
-use tarantool::index::IteratorType;
+use tarantool::index::{After, IteratorType, SelectOptions};
 use tarantool::net_box::{promise::State, Conn, ConnOptions, ConnTriggers, Options};
-use tarantool::space::Space;
-use tarantool::tuple::Tuple;
+use tarantool::space::{FieldPath, Space, UpdateOps};
+use tarantool::tuple::{ToTupleBuffer, Tuple};
 
 use crate::{
-    common::{QueryOperation, S1Record, S2Record},
+    common::{lib_name, QueryOperation, S1Record, S2Record},
     LISTEN,
 };
 use std::cell::{Cell, RefCell};
@@ -126,6 +126,65 @@ pub fn call() {
     assert_eq!(result.unwrap().decode::<(i32,)>().unwrap(), (3,));
 }
 
+pub fn call_fiber_local() {
+    thread_local! {
+        static DROPPED: Cell<u32> = Cell::new(0);
+    }
+
+    struct TraceId(u64);
+
+    impl Drop for TraceId {
+        fn drop(&mut self) {
+            DROPPED.with(|d| d.set(d.get() + 1));
+        }
+    }
+
+    tarantool::fiber_local! {
+        static TRACE_ID: RefCell<Option<TraceId>> = RefCell::new(None);
+    }
+
+    #[tarantool::proc]
+    fn proc_fiber_local(id: u64) -> Option<u64> {
+        let old = TRACE_ID.with_borrow(|t| t.as_ref().map(|t| t.0));
+        TRACE_ID.set(Some(TraceId(id)));
+        old
+    }
+
+    let name = format!("{}.proc_fiber_local", lib_name());
+    tarantool::lua_state()
+        .exec_with(
+            "box.schema.func.create(..., {language = 'C', if_not_exists = true})",
+            name.as_str(),
+        )
+        .unwrap();
+
+    // The request fiber's storage is released after the request, so the
+    // value is dropped once the storage is garbage collected.
+    let wait_dropped = |count| {
+        for _ in 0..100 {
+            tarantool::lua_state().exec("collectgarbage()").unwrap();
+            if DROPPED.with(Cell::get) == count {
+                return;
+            }
+            sleep(Duration::from_millis(10));
+        }
+        panic!("fiber-local value wasn't dropped after the request");
+    };
+
+    let conn = test_user_conn();
+    for (i, id) in [1, 2].iter().enumerate() {
+        let result = conn.call(&name, &(*id,), &Options::default()).unwrap();
+        // The value of the previous request was dropped along with its
+        // storage, so it isn't seen even if the same fiber handles this one.
+        assert_eq!(result.unwrap().decode::<(Option<u64>,)>().unwrap(), (None,));
+        wait_dropped(i as u32 + 1);
+    }
+
+    tarantool::lua_state()
+        .exec_with("box.schema.func.drop(...)", name.as_str())
+        .unwrap();
+}
+
 pub fn call_async() {
     let conn = test_user_conn();
     let p1 = conn
@@ -315,7 +374,8 @@ pub fn schema_sync() {
         &Options::default(),
     )
     .unwrap();
-    assert!(conn.space("test_s_tmp").unwrap().is_some());
+    let tmp = conn.space("test_s_tmp").unwrap().unwrap();
+    assert_eq!(tmp.format().unwrap(), []);
 
     conn.call(
         "test_schema_cleanup",
@@ -323,6 +383,9 @@ pub fn schema_sync() {
         &Options::default(),
     )
     .unwrap();
+    // Dropped spaces are removed from the schema cache.
+    assert!(conn.space("test_s_tmp").unwrap().is_none());
+    assert!(tmp.format().is_err());
 }
 
 pub fn get() {
@@ -371,6 +434,61 @@ pub fn select() {
     );
 }
 
+pub fn select_page() {
+    if !tarantool::ffi::has_iterator_position() {
+        return;
+    }
+    let conn = test_user_conn();
+    let space = conn.space("test_s2").unwrap().unwrap();
+    let index = space.primary_key();
+    let ids = |tuples: &[Tuple]| -> Vec<u32> {
+        tuples
+            .iter()
+            .map(|t| t.decode::<S2Record>().unwrap().id)
+            .collect()
+    };
+
+    let mut page_options = SelectOptions {
+        limit: Some(3),
+        ..Default::default()
+    };
+    let options = Options::default();
+    let page = index
+        .select_page(IteratorType::GE, &(5,), &page_options, &options)
+        .unwrap();
+    assert_eq!(ids(&page.tuples), [5, 6, 7]);
+
+    page_options.after = page.position.map(After::Position);
+    let page = index
+        .select_page(IteratorType::GE, &(5,), &page_options, &options)
+        .unwrap();
+    assert_eq!(ids(&page.tuples), [8, 9, 10]);
+
+    page_options.after = Some(After::Tuple((18,).to_tuple_buffer().unwrap()));
+    let page = index
+        .select_page(IteratorType::GE, &(5,), &page_options, &options)
+        .unwrap();
+    assert_eq!(ids(&page.tuples), [19, 20]);
+
+    page_options.after = page.position.map(After::Position);
+    let page = index
+        .select_page(IteratorType::GE, &(5,), &page_options, &options)
+        .unwrap();
+    assert!(page.tuples.is_empty());
+    assert!(page.position.is_none());
+
+    let after = After::Tuple((1,).to_tuple_buffer().unwrap());
+    let options = Options {
+        limit: Some(3),
+        ..Options::default()
+    };
+    let result: Vec<_> = index
+        .select_after(IteratorType::All, &(), &after, &options)
+        .unwrap()
+        .collect();
+    assert_eq!(ids(&result), [2, 3, 4]);
+}
+
 pub fn insert() {
     let local_space = Space::find("test_s1").unwrap();
     local_space.truncate().unwrap();
@@ -456,6 +574,42 @@ pub fn update() {
     assert_eq!(output.unwrap().decode::<S1Record>().unwrap().text, "New");
 }
 
+pub fn update_ops() {
+    let local_space = Space::find("test_s1").unwrap();
+    local_space.truncate().unwrap();
+
+    let input = S1Record {
+        id: 1,
+        text: "Original".to_string(),
+    };
+    local_space.insert(&input).unwrap();
+
+    let conn = test_user_conn();
+    let remote_space = conn.space("test_s1").unwrap().unwrap();
+    let format = remote_space.format().unwrap();
+    assert_eq!(format, local_space.format().unwrap());
+
+    let mut ops = UpdateOps::new();
+    ops.assign("text", "New").unwrap();
+    ops.validate(&format).unwrap();
+    let update_result = remote_space
+        .update(&(input.id,), ops.as_ref(), &Options::default())
+        .unwrap();
+    assert_eq!(
+        update_result.unwrap().decode::<S1Record>().unwrap().text,
+        "New"
+    );
+
+    let mut ops = UpdateOps::new();
+    ops.add("text", 1).unwrap();
+    assert!(ops.validate(&format).is_err());
+
+    let mut ops = UpdateOps::new();
+    ops.assign(FieldPath::field("no_such_field"), "New")
+        .unwrap();
+    assert!(ops.validate(&format).is_err());
+}
+
 pub fn upsert() {
     let local_space = Space::find("test_s1").unwrap();
     local_space.truncate().unwrap();